@@ -0,0 +1,153 @@
+//! Translation of values between AMF versions, so that they can be written in the other format
+
+use flash_lso::types::{AMFVersion, Element, Lso, Value};
+use std::collections::HashMap;
+use std::rc::Rc;
+
+/// Convert an lso so that it is written with the given AMF version, values are translated as follows
+///
+/// To AMF0:
+/// - `Integer` becomes `Number`, as AMF0 has no integer type
+/// - Dates without a timezone are given a timezone of 0 (UTC)
+/// - XML values are written as the single AMF0 XML type, so are read back as XMLString
+/// - The dense part of an `ECMAArray` becomes elements named by index, as AMF0 arrays only have named elements
+/// - Anonymous objects lose their (empty) class name, so they aren't written as typed objects
+/// - Values with no AMF0 equivalent (byte arrays, vectors, dictionaries and custom objects) are embedded as AMF3
+///
+/// To AMF3 values are converted with `Value::to_amf3`
+///
+/// Values that are shared (such as those read by reference) are converted once and stay shared
+pub(crate) fn convert(lso: &Lso, version: AMFVersion) -> Lso {
+    let body = match version {
+        AMFVersion::AMF0 => Converter {
+            converted: HashMap::new(),
+        }
+        .convert_elements(&lso.body),
+        // Converted as a single object so that values shared between elements stay shared
+        AMFVersion::AMF3 => match Value::Object(lso.body.clone(), None).to_amf3() {
+            Value::Object(elements, _) => elements,
+            _ => unreachable!("Objects stay objects"),
+        },
+    };
+    Lso::new(body, lso.header.name.clone(), version)
+}
+
+/// Converts values to AMF0
+struct Converter {
+    /// Values that have already been converted, keyed by the address of the original value
+    converted: HashMap<*const Value, Rc<Value>>,
+}
+
+impl Converter {
+    fn convert_elements(&mut self, elements: &[Element]) -> Vec<Element> {
+        elements
+            .iter()
+            .map(|e| Element {
+                name: e.name.clone(),
+                value: self.convert_rc(&e.value),
+            })
+            .collect()
+    }
+
+    fn convert_values(&mut self, values: &[Rc<Value>]) -> Vec<Rc<Value>> {
+        values.iter().map(|v| self.convert_rc(v)).collect()
+    }
+
+    fn convert_rc(&mut self, value: &Rc<Value>) -> Rc<Value> {
+        let ptr = Rc::as_ptr(value);
+        if let Some(converted) = self.converted.get(&ptr) {
+            return Rc::clone(converted);
+        }
+
+        let converted = self.amf0_value(value);
+        self.converted.insert(ptr, Rc::clone(&converted));
+        converted
+    }
+
+    fn amf0_value(&mut self, value: &Rc<Value>) -> Rc<Value> {
+        let converted = match value.as_ref() {
+            Value::Integer(i) => Value::Number(f64::from(*i)),
+            Value::Date(time, None) => Value::Date(*time, Some(0)),
+            Value::Object(elements, class_def) => Value::Object(
+                self.convert_elements(elements),
+                class_def.clone().filter(|def| !def.name.is_empty()),
+            ),
+            Value::StrictArray(items) => Value::StrictArray(self.convert_values(items)),
+            Value::ECMAArray(dense, assoc, _) => {
+                let mut elements: Vec<Element> = dense
+                    .iter()
+                    .enumerate()
+                    .map(|(index, v)| Element {
+                        name: index.to_string().into(),
+                        value: self.convert_rc(v),
+                    })
+                    .collect();
+                elements.extend(self.convert_elements(assoc));
+                Value::ECMAArray(Vec::new(), elements, dense.len() as u32)
+            }
+            Value::ByteArray(_)
+            | Value::VectorInt(_, _)
+            | Value::VectorUInt(_, _)
+            | Value::VectorDouble(_, _)
+            | Value::VectorObject(_, _, _)
+            | Value::Dictionary(_, _)
+            | Value::Custom(_, _, _) => Value::AMF3(Rc::clone(value)),
+            _ => return Rc::clone(value),
+        };
+        Rc::new(converted)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::convert;
+    use crate::dump::dump;
+    use flash_lso::read::Reader;
+    use flash_lso::types::{AMFVersion, Lso};
+    use flash_lso::write::to_bytes;
+
+    fn round_trip(lso: &Lso, version: AMFVersion) -> Lso {
+        let converted = convert(lso, version);
+        let bytes = to_bytes(&converted).expect("Unable to write converted lso");
+        let (_, reread) = Reader::default()
+            .parse(&bytes)
+            .expect("Unable to read converted lso");
+        assert_eq!(reread.header.format_version, version);
+        reread
+    }
+
+    /// The dumped tree without the header line, which includes the length
+    fn contents(lso: &Lso) -> String {
+        dump(lso, usize::MAX)
+            .lines()
+            .skip(1)
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    #[test]
+    fn amf0_to_amf3_and_back() {
+        let data = include_bytes!("../../flash-lso/tests/sol/AS2-Demo.sol");
+        let (_, sol) = Reader::default().parse(data).unwrap();
+
+        let amf3 = round_trip(&sol, AMFVersion::AMF3);
+        assert_eq!(amf3.body.len(), sol.body.len());
+        let amf0 = round_trip(&amf3, AMFVersion::AMF0);
+        // Only the timezones of dates are lost
+        assert_eq!(contents(&amf0), contents(&sol));
+    }
+
+    #[test]
+    fn amf3_to_amf0_keeps_values() {
+        let data = include_bytes!("../../flash-lso/tests/sol/AS3-Demo.sol");
+        let (_, sol) = Reader::default().parse(data).unwrap();
+
+        let amf0 = round_trip(&sol, AMFVersion::AMF0);
+        assert_eq!(amf0.body.len(), sol.body.len());
+        // Only the distinction between `Integer` and `Number` and between XMLString and XMLDocument is lost
+        assert_eq!(
+            contents(&round_trip(&amf0, AMFVersion::AMF3)),
+            contents(&sol)
+        );
+    }
+}
@@ -0,0 +1,27 @@
+//! Printing of lso contents as an indented tree
+
+use flash_lso::types::Lso;
+use std::fmt::Write;
+
+/// Render the body of an lso as a tree, with one line per value and the contents of containers indented below them
+/// Containers nested more than `max_depth` levels below an element are shown as `...`
+pub(crate) fn dump(lso: &Lso, max_depth: usize) -> String {
+    let mut out = String::new();
+    writeln!(
+        out,
+        "{} ({}, {} bytes)",
+        lso.header.name, lso.header.format_version, lso.header.length
+    )
+    .expect("Writing to a string can't fail");
+    for e in lso {
+        let tree = e.value().pretty(2, max_depth);
+        let mut lines = tree.lines();
+        if let Some(first) = lines.next() {
+            writeln!(out, "  {}: {}", e.name, first).expect("Writing to a string can't fail");
+        }
+        for line in lines {
+            writeln!(out, "  {}", line).expect("Writing to a string can't fail");
+        }
+    }
+    out
+}
@@ -0,0 +1,173 @@
+use std::fs;
+use std::process;
+
+use clap::{App, AppSettings, Arg, SubCommand};
+use flash_lso::errors::Error;
+use flash_lso::extra::*;
+use flash_lso::read::Reader;
+use flash_lso::types::{AMFVersion, Lso};
+use flash_lso::write::Writer;
+
+mod convert;
+mod dump;
+
+fn main() {
+    env_logger::init();
+
+    let input = || {
+        Arg::with_name("INPUT")
+            .help("The .sol file to read")
+            .required(true)
+    };
+
+    let matched = App::new("flash-lso-cli")
+        .version("0.1.0")
+        .author("CUB3D <callumthom11@gmail.com>")
+        .about("Inspect and convert Flash Local Shared Object (.sol) files")
+        .setting(AppSettings::SubcommandRequiredElseHelp)
+        .subcommand(
+            SubCommand::with_name("dump")
+                .about("Print the contents of a file as an indented tree")
+                .arg(
+                    Arg::with_name("max-depth")
+                        .long("max-depth")
+                        .help("How many levels of nested values to show below each element")
+                        .takes_value(true),
+                )
+                .arg(input()),
+        )
+        .subcommand(
+            SubCommand::with_name("json")
+                .about("Print the contents of a file as JSON")
+                .arg(input()),
+        )
+        .subcommand(
+            SubCommand::with_name("validate")
+                .about("Strictly parse a file, giving the offset of the first error")
+                .arg(input()),
+        )
+        .subcommand(
+            SubCommand::with_name("convert")
+                .about("Rewrite a file using a different AMF version")
+                .arg(
+                    Arg::with_name("to")
+                        .long("to")
+                        .help("The AMF version to write")
+                        .takes_value(true)
+                        .possible_values(&["amf0", "amf3"])
+                        .required(true),
+                )
+                .arg(input())
+                .arg(
+                    Arg::with_name("OUTPUT")
+                        .help("The .sol file to write")
+                        .required(true),
+                ),
+        )
+        .get_matches();
+
+    let result = match matched.subcommand() {
+        ("dump", Some(args)) => args
+            .value_of("max-depth")
+            .map_or(Ok(usize::MAX), |depth| {
+                depth
+                    .parse()
+                    .map_err(|_| format!("Invalid max depth: {}", depth))
+            })
+            .and_then(|max_depth| {
+                let sol = read_file(args.value_of("INPUT").unwrap())?;
+                print!("{}", dump::dump(&sol, max_depth));
+                Ok(())
+            }),
+        ("json", Some(args)) => read_file(args.value_of("INPUT").unwrap()).map(|sol| {
+            let json = serde_json::to_string_pretty(&sol).expect("Unable to encode lso as json");
+            println!("{}", json);
+        }),
+        ("validate", Some(args)) => validate(args.value_of("INPUT").unwrap()),
+        ("convert", Some(args)) => {
+            let version = match args.value_of("to").unwrap() {
+                "amf0" => AMFVersion::AMF0,
+                _ => AMFVersion::AMF3,
+            };
+            convert(
+                args.value_of("INPUT").unwrap(),
+                args.value_of("OUTPUT").unwrap(),
+                version,
+            )
+        }
+        _ => unreachable!("A subcommand is required"),
+    };
+
+    if let Err(message) = result {
+        eprintln!("{}", message);
+        process::exit(1);
+    }
+}
+
+fn new_reader() -> Reader {
+    let mut reader = Reader::default();
+    flex::read::register_decoders(&mut reader.amf3_decoder);
+    reader
+}
+
+fn new_writer() -> Writer {
+    let mut writer = Writer::default();
+    flex::write::register_encoders(&mut writer.amf3_encoder);
+    writer
+}
+
+fn describe_error(error: &Error<'_>) -> String {
+    match error {
+        Error::Nom(_, kind) => format!("{:?}", kind),
+        e => e.to_string(),
+    }
+}
+
+/// Read an lso file, which may be compressed as a whole
+fn read_file(path: &str) -> Result<Lso, String> {
+    let data = fs::read(path).map_err(|e| format!("Unable to read {}: {}", path, e))?;
+    new_reader()
+        .parse_maybe_compressed(&data)
+        .map(|(lso, _)| lso)
+        .map_err(|(error, offset)| {
+            format!(
+                "Couldn't parse {} at offset {:#x}: {}",
+                path,
+                offset,
+                describe_error(&error)
+            )
+        })
+}
+
+fn validate(path: &str) -> Result<(), String> {
+    let data = fs::read(path).map_err(|e| format!("Unable to read {}: {}", path, e))?;
+    let mut reader = new_reader();
+    reader.strict = true;
+    match reader.parse_with_offsets(&data) {
+        Ok(sol) => {
+            println!(
+                "{}: valid {} file with {} elements",
+                path,
+                sol.header.format_version,
+                sol.body.len()
+            );
+            Ok(())
+        }
+        Err((error, offset)) => Err(format!(
+            "{}: invalid at offset {:#x} ({}): {}",
+            path,
+            offset,
+            offset,
+            describe_error(&error)
+        )),
+    }
+}
+
+fn convert(input: &str, output: &str, version: AMFVersion) -> Result<(), String> {
+    let sol = read_file(input)?;
+    let converted = convert::convert(&sol, version);
+    let bytes = new_writer()
+        .to_bytes(&converted)
+        .map_err(|e| format!("Unable to write {}: {}", output, e))?;
+    fs::write(output, bytes).map_err(|e| format!("Unable to write {}: {}", output, e))
+}
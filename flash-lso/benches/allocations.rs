@@ -0,0 +1,75 @@
+//! Counts the heap allocations made while parsing a selection of files
+//! Run with `cargo bench --bench allocations`
+use flash_lso::read::Reader;
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::hint::black_box;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Wraps the system allocator, counting every allocation
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+macro_rules! count_allocations {
+    ($($path: expr),*) => {
+        $(
+            let input_bytes = include_bytes!(concat!("../tests/sol/", $path, ".sol"));
+            let before = ALLOCATIONS.load(Ordering::Relaxed);
+            black_box(Reader::default().parse(input_bytes).unwrap());
+            let count = ALLOCATIONS.load(Ordering::Relaxed) - before;
+            println!("parse_{}: {} allocations", $path, count);
+        )*
+    }
+}
+
+/// As `count_allocations`, but with `AMF3Decoder::intern_names` set so repeated names are shared
+macro_rules! count_interned_allocations {
+    ($($path: expr),*) => {
+        $(
+            let input_bytes = include_bytes!(concat!("../tests/sol/", $path, ".sol"));
+            let mut reader = Reader::default();
+            reader.amf3_decoder.intern_names = true;
+            let before = ALLOCATIONS.load(Ordering::Relaxed);
+            black_box(reader.parse(input_bytes).unwrap());
+            let count = ALLOCATIONS.load(Ordering::Relaxed) - before;
+            println!("parse_{}_interned: {} allocations", $path, count);
+        )*
+    }
+}
+
+fn main() {
+    count_allocations!(
+        "AS2-ECMAArray-Demo",
+        "AS3-Demo",
+        "AS3-TypedObject-Demo",
+        "AS3-VectorTypedObject-Demo",
+        "InfectonatorSurvivors76561198009932603",
+        "Party1",
+        "slot1"
+    );
+    count_interned_allocations!(
+        "AS3-TypedObject-Demo",
+        "InfectonatorSurvivors76561198009932603",
+        "Party1",
+        "slot1"
+    );
+}
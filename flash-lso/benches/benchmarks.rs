@@ -1,14 +1,34 @@
-#![feature(test)]
-
-extern crate test;
-
+use flash_lso::amf3::read::AMF3Decoder;
 use flash_lso::read::Reader;
+use std::rc::Rc;
 
-use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
 
-criterion_group!(benches, criterion_benchmark);
+criterion_group!(benches, criterion_benchmark, nested_benchmark);
 criterion_main!(benches);
 
+/// Read and drop arrays nested `depth` deep, to compare the cost per level of shallow and very deep values
+fn nested_benchmark(c: &mut Criterion) {
+    let mut group = c.benchmark_group("parse_nested_arrays");
+    for depth in [10, 1_000, 100_000] {
+        let mut input = [0x09, 0x03, 0x01].repeat(depth);
+        input.push(0x01);
+        group.bench_with_input(BenchmarkId::from_parameter(depth), &input, |b, input| {
+            b.iter(|| {
+                let mut decoder = AMF3Decoder::default();
+                decoder.max_depth = None;
+                let (_, value) = decoder.parse_single_element(input).unwrap();
+                drop(decoder);
+                // The deepest of these would overflow the stack if dropped recursively
+                if let Ok(value) = Rc::try_unwrap(black_box(value)) {
+                    value.drop_iteratively();
+                }
+            })
+        });
+    }
+    group.finish();
+}
+
 macro_rules! auto_bench {
         ($([$name: ident, $path: expr]),*) => {
             fn criterion_benchmark(c: &mut Criterion) {
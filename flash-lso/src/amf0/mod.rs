@@ -1,6 +1,7 @@
 /// Support for reading AMF0 data
 pub mod read;
 /// AMF0 type markers
-mod type_marker;
+pub(crate) mod type_marker;
 /// Support for writing AMF0 data
+#[cfg(feature = "std")]
 pub mod write;
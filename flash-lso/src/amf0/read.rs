@@ -1,19 +1,23 @@
 //! Support for decoding AMF0 data
 use crate::amf0::type_marker::TypeMarker;
 
-use crate::nom_utils::{take_str, AMFResult};
+use crate::amf3::read::{AMF3Decoder, DEFAULT_MAX_DEPTH};
+use crate::errors::Error;
+use crate::nom_utils::{self, take_str, AMFResult};
 use crate::types::{ClassDefinition, Element, Value};
-use crate::{amf3, PADDING};
+use crate::PADDING;
 use nom::bytes::complete::tag;
 use nom::combinator::map;
 use nom::error::{make_error, ErrorKind};
-use nom::multi::{many0, many_m_n};
+use nom::multi::many0;
 use nom::number::complete::{be_f64, be_u16, be_u32, be_u8};
 use nom::take_str;
 use nom::Err;
 
-use std::convert::{TryFrom, TryInto};
-use std::rc::Rc;
+use alloc::rc::Rc;
+use alloc::string::ToString;
+use alloc::vec::Vec;
+use core::convert::{TryFrom, TryInto};
 
 pub(crate) fn parse_string(i: &[u8]) -> AMFResult<'_, &str> {
     let (i, length) = be_u16(i)?;
@@ -32,54 +36,11 @@ fn parse_element_string(i: &[u8]) -> AMFResult<'_, Value> {
     map(parse_string, |s: &str| Value::String(s.to_string()))(i)
 }
 
-fn parse_element_object(i: &[u8]) -> AMFResult<'_, Value> {
-    map(parse_array_element, |elms: Vec<Element>| {
-        Value::Object(elms, None)
-    })(i)
-}
-
 fn parse_element_movie_clip(i: &[u8]) -> AMFResult<'_, Value> {
     // Reserved but unsupported
     Err(Err::Error(make_error(i, ErrorKind::Tag)))
 }
 
-#[allow(clippy::let_and_return)]
-fn parse_element_mixed_array(i: &[u8]) -> AMFResult<'_, Value> {
-    let (i, array_length) = be_u32(i)?;
-    // this `let x = ...` fixes a borrow error on array_length
-    let x = map(parse_array_element, |elms: Vec<Element>| {
-        Value::ECMAArray(Vec::new(), elms, array_length)
-    })(i);
-
-    x
-}
-
-fn parse_element_reference(i: &[u8]) -> AMFResult<'_, Value> {
-    // References arent supported
-    Err(Err::Error(make_error(i, ErrorKind::Tag)))
-}
-
-fn parse_element_array(i: &[u8]) -> AMFResult<'_, Value> {
-    let (i, length) = be_u32(i)?;
-
-    let length_usize = length
-        .try_into()
-        .map_err(|_| Err::Error(make_error(i, ErrorKind::Digit)))?;
-
-    // There must be at least `length_usize` bytes (u8) to read this, this prevents OOM errors with v.large arrays
-    if i.len() < length_usize {
-        return Err(Err::Error(make_error(i, ErrorKind::TooLarge)));
-    }
-
-    // This must parse length elements
-    let (i, elements) = many_m_n(length_usize, length_usize, parse_single_element)(i)?;
-
-    Ok((
-        i,
-        Value::StrictArray(elements.into_iter().map(Rc::new).collect()),
-    ))
-}
-
 fn parse_element_date(i: &[u8]) -> AMFResult<'_, Value> {
     let (i, millis) = be_f64(i)?;
     let (i, time_zone) = be_u16(i)?;
@@ -109,25 +70,6 @@ fn parse_element_xml(i: &[u8]) -> AMFResult<'_, Value> {
     }
 }
 
-#[allow(clippy::let_and_return)]
-fn parse_element_typed_object(i: &[u8]) -> AMFResult<'_, Value> {
-    let (i, name) = parse_string(i)?;
-
-    let x = map(parse_array_element, |elms: Vec<Element>| {
-        Value::Object(
-            elms,
-            Some(ClassDefinition::default_with_name(name.to_string())),
-        )
-    })(i);
-    x
-}
-
-fn parse_element_amf3(i: &[u8]) -> AMFResult<'_, Value> {
-    // Hopefully amf3 objects wont have references
-    let (i, x) = amf3::read::AMF3Decoder::default().parse_element_object(i)?;
-    Ok((i, Value::AMF3(x)))
-}
-
 fn read_type_marker(i: &[u8]) -> AMFResult<'_, TypeMarker> {
     let (i, type_) = be_u8(i)?;
     Ok((
@@ -136,69 +78,356 @@ fn read_type_marker(i: &[u8]) -> AMFResult<'_, TypeMarker> {
     ))
 }
 
-fn parse_single_element(i: &[u8]) -> AMFResult<'_, Value> {
-    let (i, type_) = read_type_marker(i)?;
+/// A container that `AMF0Decoder::parse_single_element` is reading the values of
+struct Container {
+    /// The index of the container in the object reference table
+    index: usize,
+    kind: ContainerKind,
+}
 
-    match type_ {
-        TypeMarker::Number => parse_element_number(i),
-        TypeMarker::Boolean => parse_element_bool(i),
-        TypeMarker::String => parse_element_string(i),
-        TypeMarker::Object => parse_element_object(i),
-        TypeMarker::MovieClip => parse_element_movie_clip(i),
-        TypeMarker::Null => Ok((i, Value::Null)),
-        TypeMarker::Undefined => Ok((i, Value::Undefined)),
-        TypeMarker::Reference => parse_element_reference(i),
-        TypeMarker::MixedArrayStart => parse_element_mixed_array(i),
-        TypeMarker::Array => parse_element_array(i),
-        TypeMarker::Date => parse_element_date(i),
-        TypeMarker::LongString => parse_element_long_string(i),
-        TypeMarker::Unsupported => Ok((i, Value::Unsupported)),
-        TypeMarker::RecordSet => parse_element_record_set(i),
-        TypeMarker::XML => parse_element_xml(i),
-        TypeMarker::TypedObject => parse_element_typed_object(i),
-        TypeMarker::AMF3 => parse_element_amf3(i),
-        TypeMarker::ObjectEnd => Err(Err::Error(make_error(i, ErrorKind::Digit))),
-    }
+enum ContainerKind {
+    /// An object or typed object, its elements are read until the object end marker
+    Object {
+        elements: Vec<Element>,
+        class_def: Option<ClassDefinition>,
+        /// The name of the element being read
+        name: Option<Rc<str>>,
+    },
+    /// An ECMA array, its elements are read like those of an object
+    ECMAArray {
+        elements: Vec<Element>,
+        array_length: u32,
+        /// The name of the element being read
+        name: Option<Rc<str>>,
+    },
+    StrictArray {
+        items: Vec<Rc<Value>>,
+        len: usize,
+    },
 }
 
-fn parse_element(i: &[u8]) -> AMFResult<'_, Element> {
-    let (i, name) = parse_string(i)?;
+impl Container {
+    /// Read up to the next value of this container, giving true if there is another value at the returned input,
+    /// or false once every value has been read
+    fn next_value<'a>(&mut self, i: &'a [u8]) -> AMFResult<'a, bool> {
+        match &mut self.kind {
+            ContainerKind::Object { name, .. } | ContainerKind::ECMAArray { name, .. } => {
+                let (j, element_name) = parse_string(i)?;
+                let (k, next_type) = read_type_marker(j)?;
+                if next_type == TypeMarker::ObjectEnd {
+                    return Ok((k, false));
+                }
+
+                *name = Some(Rc::from(element_name));
+                Ok((j, true))
+            }
+            ContainerKind::StrictArray { items, len } => Ok((i, items.len() < *len)),
+        }
+    }
 
-    map(parse_single_element, move |v| Element {
-        name: name.to_string(),
-        value: Rc::new(v),
-    })(i)
+    /// Add the next value that was read to this container
+    fn push(&mut self, value: Rc<Value>) {
+        match &mut self.kind {
+            ContainerKind::Object { elements, name, .. }
+            | ContainerKind::ECMAArray { elements, name, .. } => elements.push(Element {
+                name: name.take().unwrap_or_default(),
+                value,
+            }),
+            ContainerKind::StrictArray { items, .. } => items.push(value),
+        }
+    }
+
+    fn into_value(self) -> Value {
+        match self.kind {
+            ContainerKind::Object {
+                elements,
+                class_def,
+                ..
+            } => Value::Object(elements, class_def),
+            ContainerKind::ECMAArray {
+                elements,
+                array_length,
+                ..
+            } => Value::ECMAArray(Vec::new(), elements, array_length),
+            ContainerKind::StrictArray { items, .. } => Value::StrictArray(items),
+        }
+    }
 }
 
-fn parse_element_and_padding(i: &[u8]) -> AMFResult<'_, Element> {
-    let (i, e) = parse_element(i)?;
-    let (i, _) = tag(PADDING)(i)?;
+/// The result of starting to read a value, either the whole value or a container whose values are still to be read
+enum Opened {
+    Value(Rc<Value>),
+    Container(Container),
+}
+
+/// Handles decoding AMF0
+pub struct AMF0Decoder<'d> {
+    /// The table used to cache repeated objects
+    pub object_reference_table: Vec<Rc<Value>>,
+    /// The maximum number of objects and arrays that may be nested in each other, reading one nested any deeper will
+    /// fail with `ErrorKind::TooLarge`, see `AMF3Decoder::max_depth`
+    /// Embedded AMF3 values are limited separately, by the `max_depth` of the AMF3 decoder that reads them
+    /// Defaults to `Some(DEFAULT_MAX_DEPTH)`, or the `max_depth` of the AMF3 decoder given to `with_amf3_decoder`
+    pub max_depth: Option<usize>,
+    /// The stack of containers being read by `parse_single_element`, kept between values to reuse its allocation
+    containers: Vec<Container>,
+    /// The decoder for embedded AMF3 values, see `with_amf3_decoder`
+    amf3_decoder: Option<&'d mut AMF3Decoder>,
+}
 
-    Ok((i, e))
+impl Default for AMF0Decoder<'_> {
+    fn default() -> Self {
+        Self {
+            object_reference_table: Vec::new(),
+            max_depth: Some(DEFAULT_MAX_DEPTH),
+            containers: Vec::new(),
+            amf3_decoder: None,
+        }
+    }
 }
 
-//TODO: can this be done better somehow??
-fn parse_array_element(i: &[u8]) -> AMFResult<'_, Vec<Element>> {
-    let mut out = Vec::new();
+impl<'d> AMF0Decoder<'d> {
+    /// Create a decoder that reads embedded AMF3 values with `amf3_decoder`, so that its `external_decoders` (such as
+    /// the flex decoders) are used, its reference tables are cleared before each embedded value
+    /// The `max_depth` of `amf3_decoder` is used for the AMF0 values too
+    /// Without one embedded values are read by an `AMF3Decoder` with no external decoders
+    pub fn with_amf3_decoder(amf3_decoder: &'d mut AMF3Decoder) -> Self {
+        AMF0Decoder {
+            max_depth: amf3_decoder.max_depth,
+            amf3_decoder: Some(amf3_decoder),
+            ..AMF0Decoder::default()
+        }
+    }
+
+    fn parse_element_amf3<'a>(&mut self, i: &'a [u8]) -> AMFResult<'a, Value> {
+        // Each embedded AMF3 value has its own reference tables, separate from the rest of the body
+        let (i, x) = match &mut self.amf3_decoder {
+            Some(amf3_decoder) => {
+                amf3_decoder.clear_tables();
+                amf3_decoder.parse_single_element(i)?
+            }
+            None => AMF3Decoder::default().parse_single_element(i)?,
+        };
+        Ok((i, Value::AMF3(x)))
+    }
+
+    /// Start reading a container that is added to the reference table, `placeholder` is what a reference to it
+    /// resolves to while it is still being read, as a value can't contain itself
+    fn open_referenceable(&mut self, placeholder: Value, kind: ContainerKind) -> Opened {
+        let index = self.object_reference_table.len();
+        self.object_reference_table.push(Rc::new(placeholder));
+
+        Opened::Container(Container { index, kind })
+    }
+
+    fn open_object(&mut self) -> Opened {
+        self.open_referenceable(
+            Value::Object(Vec::new(), None),
+            ContainerKind::Object {
+                elements: Vec::new(),
+                class_def: None,
+                name: None,
+            },
+        )
+    }
+
+    fn open_mixed_array<'a>(&mut self, i: &'a [u8]) -> AMFResult<'a, Opened> {
+        // The declared length is often inaccurate, so elements are read until the object end marker
+        // and the length is only kept so that it can be written back unchanged
+        let (i, array_length) = be_u32(i)?;
+
+        // Every element is at least 3 bytes (its name length and marker), so an inaccurate length can't
+        // allocate more than the input size
+        let capacity = (array_length as usize).min(i.len() / 3);
+        let opened = self.open_referenceable(
+            Value::ECMAArray(Vec::new(), Vec::new(), array_length),
+            ContainerKind::ECMAArray {
+                elements: Vec::with_capacity(capacity),
+                array_length,
+                name: None,
+            },
+        );
+        Ok((i, opened))
+    }
+
+    fn parse_element_reference<'a>(&mut self, i: &'a [u8]) -> AMFResult<'a, Rc<Value>> {
+        let (i, index) = be_u16(i)?;
+
+        let value = self
+            .object_reference_table
+            .get(index as usize)
+            .ok_or_else(|| Err::Error(make_error(i, ErrorKind::Digit)))?;
+
+        Ok((i, Rc::clone(value)))
+    }
+
+    fn open_array<'a>(&mut self, i: &'a [u8]) -> AMFResult<'a, Opened> {
+        let (i, length) = be_u32(i)?;
+
+        let length_usize = length
+            .try_into()
+            .map_err(|_| Err::Error(make_error(i, ErrorKind::Digit)))?;
 
-    let mut i = i;
-    loop {
-        let (k, _) = parse_string(i)?;
-        let (k, next_type) = read_type_marker(k)?;
-        if next_type == TypeMarker::ObjectEnd {
-            i = k;
-            break;
+        // There must be at least `length_usize` bytes (u8) to read this, this prevents OOM errors with v.large arrays
+        if i.len() < length_usize {
+            return Err(Err::Error(make_error(i, ErrorKind::TooLarge)));
         }
 
-        let (j, e) = parse_element(i)?;
-        i = j;
+        // This must read length elements
+        let opened = self.open_referenceable(
+            Value::StrictArray(Vec::new()),
+            ContainerKind::StrictArray {
+                items: Vec::with_capacity(length_usize),
+                len: length_usize,
+            },
+        );
+        Ok((i, opened))
+    }
 
-        out.push(e.clone());
+    fn open_typed_object<'a>(&mut self, i: &'a [u8]) -> AMFResult<'a, Opened> {
+        let (i, name) = parse_string(i)?;
+        let class_def = ClassDefinition::default_with_name(name.to_string());
+
+        let opened = self.open_referenceable(
+            Value::Object(Vec::new(), Some(class_def.clone())),
+            ContainerKind::Object {
+                elements: Vec::new(),
+                class_def: Some(class_def),
+                name: None,
+            },
+        );
+        Ok((i, opened))
     }
 
-    Ok((i, out))
-}
+    /// Start reading a value, giving the whole value unless it is a container that has values to be read
+    fn open_value<'a>(&mut self, i: &'a [u8]) -> AMFResult<'a, Opened> {
+        let (i, type_) = read_type_marker(i)?;
+
+        match type_ {
+            TypeMarker::Object => Ok((i, self.open_object())),
+            TypeMarker::Reference => {
+                let (i, value) = self.parse_element_reference(i)?;
+                Ok((i, Opened::Value(value)))
+            }
+            TypeMarker::MixedArrayStart => self.open_mixed_array(i),
+            TypeMarker::Array => self.open_array(i),
+            TypeMarker::TypedObject => self.open_typed_object(i),
+            _ => {
+                let (i, v) = match type_ {
+                    TypeMarker::Number => parse_element_number(i),
+                    TypeMarker::Boolean => parse_element_bool(i),
+                    TypeMarker::String => parse_element_string(i),
+                    TypeMarker::MovieClip => parse_element_movie_clip(i),
+                    TypeMarker::Null => Ok((i, Value::Null)),
+                    TypeMarker::Undefined => Ok((i, Value::Undefined)),
+                    TypeMarker::Date => parse_element_date(i),
+                    TypeMarker::LongString => parse_element_long_string(i),
+                    TypeMarker::Unsupported => Ok((i, Value::Unsupported)),
+                    TypeMarker::RecordSet => parse_element_record_set(i),
+                    TypeMarker::XML => parse_element_xml(i),
+                    TypeMarker::AMF3 => self.parse_element_amf3(i),
+                    // ObjectEnd, the referenceable markers are handled above
+                    _ => Err(Err::Error(make_error(i, ErrorKind::Digit))),
+                }?;
+                Ok((i, Opened::Value(Rc::new(v))))
+            }
+        }
+    }
+
+    /// Finish a container whose values have all been read, replacing its placeholder in the object reference table
+    fn close(&mut self, container: Container) -> Rc<Value> {
+        let index = container.index;
+        let value = Rc::new(container.into_value());
+        self.object_reference_table[index] = Rc::clone(&value);
+        value
+    }
+
+    /// Read a value, along with the values nested in it
+    /// Rather than reading nested values recursively, the containers being read are kept on a stack on the heap, so data
+    /// that is nested very deeply can't overflow the native stack
+    fn parse_single_element<'a>(&mut self, i: &'a [u8]) -> AMFResult<'a, Rc<Value>> {
+        let mut containers = core::mem::take(&mut self.containers);
+        let result = self.parse_containers(i, &mut containers);
+        // After a failure the containers that were being read are dropped
+        containers.clear();
+        self.containers = containers;
+        result
+    }
 
-pub(crate) fn parse_body(i: &[u8]) -> AMFResult<'_, Vec<Element>> {
-    many0(parse_element_and_padding)(i)
+    fn parse_containers<'a>(
+        &mut self,
+        i: &'a [u8],
+        containers: &mut Vec<Container>,
+    ) -> AMFResult<'a, Rc<Value>> {
+        let mut i = i;
+        loop {
+            let start = i;
+            let (j, opened) = self.open_value(i)?;
+            i = j;
+            match opened {
+                Opened::Value(value) => match containers.last_mut() {
+                    Some(parent) => parent.push(value),
+                    None => return Ok((i, value)),
+                },
+                Opened::Container(container) => {
+                    if matches!(self.max_depth, Some(max) if containers.len() >= max) {
+                        return Err(Err::Error(make_error(start, ErrorKind::TooLarge)));
+                    }
+                    containers.push(container);
+                }
+            }
+
+            // Close the containers that have no values left, giving each to the container it was read in,
+            // until a container needs another value or the outermost value is finished
+            while let Some(container) = containers.last_mut() {
+                let (j, more) = container.next_value(i)?;
+                i = j;
+                if more {
+                    break;
+                }
+
+                if let Some(container) = containers.pop() {
+                    let value = self.close(container);
+                    match containers.last_mut() {
+                        Some(parent) => parent.push(value),
+                        None => return Ok((i, value)),
+                    }
+                }
+            }
+        }
+    }
+
+    fn parse_element<'a>(&mut self, i: &'a [u8]) -> AMFResult<'a, Element> {
+        let (i, name) = parse_string(i)?;
+        let (i, value) = self.parse_single_element(i)?;
+
+        Ok((
+            i,
+            Element {
+                name: Rc::from(name),
+                value,
+            },
+        ))
+    }
+
+    fn parse_element_and_padding<'a>(&mut self, i: &'a [u8]) -> AMFResult<'a, Element> {
+        let (i, e) = self.parse_element(i)?;
+        let (i, _) = tag(PADDING)(i)?;
+
+        Ok((i, e))
+    }
+
+    /// Parse a series of AMF0 elements, each followed by a padding byte
+    pub fn parse_body<'a>(&mut self, i: &'a [u8]) -> AMFResult<'a, Vec<Element>> {
+        many0(|i| self.parse_element_and_padding(i))(i)
+    }
+
+    /// Parse an AMF0 body from a slice, which must be consumed entirely
+    /// On failure the offset in the slice where parsing failed is also given
+    pub fn parse_with_offsets<'a>(
+        &mut self,
+        i: &'a [u8],
+    ) -> Result<Vec<Element>, (Error<'a>, usize)> {
+        nom_utils::parse_with_offsets(i, |i| self.parse_body(i))
+    }
 }
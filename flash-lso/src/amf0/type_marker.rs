@@ -4,6 +4,7 @@ use derive_try_from_primitive::TryFromPrimitive;
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[derive(TryFromPrimitive, Eq, PartialEq, Debug, Copy, Clone)]
 #[repr(u8)]
+#[allow(clippy::upper_case_acronyms)]
 pub(crate) enum TypeMarker {
     /// Number
     Number = 0,
@@ -19,7 +20,7 @@ pub(crate) enum TypeMarker {
     Null = 5,
     /// Undefined
     Undefined = 6,
-    /// Reference (unused)
+    /// Reference to a previously read object
     Reference = 7,
     /// Start of a mixed array
     MixedArrayStart = 8,
@@ -12,6 +12,9 @@ use cookie_factory::combinator::slice;
 use cookie_factory::combinator::string;
 use cookie_factory::multi::all;
 use cookie_factory::sequence::tuple;
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::convert::TryFrom;
 use std::ops::Deref;
 use std::rc::Rc;
 
@@ -45,15 +48,6 @@ fn write_string_element<'a, 'b: 'a, W: Write + 'a>(s: &'b str) -> impl Serialize
     tuple((write_type_marker(TypeMarker::String), write_string(s)))
 }
 
-fn write_object_element<'a, 'b: 'a, W: Write + 'a>(o: &'b [Element]) -> impl SerializeFn<W> + 'a {
-    tuple((
-        write_type_marker(TypeMarker::Object),
-        all(o.iter().map(write_element)),
-        be_u16(0),
-        write_type_marker(TypeMarker::ObjectEnd),
-    ))
-}
-
 fn write_null_element<'a, 'b: 'a, W: Write + 'a>() -> impl SerializeFn<W> + 'a {
     write_type_marker(TypeMarker::Null)
 }
@@ -62,16 +56,6 @@ fn write_undefined_element<'a, 'b: 'a, W: Write + 'a>() -> impl SerializeFn<W> +
     write_type_marker(TypeMarker::Undefined)
 }
 
-fn write_strict_array_element<'a, 'b: 'a, W: Write + 'a>(
-    elements: &'b [Rc<Value>],
-) -> impl SerializeFn<W> + 'a {
-    tuple((
-        write_type_marker(TypeMarker::Array),
-        be_u32(elements.len() as u32),
-        all(elements.iter().map(write_value)),
-    ))
-}
-
 fn write_date_element<'a, 'b: 'a, W: Write + 'a>(
     date: f64,
     tz: Option<u16>,
@@ -94,81 +78,194 @@ fn write_xml_element<'a, 'b: 'a, W: Write + 'a>(content: &'b str) -> impl Serial
     ))
 }
 
-fn write_typed_object_element<'a, 'b: 'a, W: Write + 'a>(
-    name: &'b str,
-    elements: &'b [Element],
-) -> impl SerializeFn<W> + 'a {
-    tuple((
-        write_type_marker(TypeMarker::TypedObject),
-        write_string(name),
-        all(elements.iter().map(write_element)),
-        be_u16(0),
-        write_type_marker(TypeMarker::ObjectEnd),
-    ))
+fn write_reference_element<'a, 'b: 'a, W: Write + 'a>(index: u16) -> impl SerializeFn<W> + 'a {
+    tuple((write_type_marker(TypeMarker::Reference), be_u16(index)))
 }
 
-fn write_mixed_array<'a, 'b: 'a, W: Write + 'a>(
-    elements: &'b [Element],
-    length: u32,
-) -> impl SerializeFn<W> + 'a {
-    //TODO: what is the u16 padding
-    //TODO: sometimes array length is ignored (u32) sometimes its: elements.len() as u32
+/// Check if a value is stored in the reference table when written
+fn is_referenceable(v: &Value) -> bool {
+    matches!(
+        v,
+        Value::Object(_, _) | Value::StrictArray(_) | Value::ECMAArray(_, _, _)
+    )
+}
 
-    tuple((
-        write_type_marker(TypeMarker::MixedArrayStart),
-        be_u32(length),
-        all(elements.iter().map(write_element)),
-        be_u16(0),
-        write_type_marker(TypeMarker::ObjectEnd),
-    ))
+/// Handles encoding AMF0
+#[derive(Default)]
+pub struct AMF0Encoder<'e> {
+    /// The number of values that have been added to the reference table
+    object_count: Cell<usize>,
+    /// Maps shared values (by `Rc` pointer) to their index in the reference table
+    /// Each entry keeps its value alive, so the address can't be reused by another value while the encoder is in use
+    object_pointer_table: RefCell<HashMap<*const Value, (Rc<Value>, u16)>>,
+    /// The encoder for embedded AMF3 values, see `with_amf3_encoder`
+    amf3_encoder: Option<&'e AMF3Encoder>,
 }
 
-fn write_value<'a, 'b: 'a, W: Write + 'a>(element: &'b Rc<Value>) -> impl SerializeFn<W> + 'a {
-    move |out: WriteContext<W>| match element.deref() {
-        Value::Number(n) => write_number_element(*n)(out),
-        Value::Bool(b) => write_bool_element(*b)(out),
-        Value::String(s) => {
-            if s.len() > 65535 {
-                write_long_string_element(s)(out)
-            } else {
-                write_string_element(s)(out)
-            }
+impl<'e> AMF0Encoder<'e> {
+    /// Create an encoder that writes embedded AMF3 values with `amf3_encoder`, so that its `external_encoders` (such as
+    /// the flex encoders) are used, its reference tables are cleared before each embedded value
+    /// Without one embedded values are written by an `AMF3Encoder` with no external encoders
+    pub fn with_amf3_encoder(amf3_encoder: &'e AMF3Encoder) -> Self {
+        AMF0Encoder {
+            amf3_encoder: Some(amf3_encoder),
+            ..AMF0Encoder::default()
         }
-        Value::Object(elements, class_def) => {
-            if let Some(class_def) = class_def {
-                write_typed_object_element(&class_def.name, elements)(out)
-            } else {
-                write_object_element(elements)(out)
+    }
+
+    fn write_object_element<'a, 'b: 'a, W: Write + 'a>(
+        &'b self,
+        o: &'b [Element],
+    ) -> impl SerializeFn<W> + 'a {
+        tuple((
+            write_type_marker(TypeMarker::Object),
+            all(o.iter().map(move |e| self.write_element(e))),
+            be_u16(0),
+            write_type_marker(TypeMarker::ObjectEnd),
+        ))
+    }
+
+    fn write_strict_array_element<'a, 'b: 'a, W: Write + 'a>(
+        &'b self,
+        elements: &'b [Rc<Value>],
+    ) -> impl SerializeFn<W> + 'a {
+        tuple((
+            write_type_marker(TypeMarker::Array),
+            be_u32(elements.len() as u32),
+            all(elements.iter().map(move |v| self.write_value(v))),
+        ))
+    }
+
+    fn write_typed_object_element<'a, 'b: 'a, W: Write + 'a>(
+        &'b self,
+        name: &'b str,
+        elements: &'b [Element],
+    ) -> impl SerializeFn<W> + 'a {
+        tuple((
+            write_type_marker(TypeMarker::TypedObject),
+            write_string(name),
+            all(elements.iter().map(move |e| self.write_element(e))),
+            be_u16(0),
+            write_type_marker(TypeMarker::ObjectEnd),
+        ))
+    }
+
+    fn write_mixed_array<'a, 'b: 'a, W: Write + 'a>(
+        &'b self,
+        elements: &'b [Element],
+        length: u32,
+    ) -> impl SerializeFn<W> + 'a {
+        //TODO: what is the u16 padding
+        // The length is written as given, even if it doesn't match the elements, as readers use the end marker
+
+        tuple((
+            write_type_marker(TypeMarker::MixedArrayStart),
+            be_u32(length),
+            all(elements.iter().map(move |e| self.write_element(e))),
+            be_u16(0),
+            write_type_marker(TypeMarker::ObjectEnd),
+        ))
+    }
+
+    fn write_value<'a, 'b: 'a, W: Write + 'a>(
+        &'b self,
+        element: &'b Rc<Value>,
+    ) -> impl SerializeFn<W> + 'a {
+        move |out: WriteContext<W>| {
+            let ptr = Rc::as_ptr(element);
+            let existing = self
+                .object_pointer_table
+                .borrow()
+                .get(&ptr)
+                .map(|(_, index)| *index);
+            if let Some(index) = existing {
+                return write_reference_element(index)(out);
             }
-        }
-        Value::Null => write_null_element()(out),
-        Value::Undefined => write_undefined_element()(out),
-        Value::StrictArray(a) => write_strict_array_element(a.as_slice())(out),
-        Value::Date(d, tz) => write_date_element(*d, *tz)(out),
-        Value::Unsupported => write_unsupported_element()(out),
-        Value::XML(x, _string) => write_xml_element(x)(out),
-        Value::ECMAArray(_dense, elems, elems_length) => {
-            write_mixed_array(elems, *elems_length)(out)
-        }
-        Value::AMF3(e) => AMF3Encoder::default().write_value_element(e)(out),
-        _ => {
-            write_unsupported_element()(out) /* Not in amf0, TODO: use the amf3 embedding for every thing else */
+
+            // Values written inline are added to the reference table before any of their children
+            let index = self.object_count.get();
+            if is_referenceable(element) {
+                self.object_count.set(index + 1);
+            }
+
+            let out = match element.deref() {
+                Value::Number(n) => write_number_element(*n)(out),
+                Value::Bool(b) => write_bool_element(*b)(out),
+                Value::String(s) => {
+                    if s.len() > 65535 {
+                        write_long_string_element(s)(out)
+                    } else {
+                        write_string_element(s)(out)
+                    }
+                }
+                Value::Object(elements, class_def) => {
+                    if let Some(class_def) = class_def {
+                        self.write_typed_object_element(&class_def.name, elements)(out)
+                    } else {
+                        self.write_object_element(elements)(out)
+                    }
+                }
+                Value::Null => write_null_element()(out),
+                Value::Undefined => write_undefined_element()(out),
+                Value::StrictArray(a) => self.write_strict_array_element(a.as_slice())(out),
+                Value::Date(d, tz) => write_date_element(*d, *tz)(out),
+                Value::Unsupported => write_unsupported_element()(out),
+                Value::XML(x, _string) => write_xml_element(x)(out),
+                Value::ECMAArray(_dense, elems, elems_length) => {
+                    self.write_mixed_array(elems, *elems_length)(out)
+                }
+                Value::AMF3(e) => {
+                    // Each embedded AMF3 value has its own reference tables, separate from the rest of the body
+                    let out = write_type_marker(TypeMarker::AMF3)(out)?;
+                    match self.amf3_encoder {
+                        Some(amf3_encoder) => {
+                            amf3_encoder.clear_tables();
+                            amf3_encoder.write_value_element(e)(out)
+                        }
+                        None => AMF3Encoder::default().write_value_element(e)(out),
+                    }
+                }
+                _ => {
+                    write_unsupported_element()(out) /* Not in amf0, TODO: use the amf3 embedding for every thing else */
+                }
+            }?;
+
+            // References can only address the first 65536 values
+            if is_referenceable(element) {
+                if let Ok(index) = u16::try_from(index) {
+                    self.object_pointer_table
+                        .borrow_mut()
+                        .insert(ptr, (Rc::clone(element), index));
+                }
+            }
+            Ok(out)
         }
     }
-}
 
-fn write_element<'a, 'b: 'a, W: Write + 'a>(element: &'b Element) -> impl SerializeFn<W> + 'a {
-    tuple((write_string(&element.name), write_value(&element.value)))
-}
+    fn write_element<'a, 'b: 'a, W: Write + 'a>(
+        &'b self,
+        element: &'b Element,
+    ) -> impl SerializeFn<W> + 'a {
+        tuple((
+            write_string(&element.name),
+            self.write_value(&element.value),
+        ))
+    }
 
-fn write_element_and_padding<'a, 'b: 'a, W: Write + 'a>(
-    element: &'b Element,
-) -> impl SerializeFn<W> + 'a {
-    tuple((write_element(element), slice(PADDING)))
-}
+    fn write_element_and_padding<'a, 'b: 'a, W: Write + 'a>(
+        &'b self,
+        element: &'b Element,
+    ) -> impl SerializeFn<W> + 'a {
+        tuple((self.write_element(element), slice(PADDING)))
+    }
 
-pub(crate) fn write_body<'a, 'b: 'a, W: Write + 'a>(
-    elements: &'b [Element],
-) -> impl SerializeFn<W> + 'a {
-    all(elements.iter().map(write_element_and_padding))
+    /// Write a series of AMF0 elements, each followed by a padding byte
+    pub fn write_body<'a, 'b: 'a, W: Write + 'a>(
+        &'b self,
+        elements: &'b [Element],
+    ) -> impl SerializeFn<W> + 'a {
+        all(elements
+            .iter()
+            .map(move |e| self.write_element_and_padding(e)))
+    }
 }
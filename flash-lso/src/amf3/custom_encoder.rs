@@ -1,21 +1,26 @@
 use crate::amf3::read::AMF3Decoder;
 
+#[cfg(feature = "std")]
 use crate::amf3::write::AMF3Encoder;
 
 use crate::types::Element;
+#[cfg(feature = "std")]
 use crate::types::*;
 
 use crate::nom_utils::AMFResult;
-use std::rc::Rc;
+use alloc::boxed::Box;
+use alloc::rc::Rc;
+use alloc::vec::Vec;
 
 /// A trait to define encoding for custom types for use with Externalized objects
+#[cfg(feature = "std")]
 pub trait CustomEncoder {
     /// This should implement the encoding of a given set of external elements for the given class definition
     /// Access to the AMF3Encoder is given to allow access to caches
     /// This implements the encoding side of externalized type support
-    fn encode<'a>(
+    fn encode(
         &self,
-        elements: &'a [Element],
+        elements: &[Element],
         class_def: &Option<ClassDefinition>,
         encoder: &AMF3Encoder,
     ) -> Vec<u8>;
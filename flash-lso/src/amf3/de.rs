@@ -0,0 +1,224 @@
+//! Deserializing values into types that implement `Deserialize`
+
+use crate::types::{Element, Value};
+use serde::de::value::{MapAccessDeserializer, MapDeserializer, SeqDeserializer};
+use serde::de::{self, Deserialize, Deserializer, IntoDeserializer, Visitor};
+use serde::forward_to_deserialize_any;
+use thiserror::Error;
+
+/// An error from deserializing with `from_value`
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+#[error("{0}")]
+pub struct FromValueError(String);
+
+impl de::Error for FromValueError {
+    fn custom<T: std::fmt::Display>(msg: T) -> Self {
+        FromValueError(msg.to_string())
+    }
+}
+
+/// Deserialize a value into any type implementing `Deserialize`, such as a struct with `#[derive(Deserialize)]`
+///
+/// Values are given to the type as follows:
+/// - `Object`, `Custom` and `Dictionary` values as maps, properties without a matching struct field are ignored
+/// - `StrictArray`, vectors and `ByteArray` as sequences, so they can be read into a `Vec`
+/// - `ECMAArray` as a sequence if it only has a dense part, otherwise as a map with the dense part keyed by index
+/// - `Integer` and whole `Number`s into any numeric field that can hold them
+/// - `Null` and `Undefined` as `None` or `()`, dates as their time in milliseconds and XML as a string
+/// - `AMF3` wrapped values as their contents
+///
+/// This works with values read from either AMF version
+pub fn from_value<'de, T: Deserialize<'de>>(value: &'de Value) -> Result<T, FromValueError> {
+    T::deserialize(ValueDeserializer(value))
+}
+
+/// A `Deserializer` over a borrowed value
+#[derive(Clone, Copy)]
+struct ValueDeserializer<'de>(&'de Value);
+
+impl<'de> IntoDeserializer<'de, FromValueError> for ValueDeserializer<'de> {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+/// Give the elements of an object as a map from name to value
+fn element_map<'de>(
+    elements: impl Iterator<Item = &'de Element>,
+) -> MapDeserializer<'de, impl Iterator<Item = (&'de str, ValueDeserializer<'de>)>, FromValueError>
+{
+    MapDeserializer::new(elements.map(|e| (e.name(), ValueDeserializer(&e.value))))
+}
+
+fn visit_seq<'de, V: Visitor<'de>, I>(visitor: V, items: I) -> Result<V::Value, FromValueError>
+where
+    I: Iterator,
+    I::Item: IntoDeserializer<'de, FromValueError>,
+{
+    let mut seq = SeqDeserializer::new(items);
+    let value = visitor.visit_seq(&mut seq)?;
+    seq.end()?;
+    Ok(value)
+}
+
+fn visit_map<'de, V: Visitor<'de>, I, K, T>(
+    visitor: V,
+    mut map: MapDeserializer<'de, I, FromValueError>,
+) -> Result<V::Value, FromValueError>
+where
+    I: Iterator<Item = (K, T)>,
+    K: IntoDeserializer<'de, FromValueError>,
+    T: IntoDeserializer<'de, FromValueError>,
+{
+    let value = visitor.visit_map(&mut map)?;
+    map.end()?;
+    Ok(value)
+}
+
+impl<'de> ValueDeserializer<'de> {
+    /// Give whole numbers as integers, so that they can be read into integer fields
+    fn deserialize_integer<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, FromValueError> {
+        match self.0 {
+            Value::Number(n) if n.fract() == 0.0 && *n < 0.0 => visitor.visit_i64(*n as i64),
+            Value::Number(n) if n.fract() == 0.0 => visitor.visit_u64(*n as u64),
+            Value::AMF3(inner) => ValueDeserializer(inner).deserialize_integer(visitor),
+            _ => self.deserialize_any(visitor),
+        }
+    }
+}
+
+impl<'de> Deserializer<'de> for ValueDeserializer<'de> {
+    type Error = FromValueError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, FromValueError> {
+        match self.0 {
+            Value::Number(n) | Value::Date(n, _) => visitor.visit_f64(*n),
+            Value::Integer(i) => visitor.visit_i32(*i),
+            Value::Bool(b) => visitor.visit_bool(*b),
+            Value::String(s) | Value::XML(s, _) => visitor.visit_borrowed_str(s),
+            Value::Null | Value::Undefined | Value::Unsupported => visitor.visit_unit(),
+            Value::Object(elements, _) => visit_map(visitor, element_map(elements.iter())),
+            Value::Custom(custom_elements, elements, _) => visit_map(
+                visitor,
+                element_map(custom_elements.iter().chain(elements.iter())),
+            ),
+            Value::ECMAArray(dense, assoc, _) if assoc.is_empty() => {
+                visit_seq(visitor, dense.iter().map(|v| ValueDeserializer(v)))
+            }
+            Value::ECMAArray(dense, assoc, _) => {
+                let entries = dense
+                    .iter()
+                    .enumerate()
+                    .map(|(index, v)| (index.to_string(), ValueDeserializer(v)))
+                    .chain(
+                        assoc
+                            .iter()
+                            .map(|e| (e.name().to_string(), ValueDeserializer(&e.value))),
+                    );
+                visit_map(visitor, MapDeserializer::new(entries))
+            }
+            Value::StrictArray(items) | Value::VectorObject(items, _, _) => {
+                visit_seq(visitor, items.iter().map(|v| ValueDeserializer(v)))
+            }
+            Value::ByteArray(bytes) => visit_seq(visitor, bytes.iter().copied()),
+            Value::VectorInt(items, _) => visit_seq(visitor, items.iter().copied()),
+            Value::VectorUInt(items, _) => visit_seq(visitor, items.iter().copied()),
+            Value::VectorDouble(items, _) => visit_seq(visitor, items.iter().copied()),
+            Value::Dictionary(pairs, _) => visit_map(
+                visitor,
+                MapDeserializer::new(
+                    pairs
+                        .iter()
+                        .map(|(k, v)| (ValueDeserializer(k), ValueDeserializer(v))),
+                ),
+            ),
+            Value::AMF3(inner) => ValueDeserializer(inner).deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, FromValueError> {
+        match self.0 {
+            Value::Null | Value::Undefined => visitor.visit_none(),
+            Value::AMF3(inner) => ValueDeserializer(inner).deserialize_option(visitor),
+            _ => visitor.visit_some(self),
+        }
+    }
+
+    fn deserialize_bytes<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, FromValueError> {
+        match self.0 {
+            Value::ByteArray(bytes) => visitor.visit_borrowed_bytes(bytes),
+            Value::AMF3(inner) => ValueDeserializer(inner).deserialize_bytes(visitor),
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_byte_buf<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, FromValueError> {
+        self.deserialize_bytes(visitor)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, FromValueError> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    /// Unit variants are read from strings, other variants from an object with a single property named by the variant
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, FromValueError> {
+        match self.0 {
+            Value::String(s) => visitor.visit_enum(s.as_str().into_deserializer()),
+            Value::Object(elements, _) => {
+                visitor.visit_enum(MapAccessDeserializer::new(element_map(elements.iter())))
+            }
+            Value::AMF3(inner) => {
+                ValueDeserializer(inner).deserialize_enum(name, variants, visitor)
+            }
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_i8<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, FromValueError> {
+        self.deserialize_integer(visitor)
+    }
+
+    fn deserialize_i16<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, FromValueError> {
+        self.deserialize_integer(visitor)
+    }
+
+    fn deserialize_i32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, FromValueError> {
+        self.deserialize_integer(visitor)
+    }
+
+    fn deserialize_i64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, FromValueError> {
+        self.deserialize_integer(visitor)
+    }
+
+    fn deserialize_u8<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, FromValueError> {
+        self.deserialize_integer(visitor)
+    }
+
+    fn deserialize_u16<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, FromValueError> {
+        self.deserialize_integer(visitor)
+    }
+
+    fn deserialize_u32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, FromValueError> {
+        self.deserialize_integer(visitor)
+    }
+
+    fn deserialize_u64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, FromValueError> {
+        self.deserialize_integer(visitor)
+    }
+
+    forward_to_deserialize_any! {
+        bool i128 u128 f32 f64 char str string unit unit_struct seq tuple tuple_struct map struct identifier
+        ignored_any
+    }
+}
@@ -32,6 +32,24 @@ impl<T: PartialEq + Clone + Debug> ElementCache<T> {
         }
     }
 
+    /// Add the given item to the cache, even if an equal item already exists
+    #[inline]
+    pub(crate) fn push(&self, val: T) {
+        self.cache.borrow_mut().push(val);
+    }
+
+    /// Remove every item from the cache
+    #[inline]
+    pub(crate) fn clear(&self) {
+        self.cache.borrow_mut().clear();
+    }
+
+    /// The number of items in the cache
+    #[inline]
+    pub(crate) fn len(&self) -> usize {
+        self.cache.borrow().len()
+    }
+
     /// Retrieve the item at the given index from the cache
     #[inline]
     pub fn get_element(&self, index: usize) -> Option<T> {
@@ -64,12 +82,6 @@ impl<T: PartialEq + Clone + Debug> ElementCache<T> {
 }
 
 impl<T: PartialEq + Clone + Debug> ElementCache<Vec<T>> {
-    /// See #store, will convert slices of &[T] into Vec<T> before storing
-    #[inline]
-    pub(crate) fn store_slice(&self, val: &[T]) {
-        self.store(val.to_vec());
-    }
-
     /// See #get_index, will convert slices of &[T] into Vec<T> before retrieving
     #[inline]
     pub fn get_slice_index(&self, val: &[T]) -> Option<usize> {
@@ -1,5 +1,8 @@
+#[cfg(feature = "std")]
 use crate::amf3::write::AMF3Encoder;
+#[cfg(feature = "std")]
 use cookie_factory::SerializeFn;
+#[cfg(feature = "std")]
 use std::io::Write;
 
 #[derive(Copy, Clone, Debug, Eq, Ord, PartialOrd, PartialEq)]
@@ -9,21 +12,17 @@ pub(crate) enum Length {
 }
 
 impl Length {
+    #[cfg(feature = "std")]
     pub(crate) fn is_reference(&self) -> bool {
         matches!(self, Length::Reference(_))
     }
 
+    #[cfg(feature = "std")]
     pub(crate) fn is_size(&self) -> bool {
         matches!(self, Length::Size(_))
     }
 
-    pub(crate) fn to_position(&self) -> Option<usize> {
-        match self {
-            Length::Reference(x) => Some(*x),
-            _ => None,
-        }
-    }
-
+    #[cfg(feature = "std")]
     pub(crate) fn write<'a, 'b: 'a, W: Write + 'a>(
         &self,
         amf3: &AMF3Encoder,
@@ -32,6 +31,7 @@ impl Length {
     }
 }
 
+#[cfg(feature = "std")]
 fn write_length<'a, 'b: 'a, W: Write + 'a>(
     amf3: &AMF3Encoder,
     s: &Length,
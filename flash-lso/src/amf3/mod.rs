@@ -1,12 +1,25 @@
 /// Support for custom encoders / decoders
 pub mod custom_encoder;
+/// Deserializing values into types that implement `Deserialize`
+#[cfg(feature = "serde")]
+pub mod de;
 /// Cache pool for the 3 amf3 cache types
+#[cfg(feature = "std")]
 pub mod element_cache;
 /// Abstraction over the AMF3 length and reference types
 pub mod length;
 /// Reading of AMF3 data
 pub mod read;
 /// AMF3 type markers
-mod type_marker;
+pub mod type_marker;
+/// Streaming reading of AMF3 data
+pub mod visit;
 /// Writing of AMF3 data
+#[cfg(feature = "std")]
 pub mod write;
+
+#[cfg(feature = "serde")]
+pub use de::from_value;
+pub use read::{decode_one, read_i29, read_u29};
+#[cfg(feature = "std")]
+pub use write::{write_element, write_i29, write_u29};
@@ -2,59 +2,36 @@ use crate::amf3::custom_encoder::ExternalDecoderFn;
 use crate::amf3::type_marker::TypeMarker;
 
 use crate::amf3::length::Length;
-use crate::nom_utils::AMFResult;
+use crate::errors::{Error, ReferenceTable};
+use crate::nom_utils::{self, AMFResult};
 use crate::types::*;
 use crate::types::{Element, Value};
+use crate::Set;
 use crate::PADDING;
-use enumset::EnumSet;
 use nom::bytes::complete::tag;
 use nom::combinator::map;
 use nom::error::{make_error, ErrorKind};
-use nom::lib::std::collections::HashMap;
 use nom::multi::{many_m_n, separated_list0};
 use nom::number::complete::{be_f64, be_i32, be_u32, be_u8};
 use nom::take;
 use nom::take_str;
 use nom::Err;
 
-use std::convert::{TryFrom, TryInto};
-use std::ops::DerefMut;
-use std::rc::Rc;
-
-const REFERENCE_FLAG: u32 = 0x01;
-
-#[allow(clippy::unusual_byte_groupings)]
-fn read_int_signed(i: &[u8]) -> AMFResult<'_, i32> {
-    // Read the first byte of the number
-    let (mut i, num) = be_u8(i)?;
-    let mut value = (num & 0b01111111) as i32;
-    // Check if we have another byte
-    if num & 0b10000000 == 0 {
-        return Ok((i, value));
-    }
-
-    for _ in 0..2 {
-        let (j, num) = be_u8(i)?;
-        i = j;
-        value = (value << 7) | ((num & 0b01111111) as i32);
-        // Check if we have another byte
-        if num & 0b10000000 == 0 {
-            return Ok((i, value));
-        }
-    }
-    let (i, num) = be_u8(i)?;
-    value = (value << 8) | (num as i32);
-
-    // Negate if negative
-    if value & 0b000_1000000_0000000_0000000_00000000 != 0 {
-        value -= 0b001_0000000_0000000_0000000_00000000;
-    }
-
-    Ok((i, value))
-}
-
-#[allow(clippy::unusual_byte_groupings)]
-fn read_int(i: &[u8]) -> AMFResult<'_, u32> {
+use alloc::borrow::Cow;
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::rc::Rc;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::convert::{TryFrom, TryInto};
+
+pub(crate) const REFERENCE_FLAG: u32 = 0x01;
+
+/// Read an AMF3 variable length unsigned integer (U29), as used for lengths and references
+/// This is 1 to 4 bytes, the first 3 give 7 bits each and have the high bit set if another byte follows,
+/// the 4th byte gives 8 bits
+pub fn read_u29(i: &[u8]) -> AMFResult<'_, u32> {
     // Read the first byte of the number
     let (mut i, num) = be_u8(i)?;
     let mut value = (num & 0b01111111) as u32;
@@ -75,53 +52,26 @@ fn read_int(i: &[u8]) -> AMFResult<'_, u32> {
     let (i, num) = be_u8(i)?;
     value = (value << 8) | (num as u32);
 
-    if value & 0b000_1000000_0000000_0000000_00000000 != 0 {
-        value <<= 1;
-        value += 1;
-    }
-
     Ok((i, value))
 }
 
-#[cfg(test)]
-mod read_number_tests {
-    use crate::amf3::read::{read_int, read_int_signed};
-
-    #[test]
-    fn test_read_1byte_number() {
-        assert_eq!(0b00101011, read_int_signed(&[0b00101011]).unwrap().1)
-    }
-
-    #[test]
-    fn test_read_4byte_number() {
-        let i = &[0b10000000, 0b11000000, 0b10000000, 0b10000000];
-        assert_eq!(2097280, read_int_signed(i).unwrap().1);
-    }
-
-    #[test]
-    fn read_neg_number() {
-        assert_eq!(-268435455, read_int_signed(&[192, 128, 128, 1]).unwrap().1);
-    }
-
-    #[test]
-    fn test_read_1byte_number_unsigned() {
-        assert_eq!(0b00101011, read_int(&[0b00101011]).unwrap().1)
-    }
+/// Read an AMF3 variable length signed integer (I29), as used for the integer type
+/// This is encoded as a U29 holding the 29 bit two's complement of the value, see `read_u29`
+#[allow(clippy::unusual_byte_groupings)]
+pub fn read_i29(i: &[u8]) -> AMFResult<'_, i32> {
+    let (i, value) = read_u29(i)?;
+    let mut value = value as i32;
 
-    #[test]
-    fn test_read_4byte_number_unsigned() {
-        let i = &[0b10000000, 0b11000000, 0b10000000, 0b10000000];
-        assert_eq!(2097280, read_int(i).unwrap().1);
+    // Negate if negative
+    if value & 0b000_1000000_0000000_0000000_00000000 != 0 {
+        value -= 0b001_0000000_0000000_0000000_00000000;
     }
 
-    #[test]
-    fn read_neg_number_unsigned() {
-        assert_eq!(536870915, read_int(&[192, 128, 128, 1]).unwrap().1);
-    }
+    Ok((i, value))
 }
 
-fn read_length(i: &[u8]) -> AMFResult<'_, Length> {
-    let (i, val) = read_int(i)?;
+pub(crate) fn read_length(i: &[u8]) -> AMFResult<'_, Length> {
+    let (i, val) = read_u29(i)?;
     Ok((
         i,
         match val & REFERENCE_FLAG == 0 {
@@ -132,21 +82,196 @@ fn read_length(i: &[u8]) -> AMFResult<'_, Length> {
 }
 
 fn parse_element_int(i: &[u8]) -> AMFResult<'_, Rc<Value>> {
-    let (i, s) = map(read_int_signed, Value::Integer)(i)?;
+    let (i, s) = map(read_i29, Value::Integer)(i)?;
     Ok((i, Rc::new(s)))
 }
 
+/// How often a class was seen while decoding
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ClassStats {
+    /// The number of times a full definition of the class was read
+    pub definitions: usize,
+    /// The number of times the class was referenced from the trait reference table
+    pub references: usize,
+}
+
+/// Type used for `AMF3Decoder::name_transform`, giving the name to use for each name that is read
+pub type NameTransformFn = Rc<dyn Fn(&str) -> String>;
+
+/// An AMF3 body read with `AMF3Decoder::parse_body_checked`
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParsedBody {
+    /// The elements of the body
+    pub elements: Vec<Element>,
+    /// The number of bytes left after the padding that ends the last element, such as data appended to the file
+    /// These are always at the end of the input, so can be found with `&input[input.len() - trailing_bytes..]`
+    pub trailing_bytes: usize,
+}
+
+/// The bytes a value was read from, see `AMF3Decoder::parse_with_spans`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Span {
+    /// The path to the value, the names of the element and properties it is nested in separated by `.`,
+    /// with items of arrays and vectors named by index and dictionary entries as `<index>.key` and `<index>.value`
+    pub path: String,
+    /// The offset of the type marker of the value
+    pub start: usize,
+    /// The offset of the first byte after the value
+    pub end: usize,
+}
+
+/// How strings that are not valid UTF-8 are handled when decoding
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum StringMode {
+    /// Fail to parse with `ErrorKind::Alpha`
+    #[default]
+    Strict,
+    /// Replace invalid sequences with U+FFFD and record the offset of the string, see `AMF3Decoder::lossy_string_offsets`
+    Lossy,
+}
+
+/// The default `max_depth` of `AMF3Decoder` and `AMF0Decoder`
+/// Real saves are rarely nested more than 10 deep, while values nested this deep can still be written (the most
+/// stack hungry use) with well under a megabyte of stack in release builds
+pub const DEFAULT_MAX_DEPTH: usize = 128;
+
 /// Handles decoding AMF3
-#[derive(Default)]
 pub struct AMF3Decoder {
     /// The table used to cache repeated byte strings
+    /// This keeps a copy of every distinct non-empty string (and class and property name) read until the next `reset`,
+    /// in addition to the decoded values, use `clear_string_table` to free it once no more references to it will be read
     pub string_reference_table: Vec<Vec<u8>>,
     /// The table used to cache repeated trait definitions
     pub trait_reference_table: Vec<ClassDefinition>,
     /// The table used to cache repeated objects
     pub object_reference_table: Vec<Rc<Value>>,
     /// Encoders used for handling externalized types
-    pub external_decoders: HashMap<String, ExternalDecoderFn>,
+    pub external_decoders: BTreeMap<String, ExternalDecoderFn>,
+    /// If true, record how often each class definition is read, see `class_stats`
+    /// Defaults to false
+    pub record_class_stats: bool,
+    /// Statistics per class name, only populated if `record_class_stats` is set
+    class_stats: BTreeMap<String, ClassStats>,
+    /// If true, count how many values of each type are read, see `marker_counts`
+    /// Defaults to false
+    pub record_marker_counts: bool,
+    /// Number of values read per type marker, only populated if `record_marker_counts` is set
+    marker_counts: BTreeMap<TypeMarker, usize>,
+    /// The maximum number of items an array, vector or dictionary may declare, larger collections will fail with `ErrorKind::TooLarge`
+    /// Defaults to None (unlimited)
+    pub max_collection_len: Option<usize>,
+    /// The maximum total length of the strings held in `string_reference_table`, adding a string that would go over this
+    /// will fail with `ErrorKind::TooLarge`
+    /// Defaults to None (unlimited)
+    pub max_string_table_bytes: Option<usize>,
+    /// The maximum length in bytes of a single string (including names, class names and vector type names), a longer
+    /// declared length will fail with `ErrorKind::TooLarge` before anything is read or copied
+    /// XML and byte arrays are not limited by this
+    /// Defaults to None (unlimited)
+    pub max_string_len: Option<usize>,
+    /// The maximum number of containers (objects, arrays, vectors and dictionaries) that may be nested in each other,
+    /// reading a container nested any deeper will fail with `ErrorKind::TooLarge`
+    /// Reading doesn't recurse, but most other uses of a value (such as comparing, writing or dropping it) do, so
+    /// without a limit a small input can give a value that overflows the stack when it is used
+    /// Defaults to `Some(DEFAULT_MAX_DEPTH)`
+    pub max_depth: Option<usize>,
+    /// The number of containers currently being read, see `max_depth`
+    depth: usize,
+    /// Total length of the strings added to `string_reference_table` since the last `reset`
+    string_table_bytes: usize,
+    /// How to handle strings that are not valid UTF-8
+    /// Defaults to `StringMode::Strict`
+    pub string_decoding: StringMode,
+    /// Offsets of the strings that were decoded lossily, see `lossy_string_offsets`
+    lossy_string_offsets: Vec<usize>,
+    /// If true, strings that are not valid UTF-8 are decoded as with `StringMode::Lossy` rather than failing, and the
+    /// path and raw bytes of each are recorded, see `invalid_strings`
+    /// This keeps track of the path of every value being read, so reading is slower while it is set
+    /// Defaults to false
+    pub record_invalid_strings: bool,
+    /// The strings that were not valid UTF-8, see `invalid_strings`
+    invalid_strings: Vec<(String, Vec<u8>)>,
+    /// If true, each object in an object vector must have a class name matching the type name of the vector, otherwise
+    /// reading fails with `ErrorKind::Verify` at the start of the first object that doesn't match
+    /// Vectors with an empty, `*` or `Object` type name accept anything, and items that aren't objects (such as
+    /// null or strings) are not checked. As class hierarchies aren't stored, objects of a subclass of the type don't match
+    /// Defaults to false
+    pub validate_vector_types: bool,
+    /// If true, an unknown type marker is read as `Value::Unsupported` rather than failing with `ErrorKind::HexDigit`
+    /// Nothing after the marker is skipped, so the data that follows is read as the next value, see `unknown_markers`
+    /// Defaults to false
+    pub lenient_markers: bool,
+    /// Applied to the name of each element and object property as it is read, such as to strip a prefix
+    /// Class names and the static property names kept in class definitions are left unchanged, so to write the same
+    /// data back the caller must apply the inverse transform to the element names first
+    /// Defaults to None (names are kept as read)
+    pub name_transform: Option<NameTransformFn>,
+    /// If true, elements and object properties with the same name share a single copy of it rather than each
+    /// allocating their own, which saves memory for bodies holding many objects of the same class
+    /// Names are kept in a pool until the next `reset`. Names read by an `AMF0Decoder` are not shared
+    /// Defaults to false
+    pub intern_names: bool,
+    /// The names shared between elements when `intern_names` is set
+    name_pool: Set<Rc<str>>,
+    /// Offsets and values of the unknown type markers that were read, see `unknown_markers`
+    unknown_markers: Vec<(usize, u8)>,
+    /// Address of the body currently being parsed, used to calculate offsets
+    /// For a value read on its own with `parse_single_element`, this is the address of that value
+    body_start: usize,
+    /// True while a body or a value read on its own is being parsed, so nested values don't move `body_start`
+    pub(crate) reading_body: bool,
+    /// True while parsing with `parse_with_spans`
+    record_spans: bool,
+    /// The spans of the values read so far, in the order they were started
+    spans: Vec<Span>,
+    /// The index in `spans` of each value currently being read, along with how many unnamed values have been read inside it
+    span_stack: Vec<(usize, usize)>,
+    /// The name to give to the next value read, unnamed values are named by their index in their parent
+    span_name: Option<String>,
+    /// The stack of containers being read by `parse_nested`, kept between values to reuse its allocation
+    containers: Vec<Container>,
+    /// True while values are only reported to `ReadEvents` that don't build them, containers are then not built and
+    /// the object reference table only holds placeholders
+    visiting: bool,
+}
+
+impl Default for AMF3Decoder {
+    fn default() -> Self {
+        Self {
+            string_reference_table: Vec::new(),
+            trait_reference_table: Vec::new(),
+            object_reference_table: Vec::new(),
+            external_decoders: BTreeMap::new(),
+            record_class_stats: false,
+            class_stats: BTreeMap::new(),
+            record_marker_counts: false,
+            marker_counts: BTreeMap::new(),
+            max_collection_len: None,
+            max_string_table_bytes: None,
+            max_string_len: None,
+            max_depth: Some(DEFAULT_MAX_DEPTH),
+            depth: 0,
+            string_table_bytes: 0,
+            string_decoding: StringMode::default(),
+            lossy_string_offsets: Vec::new(),
+            record_invalid_strings: false,
+            invalid_strings: Vec::new(),
+            validate_vector_types: false,
+            lenient_markers: false,
+            name_transform: None,
+            intern_names: false,
+            name_pool: Set::default(),
+            unknown_markers: Vec::new(),
+            body_start: 0,
+            reading_body: false,
+            record_spans: false,
+            spans: Vec::new(),
+            span_stack: Vec::new(),
+            span_name: None,
+            containers: Vec::new(),
+            visiting: false,
+        }
+    }
 }
 
 fn parse_element_number(i: &[u8]) -> AMFResult<'_, Rc<Value>> {
@@ -154,20 +279,299 @@ fn parse_element_number(i: &[u8]) -> AMFResult<'_, Rc<Value>> {
     Ok((i, Rc::new(v)))
 }
 
+/// Check if an item of an object vector has the type of the vector, see `AMF3Decoder::validate_vector_types`
+fn has_vector_type(item: &Value, type_name: &str) -> bool {
+    match item {
+        Value::Object(_, class_def) | Value::Custom(_, _, class_def) => {
+            class_def.as_ref().is_some_and(|def| def.name == type_name)
+        }
+        _ => true,
+    }
+}
+
+/// The fewest bytes a dictionary entry can take, as the key and value are each at least a type marker
+const MIN_DICTIONARY_PAIR_BYTES: usize = 2;
+
+/// Check that `i` is long enough to hold a dictionary of `len` entries, so that huge declared lengths fail before
+/// anything is allocated for them, giving the number of values (keys and values) to read
+pub(crate) fn check_dictionary_len(i: &[u8], len: usize) -> Result<usize, Err<Error<'_>>> {
+    let min_bytes = len
+        .checked_mul(MIN_DICTIONARY_PAIR_BYTES)
+        .ok_or_else(|| Err::Error(make_error(i, ErrorKind::TooLarge)))?;
+    if i.len() < min_bytes {
+        return Err(Err::Error(make_error(i, ErrorKind::TooLarge)));
+    }
+    Ok(len * 2)
+}
+
+/// Apply `transform` to the name of an element or property, sharing an equal name already in `pool` if one is given
+fn make_name(
+    name: &str,
+    transform: Option<&NameTransformFn>,
+    pool: Option<&mut Set<Rc<str>>>,
+) -> Rc<str> {
+    let transformed;
+    let name = match transform {
+        Some(transform) => {
+            transformed = transform(name);
+            transformed.as_str()
+        }
+        None => name,
+    };
+
+    match pool {
+        Some(pool) => match pool.get(name) {
+            Some(shared) => Rc::clone(shared),
+            None => {
+                let shared: Rc<str> = Rc::from(name);
+                pool.insert(Rc::clone(&shared));
+                shared
+            }
+        },
+        None => Rc::from(name),
+    }
+}
+
+/// Where the bytes of a string read by `AMF3Decoder::read_byte_stream` are
+enum StringBytes<'a> {
+    /// Inline in the input
+    Inline(&'a [u8]),
+    /// In the string reference table at this index
+    Reference(usize),
+}
+
+/// Check that `i` is long enough to hold a vector of `len` items of `item_size` bytes, so that huge declared lengths
+/// fail with `ErrorKind::TooLarge` before anything is allocated for them, even where `len * item_size` would overflow
+pub(crate) fn check_vector_len(
+    i: &[u8],
+    len: usize,
+    item_size: usize,
+) -> Result<(), Err<Error<'_>>> {
+    match len.checked_mul(item_size) {
+        Some(min_bytes) if i.len() >= min_bytes => Ok(()),
+        _ => Err(Err::Error(make_error(i, ErrorKind::TooLarge))),
+    }
+}
+
+/// A container that `AMF3Decoder::parse_nested` is reading the values of
+struct Container {
+    /// The index of the container in the object reference table
+    index: usize,
+    /// The number of values read into the container so far
+    count: usize,
+    kind: ContainerKind,
+}
+
+pub(crate) enum ContainerKind {
+    /// An object that isn't external, its static properties are read and then its dynamic members if it is dynamic
+    Object {
+        class_def: ClassDefinition,
+        elements: Vec<Element>,
+        /// The name of the property being read
+        name: Option<Rc<str>>,
+    },
+    /// An array, its associative part is read until the empty key and then `len` dense values
+    Array {
+        dense: Vec<Rc<Value>>,
+        assoc: Vec<Element>,
+        len: usize,
+        /// The name of the associative element being read, None while reading the dense part
+        name: Option<Rc<str>>,
+        /// The number of values read before the dense part, once the empty key ending the associative part has been read
+        dense_start: Option<usize>,
+    },
+    ObjectVector {
+        items: Vec<Rc<Value>>,
+        len: usize,
+        type_name: String,
+        fixed_length: bool,
+        /// True if items must have the type of the vector, see `AMF3Decoder::validate_vector_types`
+        validate: bool,
+        /// The length of the input from the start of the item being read, where a type mismatch is reported
+        item_start: usize,
+    },
+    Dictionary {
+        pairs: Vec<(Rc<Value>, Rc<Value>)>,
+        len: usize,
+        weak_keys: bool,
+        /// The key of the pair being read, once it has been read
+        key: Option<Rc<Value>>,
+    },
+}
+
+impl Container {
+    fn new(index: usize, kind: ContainerKind) -> Self {
+        Container {
+            index,
+            count: 0,
+            kind,
+        }
+    }
+
+    /// Add the next value that was read to this container, `input` being the input the outermost container was read from
+    /// If `build` is false the value is only counted
+    fn push<'a>(
+        &mut self,
+        value: Rc<Value>,
+        input: &'a [u8],
+        build: bool,
+    ) -> Result<(), Err<Error<'a>>> {
+        self.count += 1;
+        if !build {
+            return Ok(());
+        }
+
+        match &mut self.kind {
+            ContainerKind::Object { elements, name, .. } => elements.push(Element {
+                name: name.take().unwrap_or_default(),
+                value,
+            }),
+            ContainerKind::Array {
+                dense, assoc, name, ..
+            } => match name.take() {
+                Some(name) => assoc.push(Element { name, value }),
+                None => dense.push(value),
+            },
+            ContainerKind::ObjectVector {
+                items,
+                type_name,
+                validate,
+                item_start,
+                ..
+            } => {
+                if *validate && !has_vector_type(&value, type_name) {
+                    return Err(Err::Error(make_error(
+                        &input[input.len() - *item_start..],
+                        ErrorKind::Verify,
+                    )));
+                }
+                items.push(value);
+            }
+            ContainerKind::Dictionary { pairs, key, .. } => match key.take() {
+                Some(key) => pairs.push((key, value)),
+                None => *key = Some(value),
+            },
+        }
+        Ok(())
+    }
+
+    fn into_value(self) -> Value {
+        match self.kind {
+            ContainerKind::Object {
+                class_def,
+                elements,
+                ..
+            } => Value::Object(elements, Some(class_def)),
+            // An array is only strict if the first key was empty
+            ContainerKind::Array { dense, assoc, .. } if assoc.is_empty() => {
+                Value::StrictArray(dense)
+            }
+            ContainerKind::Array { dense, assoc, .. } => {
+                let assoc_len = assoc.len() as u32;
+                Value::ECMAArray(dense, assoc, assoc_len)
+            }
+            ContainerKind::ObjectVector {
+                items,
+                type_name,
+                fixed_length,
+                ..
+            } => Value::VectorObject(items, type_name, fixed_length),
+            ContainerKind::Dictionary {
+                pairs, weak_keys, ..
+            } => Value::Dictionary(pairs, weak_keys),
+        }
+    }
+}
+
+/// The result of starting to read a value, either the whole value or a container whose values are still to be read
+enum Opened {
+    Value(Rc<Value>),
+    /// A value sent by reference, with its index in the object reference table
+    Reference(usize, Rc<Value>),
+    Container(Container),
+}
+
+/// Told about each part of a value as `AMF3Decoder::parse_nested` reads it, this is how `parse_with_visitor` reports
+/// values without building them (see `amf3::visit`)
+pub(crate) trait ReadEvents {
+    /// If false the values that are read are only reported, see `AMF3Decoder::visiting`
+    const BUILD: bool;
+
+    /// Called for a value read from `start` that has nothing nested in it, including externalized objects
+    fn on_value(&mut self, _start: &[u8], _value: &Value) {}
+    /// Called for a value that refers to the value at `index` in the object reference table
+    fn on_reference(&mut self, _index: usize) {}
+    /// Called with the name of the next value, for elements, object properties and the associative part of arrays
+    fn on_name(&mut self, _name: &str) {}
+    /// Called before the values of a container are read
+    fn on_open(&mut self, _kind: &ContainerKind) {}
+    /// Called after the values of a container have been read
+    fn on_close(&mut self, _kind: &ContainerKind) {}
+}
+
+/// Builds the values that are read, without reporting anything
+impl ReadEvents for () {
+    const BUILD: bool = true;
+}
+
+/// Decode exactly one AMF3 value from the start of `i` with a new decoder, giving the value and the bytes that follow it
+/// To decode a sequence of values that may refer to each other, use `AMF3Decoder::decode_one`
+pub fn decode_one(i: &[u8]) -> Result<(Rc<Value>, &[u8]), Error<'_>> {
+    AMF3Decoder::default().decode_one(i)
+}
+
+/// Check if `i` holds exactly one AMF3 value and nothing after it, as written by `ByteArray.writeObject`, such as to
+/// find the byte arrays of an lso that hold a serialized object (see `Lso::byte_arrays`)
+/// Short data can match by chance, a single `0x01` is null and `0x04` followed by one byte below `0x80` is an integer
+pub fn is_amf3_value(i: &[u8]) -> bool {
+    matches!(decode_one(i), Ok((_, rest)) if rest.is_empty())
+}
+
 impl AMF3Decoder {
     fn parse_element_string<'a>(&mut self, i: &'a [u8]) -> AMFResult<'a, Rc<Value>> {
         let (i, s) = map(|i| self.parse_string(i), Value::String)(i)?;
         Ok((i, Rc::new(s)))
     }
 
-    fn parse_string<'a>(&mut self, i: &'a [u8]) -> AMFResult<'a, String> {
-        let (i, bytes) = self.parse_byte_stream(i)?;
-        let bytes_str =
-            String::from_utf8(bytes).map_err(|_| Err::Error(make_error(i, ErrorKind::Alpha)))?;
-        Ok((i, bytes_str))
+    pub(crate) fn parse_string<'a>(&mut self, i: &'a [u8]) -> AMFResult<'a, String> {
+        let (j, bytes) = self.parse_byte_stream(i)?;
+        let bytes_str = match String::from_utf8(bytes) {
+            Ok(s) => s,
+            Err(e) => self.decode_invalid_string(i, e.into_bytes())?,
+        };
+        Ok((j, bytes_str))
     }
 
-    fn parse_class_def<'a>(&mut self, length: u32, i: &'a [u8]) -> AMFResult<'a, ClassDefinition> {
+    /// Decode the bytes of a string at `i` that are not valid UTF-8, replacing invalid sequences if `string_decoding`
+    /// is lossy or `record_invalid_strings` is set, otherwise failing with `ErrorKind::Alpha`
+    fn decode_invalid_string<'a>(
+        &mut self,
+        i: &'a [u8],
+        bytes: Vec<u8>,
+    ) -> Result<String, Err<Error<'a>>> {
+        if self.string_decoding != StringMode::Lossy && !self.record_invalid_strings {
+            return Err(Err::Error(make_error(i, ErrorKind::Alpha)));
+        }
+
+        let offset = (i.as_ptr() as usize).saturating_sub(self.body_start);
+        self.lossy_string_offsets.push(offset);
+        let s = String::from_utf8_lossy(&bytes).into_owned();
+        if self.record_invalid_strings {
+            let path = self
+                .span_stack
+                .last()
+                .map(|(index, _)| self.spans[*index].path.clone())
+                .unwrap_or_default();
+            self.invalid_strings.push((path, bytes));
+        }
+        Ok(s)
+    }
+
+    pub(crate) fn parse_class_def<'a>(
+        &mut self,
+        length: u32,
+        i: &'a [u8],
+    ) -> AMFResult<'a, ClassDefinition> {
         if length & REFERENCE_FLAG == 0 {
             let len_usize: usize = (length >> 1)
                 .try_into()
@@ -176,20 +580,29 @@ impl AMF3Decoder {
             let class_def = self
                 .trait_reference_table
                 .get(len_usize)
-                .ok_or_else(|| Err::Error(make_error(i, ErrorKind::Digit)))?
+                .ok_or_else(|| {
+                    Error::bad_reference(
+                        i,
+                        len_usize,
+                        &self.trait_reference_table,
+                        ReferenceTable::Trait,
+                    )
+                })?
                 .clone();
 
+            if self.record_class_stats {
+                self.class_stats
+                    .entry(class_def.name.clone())
+                    .or_default()
+                    .references += 1;
+            }
+
             return Ok((i, class_def));
         }
         let length = length >> 1;
 
         //TODO: should name be Option<String>
-        let (i, name) = self.parse_byte_stream(i)?;
-        let name_str = if name.is_empty() {
-            "".to_string()
-        } else {
-            String::from_utf8(name).map_err(|_| Err::Error(make_error(i, ErrorKind::Alpha)))?
-        };
+        let (i, name_str) = self.parse_string(i)?;
 
         let encoding = (length & 0x03) as u8;
 
@@ -199,28 +612,28 @@ impl AMF3Decoder {
             .try_into()
             .map_err(|_| Err::Error(make_error(i, ErrorKind::Digit)))?;
 
+        // Each name takes at least a byte, so huge declared counts fail before anything is allocated for them
+        if i.len() < attr_count_usize {
+            return Err(Err::Error(make_error(i, ErrorKind::TooLarge)));
+        }
+
         // Read static attributes if they exist
         let (i, static_props) =
             many_m_n(attr_count_usize, attr_count_usize, |i| self.parse_string(i))(i)?;
 
-        let is_external = encoding & 0b1 == 1;
-        let is_dynamic = encoding & 0b10 == 0b10;
-
-        let mut attributes = EnumSet::empty();
-
-        if is_external {
-            attributes |= Attribute::External;
-        }
-        if is_dynamic {
-            attributes |= Attribute::Dynamic;
-        }
-
         let class_def = ClassDefinition {
             name: name_str,
-            attributes,
+            attributes: Attribute::from_encoding(encoding),
             static_properties: static_props,
         };
 
+        if self.record_class_stats {
+            self.class_stats
+                .entry(class_def.name.clone())
+                .or_default()
+                .definitions += 1;
+        }
+
         self.trait_reference_table.push(class_def.clone());
         Ok((i, class_def))
     }
@@ -229,113 +642,164 @@ impl AMF3Decoder {
         &mut self,
         i: &'a [u8],
         parser: impl FnOnce(&mut Self, &'a [u8], usize) -> AMFResult<'a, Value>,
-    ) -> AMFResult<'a, Rc<Value>> {
+    ) -> AMFResult<'a, Opened> {
         let (i, len) = read_length(i)?;
 
         match len {
-            Length::Reference(index) => {
-                let ref_result = Rc::clone(
-                    self.object_reference_table
-                        .get(index)
-                        .ok_or_else(|| Err::Error(make_error(i, ErrorKind::Digit)))?,
-                );
-
-                Ok((i, ref_result))
-            }
+            Length::Reference(index) => Ok((
+                i,
+                Opened::Reference(index, self.object_reference(i, index)?),
+            )),
             Length::Size(len) => {
                 let len_usize: usize = len
                     .try_into()
                     .map_err(|_| Err::Error(make_error(i, ErrorKind::Digit)))?;
 
-                let initial = Rc::new(Value::Null);
+                // A reference to this value while it is still being parsed resolves to this placeholder,
+                // as a value can't contain itself
                 let index = self.object_reference_table.len();
-                self.object_reference_table.push(initial);
+                self.object_reference_table.push(Rc::new(Value::Null));
 
                 let (i, res) = parser(self, i, len_usize)?;
+                let res = Rc::new(res);
+                if !self.visiting {
+                    self.object_reference_table[index] = Rc::clone(&res);
+                }
 
-                //TODO: this should be an error case and also never happen
-                let mut initial_inner = Rc::get_mut(
-                    self.object_reference_table
-                        .get_mut(index)
-                        .expect("Index not in reference table"),
-                )
-                .expect("Reference still held to rc");
-                *initial_inner.deref_mut() = res;
+                Ok((i, Opened::Value(res)))
+            }
+        }
+    }
 
-                Ok((
-                    i,
-                    Rc::clone(
-                        self.object_reference_table
-                            .get(index)
-                            .expect("Index not in reference table"),
-                    ),
-                ))
+    /// Read the length of a container that may be sent by reference, giving the referenced value or a new container
+    /// As with `parse_reference_or_val` an inline container takes a slot in the object reference table, which holds a
+    /// placeholder until the container has been read
+    fn open_reference_or_val<'a>(
+        &mut self,
+        i: &'a [u8],
+        opener: impl FnOnce(&mut Self, &'a [u8], usize) -> AMFResult<'a, ContainerKind>,
+    ) -> AMFResult<'a, Opened> {
+        let (i, len) = read_length(i)?;
+
+        match len {
+            Length::Reference(index) => Ok((
+                i,
+                Opened::Reference(index, self.object_reference(i, index)?),
+            )),
+            Length::Size(len) => {
+                let len_usize: usize = len
+                    .try_into()
+                    .map_err(|_| Err::Error(make_error(i, ErrorKind::Digit)))?;
+
+                let index = self.object_reference_table.len();
+                self.object_reference_table.push(Rc::new(Value::Null));
+
+                let (i, kind) = opener(self, i, len_usize)?;
+                Ok((i, Opened::Container(Container::new(index, kind))))
             }
         }
     }
 
-    fn parse_byte_stream<'a>(&mut self, i: &'a [u8]) -> AMFResult<'a, Vec<u8>> {
+    /// Get the value at `index` in the object reference table
+    fn object_reference<'a>(&self, i: &'a [u8], index: usize) -> Result<Rc<Value>, Err<Error<'a>>> {
+        self.object_reference_table
+            .get(index)
+            .map(Rc::clone)
+            .ok_or_else(|| {
+                Error::bad_reference(
+                    i,
+                    index,
+                    &self.object_reference_table,
+                    ReferenceTable::Object,
+                )
+            })
+    }
+
+    /// Read a string or a reference to one without copying it, adding a non-empty inline string to the string reference table
+    fn read_byte_stream<'a>(&mut self, i: &'a [u8]) -> AMFResult<'a, StringBytes<'a>> {
         let (i, len) = read_length(i)?;
 
         match len {
             Length::Size(len) => {
+                if matches!(self.max_string_len, Some(max) if len as usize > max) {
+                    return Err(Err::Error(make_error(i, ErrorKind::TooLarge)));
+                }
+
                 if len == 0 {
-                    Ok((i, vec![]))
+                    Ok((i, StringBytes::Inline(&[])))
                 } else {
-                    let (i, bytes) = take!(i, len)?;
+                    let (rest, bytes) = take!(i, len)?;
+                    let total = self.string_table_bytes.saturating_add(bytes.len());
+                    if matches!(self.max_string_table_bytes, Some(max) if total > max) {
+                        return Err(Err::Error(make_error(i, ErrorKind::TooLarge)));
+                    }
+                    self.string_table_bytes = total;
                     self.string_reference_table.push(bytes.to_vec());
-                    Ok((i, bytes.to_vec()))
+                    Ok((rest, StringBytes::Inline(bytes)))
                 }
             }
             Length::Reference(index) => {
-                let ref_result = self
-                    .string_reference_table
-                    .get(index)
-                    .ok_or_else(|| Err::Error(make_error(i, ErrorKind::Digit)))?
-                    .clone();
+                if index >= self.string_reference_table.len() {
+                    return Err(Error::bad_reference(
+                        i,
+                        index,
+                        &self.string_reference_table,
+                        ReferenceTable::String,
+                    ));
+                }
 
-                Ok((i, ref_result))
+                Ok((i, StringBytes::Reference(index)))
             }
         }
     }
 
-    fn parse_object_static<'a>(
-        &mut self,
-        i: &'a [u8],
-        class_def: &ClassDefinition,
-    ) -> AMFResult<'a, Vec<Element>> {
-        let mut elements = Vec::new();
-        let mut i = i;
+    fn parse_byte_stream<'a>(&mut self, i: &'a [u8]) -> AMFResult<'a, Vec<u8>> {
+        let (i, bytes) = self.read_byte_stream(i)?;
+        let bytes = match bytes {
+            StringBytes::Inline(bytes) => bytes.to_vec(),
+            StringBytes::Reference(index) => self.string_reference_table[index].clone(),
+        };
+        Ok((i, bytes))
+    }
 
-        for name in class_def.static_properties.iter() {
-            let (j, e) = self.parse_single_element(i)?;
+    /// Read the name of an element or property and apply `name_transform`, sharing it if `intern_names` is set
+    /// Gives None for an empty name, which ends the dynamic members of an object or the associative part of an array
+    pub(crate) fn parse_name<'a>(&mut self, i: &'a [u8]) -> AMFResult<'a, Option<Rc<str>>> {
+        let (j, bytes) = self.read_byte_stream(i)?;
+        let bytes = match bytes {
+            StringBytes::Inline(bytes) => bytes,
+            StringBytes::Reference(index) => self.string_reference_table[index].as_slice(),
+        };
+        if bytes.is_empty() {
+            return Ok((j, None));
+        }
 
-            elements.push(Element {
-                name: name.clone(),
-                value: e,
-            });
+        let name = match core::str::from_utf8(bytes) {
+            Ok(name) => Cow::Borrowed(name),
+            Err(_) => Cow::Owned(self.decode_invalid_string(i, bytes.to_vec())?),
+        };
 
-            i = j;
-        }
+        let pool = self.intern_names.then_some(&mut self.name_pool);
+        let name = make_name(&name, self.name_transform.as_ref(), pool);
+        Ok((j, Some(name)))
+    }
 
-        Ok((i, elements))
+    /// Get the name to give an element or property from a name that was already read, see `parse_name`
+    fn element_name(&mut self, name: &str) -> Rc<str> {
+        let pool = self.intern_names.then_some(&mut self.name_pool);
+        make_name(name, self.name_transform.as_ref(), pool)
     }
 
-    pub(crate) fn parse_element_object<'a>(&mut self, i: &'a [u8]) -> AMFResult<'a, Rc<Value>> {
-        let (i, mut length) = read_int(i)?;
+    fn open_object<'a>(&mut self, i: &'a [u8]) -> AMFResult<'a, Opened> {
+        let (i, mut length) = read_u29(i)?;
 
         if length & REFERENCE_FLAG == 0 {
             let len_usize: usize = (length >> 1)
                 .try_into()
                 .map_err(|_| Err::Error(make_error(i, ErrorKind::Digit)))?;
 
-            let obj = Rc::clone(
-                self.object_reference_table
-                    .get(len_usize)
-                    .ok_or_else(|| Err::Error(make_error(i, ErrorKind::Digit)))?,
-            );
-
-            return Ok((i, obj));
+            let value = self.object_reference(i, len_usize)?;
+            return Ok((i, Opened::Reference(len_usize, value)));
         }
         length >>= 1;
 
@@ -346,106 +810,60 @@ impl AMF3Decoder {
         // Class def
         let (i, class_def) = self.parse_class_def(length, i)?;
 
-        {
-            let mut_obj = Rc::get_mut(
-                self.object_reference_table
-                    .get_mut(index)
-                    .expect("Index invalid"),
-            )
-            .expect("Unable to get Object");
-            if let Value::Object(_, ref mut def) = mut_obj {
-                *def = Some(class_def.clone());
-            }
+        // References to this object while its members are being read resolve to it without any members
+        if !self.visiting {
+            self.object_reference_table[index] =
+                Rc::new(Value::Object(Vec::new(), Some(class_def.clone())));
         }
 
-        let mut elements = Vec::new();
-        let external_elements;
-
-        let mut i = i;
         if class_def.attributes.contains(Attribute::External) {
             return if self.external_decoders.contains_key(&class_def.name) {
                 let decoder = Rc::clone(&self.external_decoders[&class_def.name]);
-                let (j, v) = decoder(i, self)?;
-                external_elements = v;
-                i = j;
+                let (i, external_elements) = decoder(i, self)?;
                 //TODO: should it be possible to have both dynamic and external together
-                Ok((
-                    i,
-                    Rc::new(Value::Custom(
-                        external_elements,
-                        vec![],
-                        Some(class_def.clone()),
-                    )),
-                ))
+                let obj = Rc::new(Value::Custom(external_elements, vec![], Some(class_def)));
+                if !self.visiting {
+                    self.object_reference_table[index] = Rc::clone(&obj);
+                }
+                Ok((i, Opened::Value(obj)))
             } else {
                 Err(Err::Error(make_error(i, ErrorKind::Tag)))
             };
         }
 
-        let mut i = i;
-        if class_def.attributes.contains(Attribute::Dynamic) {
-            let (j, x) = self.parse_object_static(i, &class_def)?;
-            elements.extend(x);
-
-            // Read dynamic
-            let (mut j, mut attr) = self.parse_byte_stream(j)?;
-            while !attr.is_empty() {
-                let attr_str = String::from_utf8(attr)
-                    .map_err(|_| Err::Error(make_error(i, ErrorKind::Alpha)))?;
-                let (k, val) = self.parse_single_element(j)?;
-                elements.push(Element {
-                    name: attr_str,
-                    value: val,
-                });
-
-                let (k, attr2) = self.parse_byte_stream(k)?;
-                j = k;
-                attr = attr2;
-            }
-            i = j;
-        }
-        if class_def.attributes.is_empty() {
-            let (j, x) = self.parse_object_static(i, &class_def)?;
-            elements.extend(x);
-
-            i = j;
-        }
+        let kind = ContainerKind::Object {
+            elements: Vec::with_capacity(class_def.static_properties.len()),
+            class_def,
+            name: None,
+        };
+        Ok((i, Opened::Container(Container::new(index, kind))))
+    }
 
-        {
-            let mut_obj = Rc::get_mut(
-                self.object_reference_table
-                    .get_mut(index)
-                    .expect("Index invalid"),
-            )
-            .expect("Unable to get Object");
-            if let Value::Object(ref mut elements_inner, _) = mut_obj {
-                *elements_inner = elements;
-            }
+    /// Check the declared number of items in a collection against `max_collection_len`
+    pub(crate) fn check_collection_len<'a>(
+        &self,
+        i: &'a [u8],
+        len: usize,
+    ) -> Result<(), Err<Error<'a>>> {
+        match self.max_collection_len {
+            Some(max) if len > max => Err(Err::Error(make_error(i, ErrorKind::TooLarge))),
+            _ => Ok(()),
         }
-
-        Ok((
-            i,
-            Rc::clone(
-                self.object_reference_table
-                    .get(index)
-                    .expect("Index invalid"),
-            ),
-        ))
     }
 
-    fn parse_element_byte_array<'a>(&mut self, i: &'a [u8]) -> AMFResult<'a, Rc<Value>> {
+    fn parse_element_byte_array<'a>(&mut self, i: &'a [u8]) -> AMFResult<'a, Opened> {
         self.parse_reference_or_val(i, |_this, i, len| {
             let (i, bytes) = take!(i, len)?;
             Ok((i, Value::ByteArray(bytes.to_vec())))
         })
     }
 
-    fn parse_element_vector_int<'a>(&mut self, i: &'a [u8]) -> AMFResult<'a, Rc<Value>> {
-        self.parse_reference_or_val(i, |_this, i, len| {
+    fn parse_element_vector_int<'a>(&mut self, i: &'a [u8]) -> AMFResult<'a, Opened> {
+        self.parse_reference_or_val(i, |this, i, len| {
+            this.check_collection_len(i, len)?;
+
             // There must be at least `len * 4` (i32 = 4 bytes) bytes to read this, this prevents OOM errors with v.large vecs
-            if i.len() < len * 4 {
-                return Err(Err::Error(make_error(i, ErrorKind::TooLarge)));
-            }
+            check_vector_len(i, len, 4)?;
 
             let (i, fixed_length) = be_u8(i)?;
 
@@ -455,12 +873,12 @@ impl AMF3Decoder {
         })
     }
 
-    fn parse_element_vector_uint<'a>(&mut self, i: &'a [u8]) -> AMFResult<'a, Rc<Value>> {
-        self.parse_reference_or_val(i, |_this, i, len| {
+    fn parse_element_vector_uint<'a>(&mut self, i: &'a [u8]) -> AMFResult<'a, Opened> {
+        self.parse_reference_or_val(i, |this, i, len| {
+            this.check_collection_len(i, len)?;
+
             // There must be at least `len * 4` (u32 = 4 bytes) bytes to read this, this prevents OOM errors with v.large vecs
-            if i.len() < len * 4 {
-                return Err(Err::Error(make_error(i, ErrorKind::TooLarge)));
-            }
+            check_vector_len(i, len, 4)?;
             let (i, fixed_length) = be_u8(i)?;
 
             let (i, ints) = many_m_n(len, len, be_u32)(i)?;
@@ -469,12 +887,12 @@ impl AMF3Decoder {
         })
     }
 
-    fn parse_element_vector_double<'a>(&mut self, i: &'a [u8]) -> AMFResult<'a, Rc<Value>> {
-        self.parse_reference_or_val(i, |_this, i, len| {
-            // There must be at least `len * 8` (f64 = 8 bytes) bytes to read this, this prevents OOM errors with v.large dicts
-            if i.len() < len * 8 {
-                return Err(Err::Error(make_error(i, ErrorKind::TooLarge)));
-            }
+    fn parse_element_vector_double<'a>(&mut self, i: &'a [u8]) -> AMFResult<'a, Opened> {
+        self.parse_reference_or_val(i, |this, i, len| {
+            this.check_collection_len(i, len)?;
+
+            // There must be at least `len * 8` (f64 = 8 bytes) bytes to read this, this prevents OOM errors with v.large vecs
+            check_vector_len(i, len, 8)?;
             let (i, fixed_length) = be_u8(i)?;
 
             let (i, numbers) = many_m_n(len, len, be_f64)(i)?;
@@ -483,150 +901,718 @@ impl AMF3Decoder {
         })
     }
 
-    fn parse_element_object_vector<'a>(&mut self, i: &'a [u8]) -> AMFResult<'a, Rc<Value>> {
-        self.parse_reference_or_val(i, |this, i, len| {
+    fn open_object_vector<'a>(&mut self, i: &'a [u8]) -> AMFResult<'a, Opened> {
+        self.open_reference_or_val(i, |this, i, len| {
+            this.check_collection_len(i, len)?;
+
             let (i, fixed_length) = be_u8(i)?;
 
-            let (i, object_type_name) = this.parse_string(i)?;
+            let (i, type_name) = this.parse_string(i)?;
 
-            let (i, elems) = many_m_n(len, len, |i| this.parse_single_element(i))(i)?;
+            // Every item is at least one byte (its marker), so this can't allocate more than the input size
+            // The items are objects, which each take a slot in the reference table when they are read inline
+            this.object_reference_table.reserve(len.min(i.len()));
+            let validate =
+                this.validate_vector_types && !matches!(type_name.as_str(), "" | "*" | "Object");
 
             Ok((
                 i,
-                Value::VectorObject(elems, object_type_name, fixed_length == 1),
+                ContainerKind::ObjectVector {
+                    items: Vec::with_capacity(len.min(i.len())),
+                    len,
+                    type_name,
+                    fixed_length: fixed_length == 1,
+                    validate,
+                    item_start: i.len(),
+                },
             ))
         })
     }
 
-    fn parse_element_array<'a>(&mut self, i: &'a [u8]) -> AMFResult<'a, Rc<Value>> {
-        self.parse_reference_or_val(i, |this, i, length_usize| {
-            // There must be at least `length_usize` bytes to read this, this prevents OOM errors with v.large dicts
-            if i.len() < length_usize {
-                return Err(Err::Error(make_error(i, ErrorKind::TooLarge)));
-            }
-
-            let (i, mut key) = this.parse_byte_stream(i)?;
-
-            if key.is_empty() {
-                let (i, elements) =
-                    many_m_n(length_usize, length_usize, |i| this.parse_single_element(i))(i)?;
-
-                return Ok((i, Value::StrictArray(elements)));
-            }
-
-            let mut elements = Vec::with_capacity(length_usize);
-
-            let mut i = i;
-            while !key.is_empty() {
-                let (j, e) = this.parse_single_element(i)?;
-                let key_str = String::from_utf8(key)
-                    .map_err(|_| Err::Error(make_error(i, ErrorKind::Alpha)))?;
+    fn open_array<'a>(&mut self, i: &'a [u8]) -> AMFResult<'a, Opened> {
+        self.open_reference_or_val(i, |this, i, len| {
+            this.check_collection_len(i, len)?;
 
-                elements.push(Element {
-                    name: key_str,
-                    value: e,
-                });
-                let (j, k) = this.parse_byte_stream(j)?;
-                i = j;
-                key = k;
+            // There must be at least `len` bytes to read this, this is checked again once the associative part has
+            // been read, before anything is allocated for the dense part
+            if i.len() < len {
+                return Err(Err::Error(make_error(i, ErrorKind::TooLarge)));
             }
 
-            // Must parse `length` elements
-            let (i, el) =
-                many_m_n(length_usize, length_usize, |i| this.parse_single_element(i))(i)?;
-
-            let elements_len = elements.len() as u32;
-            Ok((i, Value::ECMAArray(el, elements, elements_len)))
+            Ok((
+                i,
+                ContainerKind::Array {
+                    dense: Vec::new(),
+                    assoc: Vec::new(),
+                    len,
+                    name: None,
+                    dense_start: None,
+                },
+            ))
         })
     }
 
-    fn parse_element_dict<'a>(&mut self, i: &'a [u8]) -> AMFResult<'a, Rc<Value>> {
-        self.parse_reference_or_val(i, |this, i, len| {
+    fn open_dict<'a>(&mut self, i: &'a [u8]) -> AMFResult<'a, Opened> {
+        self.open_reference_or_val(i, |this, i, len| {
+            this.check_collection_len(i, len)?;
+
             //TODO: implications of this
             let (i, weak_keys) = be_u8(i)?;
 
-            // There must be at least `len * 2` bytes (due to (key,val) pairs) to read this, this prevents OOM errors with v.large dicts
-            if i.len() < len * 2 {
-                return Err(Err::Error(make_error(i, ErrorKind::TooLarge)));
-            }
-
-            let (i, pairs) = many_m_n(len * 2, len * 2, |i| this.parse_single_element(i))(i)?;
-
-            let pairs = pairs
-                .chunks_exact(2)
-                .map(|chunk| (chunk[0].clone(), chunk[1].clone()))
-                .collect::<Vec<_>>();
+            check_dictionary_len(i, len)?;
 
-            Ok((i, Value::Dictionary(pairs, weak_keys == 1)))
+            // The capacity is limited by the length of the input, due to the check above
+            Ok((
+                i,
+                ContainerKind::Dictionary {
+                    pairs: Vec::with_capacity(len),
+                    len,
+                    weak_keys: weak_keys == 1,
+                    key: None,
+                },
+            ))
         })
     }
 
-    fn parse_element_date<'a>(&mut self, i: &'a [u8]) -> AMFResult<'a, Rc<Value>> {
+    fn parse_element_date<'a>(&mut self, i: &'a [u8]) -> AMFResult<'a, Opened> {
+        // Only the reference flag of the length is used for dates, the remaining bits are unused by the spec
+        // References are resolved by `parse_reference_or_val`, so this always reads an inline date
         self.parse_reference_or_val(i, |_this, i, _len| {
             let (i, ms) = be_f64(i)?;
             Ok((i, Value::Date(ms, None)))
         })
     }
 
-    fn parse_element_xml<'a>(&mut self, i: &'a [u8], string: bool) -> AMFResult<'a, Rc<Value>> {
+    fn parse_element_xml<'a>(&mut self, i: &'a [u8], string: bool) -> AMFResult<'a, Opened> {
         self.parse_reference_or_val(i, |_this, i, len| {
-            let (i, data) = take_str!(i, len as u32)?;
+            // There must be at least `len` bytes to read this, a longer declared length fails before taking anything
+            if i.len() < len {
+                return Err(Err::Error(make_error(i, ErrorKind::TooLarge)));
+            }
+            let (i, data) = take_str!(i, len)?;
             Ok((i, Value::XML(data.into(), string)))
         })
     }
 
-    fn read_type_marker<'a>(&self, i: &'a [u8]) -> AMFResult<'a, TypeMarker> {
-        let (i, type_) = be_u8(i)?;
+    /// Read a type marker, giving None for an unknown marker if `lenient_markers` is set
+    pub(crate) fn read_type_marker<'a>(
+        &mut self,
+        i: &'a [u8],
+    ) -> AMFResult<'a, Option<TypeMarker>> {
+        let (j, type_) = be_u8(i)?;
         if let Ok(type_) = TypeMarker::try_from(type_) {
-            Ok((i, type_))
+            if self.record_marker_counts {
+                *self.marker_counts.entry(type_).or_insert(0) += 1;
+            }
+            Ok((j, Some(type_)))
+        } else if self.lenient_markers {
+            let offset = (i.as_ptr() as usize).saturating_sub(self.body_start);
+            self.unknown_markers.push((offset, type_));
+            Ok((j, None))
         } else {
-            Err(Err::Error(make_error(i, ErrorKind::HexDigit)))
+            Err(Err::Error(make_error(j, ErrorKind::HexDigit)))
         }
     }
 
+    /// Create a decoder that reads the flex `ArrayCollection`, `ArrayList` and object proxy types
+    /// Each is read as a `Value::Custom` with the class name in its class definition and the wrapped value as its only
+    /// custom element, see `flex::wrapped_value`
+    #[cfg(feature = "flex")]
+    pub fn with_flex_types() -> Self {
+        let mut decoder = Self::default();
+        crate::extra::flex::read::register_collection_decoders(&mut decoder);
+        decoder
+    }
+
+    /// Clear the string, trait and object reference tables so the decoder can be reused for unrelated data
+    /// References in data parsed after a reset can't refer to anything parsed before it
+    /// Registered `external_decoders`, recorded class statistics and marker counts are kept
+    pub fn reset(&mut self) {
+        self.clear_tables();
+        self.lossy_string_offsets.clear();
+        self.invalid_strings.clear();
+        self.unknown_markers.clear();
+        self.name_pool.clear();
+    }
+
+    /// Clear only the string, trait and object reference tables, keeping what was recorded about the data, see `reset`
+    pub(crate) fn clear_tables(&mut self) {
+        self.string_reference_table.clear();
+        self.string_table_bytes = 0;
+        self.trait_reference_table.clear();
+        self.object_reference_table.clear();
+    }
+
+    /// Clear only the string reference table, freeing the copies of the strings it holds
+    /// Values that were already read are unaffected, but a string reference to a string read before this will then
+    /// fail with `Error::BadReference`, so this is only safe between data that doesn't share strings, such as after a
+    /// body has been read with `parse_body` and before the next one
+    pub fn clear_string_table(&mut self) {
+        self.string_reference_table = Vec::new();
+        self.string_table_bytes = 0;
+    }
+
+    /// Free the unused capacity of the reference tables, such as after `reset` once a large body has been read,
+    /// so that a decoder kept for later use doesn't hold on to memory sized for the largest data it has read
+    pub fn shrink_tables(&mut self) {
+        self.string_reference_table.shrink_to_fit();
+        self.trait_reference_table.shrink_to_fit();
+        self.object_reference_table.shrink_to_fit();
+    }
+
+    /// Get the offset of each string that was decoded lossily with `StringMode::Lossy`, in the order they were read
+    /// Offsets are relative to the start of the slice given to `parse_body`, or to `parse_single_element` for a value read
+    /// on its own, and point to the length (or reference) of the string,
+    /// a string that is sent by reference will be reported again at each reference
+    pub fn lossy_string_offsets(&self) -> &[usize] {
+        &self.lossy_string_offsets
+    }
+
+    /// Get the path and raw bytes of each string that was not valid UTF-8, recorded while `record_invalid_strings`
+    /// is set, in the order they were read
+    /// The path is that of the value the string was read in, as in `Span::path`, so for the name of a property or a class
+    /// name it is the path of the object. Names of the elements of a body, and strings read by a `Visitor`, have an
+    /// empty path. As with `lossy_string_offsets`, a string sent by reference is reported again at each reference
+    /// Cleared at the start of each body
+    pub fn invalid_strings(&self) -> &[(String, Vec<u8>)] {
+        &self.invalid_strings
+    }
+
+    /// Get the offset and value of each unknown type marker that was read with `lenient_markers` set, in the order they were read
+    /// Offsets are relative to the same start as `lossy_string_offsets` and point to the marker itself
+    /// As the length of an unknown value can't be known, anything read after the first of these may be wrong
+    pub fn unknown_markers(&self) -> &[(usize, u8)] {
+        &self.unknown_markers
+    }
+
+    /// Note the start of a new body, so that offsets can be calculated relative to it
+    pub(crate) fn start_body(&mut self, i: &[u8]) {
+        self.body_start = i.as_ptr() as usize;
+        self.lossy_string_offsets.clear();
+        self.invalid_strings.clear();
+        self.unknown_markers.clear();
+    }
+
+    /// Get every class definition that has been read, in the order they were first seen
+    /// Only the first definition of each class name is kept. This is taken from `trait_reference_table`,
+    /// so includes every inline definition read since the last `reset`
+    pub fn collected_class_defs(&self) -> Vec<ClassDefinition> {
+        let mut seen = Set::new();
+        self.trait_reference_table
+            .iter()
+            .filter(|def| seen.insert(def.name.as_str()))
+            .cloned()
+            .collect()
+    }
+
+    /// Get the statistics recorded for each class name, keyed by class name
+    /// This will be empty unless `record_class_stats` was set before decoding
+    pub fn class_stats(&self) -> &BTreeMap<String, ClassStats> {
+        &self.class_stats
+    }
+
+    /// Get the number of values read with each type marker, markers that were never read are left out
+    /// References are counted under the type of the value they refer to, and unknown markers are not counted, see
+    /// `unknown_markers`. This will be empty unless `record_marker_counts` was set before decoding
+    pub fn marker_counts(&self) -> &BTreeMap<TypeMarker, usize> {
+        &self.marker_counts
+    }
+
     /// Parse a single AMF3 element from the input
     #[inline]
     pub fn parse_single_element<'a>(&mut self, i: &'a [u8]) -> AMFResult<'a, Rc<Value>> {
-        let (i, type_) = self.read_type_marker(i)?;
-
-        match type_ {
-            TypeMarker::Undefined => Ok((i, Rc::new(Value::Undefined))),
-            TypeMarker::Null => Ok((i, Rc::new(Value::Null))),
-            TypeMarker::False => Ok((i, Rc::new(Value::Bool(false)))),
-            TypeMarker::True => Ok((i, Rc::new(Value::Bool(true)))),
-            TypeMarker::Integer => parse_element_int(i),
-            TypeMarker::Number => parse_element_number(i),
-            TypeMarker::String => self.parse_element_string(i),
-            TypeMarker::XML => self.parse_element_xml(i, false),
-            TypeMarker::Date => self.parse_element_date(i),
-            TypeMarker::Array => self.parse_element_array(i),
-            TypeMarker::Object => self.parse_element_object(i),
-            TypeMarker::XmlString => self.parse_element_xml(i, true),
-            TypeMarker::ByteArray => self.parse_element_byte_array(i),
-            TypeMarker::VectorObject => self.parse_element_object_vector(i),
-            TypeMarker::VectorInt => self.parse_element_vector_int(i),
-            TypeMarker::VectorUInt => self.parse_element_vector_uint(i),
-            TypeMarker::VectorDouble => self.parse_element_vector_double(i),
-            TypeMarker::Dictionary => self.parse_element_dict(i),
+        // A value read on its own rather than as part of a body, offsets are relative to its start
+        let standalone = !self.reading_body;
+        if standalone {
+            self.body_start = i.as_ptr() as usize;
+            self.reading_body = true;
+        }
+
+        let result = self.parse_nested(i, &mut ());
+        if standalone {
+            self.reading_body = false;
+        }
+        result
+    }
+
+    /// Check if the paths of the values being read are needed, while recording spans or invalid strings
+    fn tracking_paths(&self) -> bool {
+        self.record_spans || self.record_invalid_strings
+    }
+
+    /// Name the next value read in its span, only used while tracking paths
+    fn name_next_span(&mut self, name: impl FnOnce() -> String) {
+        if self.tracking_paths() {
+            self.span_name = Some(name());
+        }
+    }
+
+    /// Start the span of the value at `i`, only used while tracking paths
+    fn start_span(&mut self, i: &[u8]) {
+        if !self.tracking_paths() {
+            return;
+        }
+
+        let name = self.span_name.take();
+        let path = match self.span_stack.last_mut() {
+            Some((parent, unnamed)) => {
+                let name = name.unwrap_or_else(|| {
+                    *unnamed += 1;
+                    (*unnamed - 1).to_string()
+                });
+                format!("{}.{}", self.spans[*parent].path, name)
+            }
+            None => name.unwrap_or_default(),
+        };
+
+        let start = (i.as_ptr() as usize).saturating_sub(self.body_start);
+        let index = self.spans.len();
+        self.spans.push(Span {
+            path,
+            start,
+            end: start,
+        });
+        self.span_stack.push((index, 0));
+    }
+
+    /// End the span of the value that was most recently started, `i` being the input following it
+    fn end_span(&mut self, i: &[u8]) {
+        if !self.tracking_paths() {
+            return;
+        }
+
+        if let Some((index, _)) = self.span_stack.pop() {
+            if self.record_spans {
+                self.spans[index].end = (i.as_ptr() as usize).saturating_sub(self.body_start);
+            } else {
+                // Only the paths of the values still being read are needed, and this is always the last of those
+                self.spans.truncate(index);
+            }
+        }
+    }
+
+    /// Decode exactly one AMF3 value from the start of `i`, giving the value and the bytes that follow it
+    /// Unlike `parse_body` no element name or padding is expected, so callers can frame values themselves
+    /// Reference tables are kept between calls, use `reset` before decoding unrelated data
+    pub fn decode_one<'a>(&mut self, i: &'a [u8]) -> Result<(Rc<Value>, &'a [u8]), Error<'a>> {
+        match self.parse_single_element(i) {
+            Ok((rest, value)) => Ok((value, rest)),
+            Err(Err::Error(e)) | Err(Err::Failure(e)) => Err(e),
+            Err(Err::Incomplete(_)) => Err(Error::OutOfBounds),
+        }
+    }
+
+    /// Read a value, along with the values nested in it, reporting each part of it to `events`
+    /// Rather than reading nested values recursively, the containers being read are kept on a stack on the heap, so data
+    /// that is nested very deeply can't overflow the native stack. External objects are the exception, as their
+    /// external decoder reads the values inside them with `parse_single_element`
+    pub(crate) fn parse_nested<'a, E: ReadEvents>(
+        &mut self,
+        i: &'a [u8],
+        events: &mut E,
+    ) -> AMFResult<'a, Rc<Value>> {
+        let span_depth = self.span_stack.len();
+        // Values read by an external decoder while this is reading find the stack empty, and allocate their own
+        let mut containers = core::mem::take(&mut self.containers);
+        let visiting = core::mem::replace(&mut self.visiting, !E::BUILD);
+        let depth = self.depth;
+        let result = self.parse_containers(i, &mut containers, events);
+        self.depth = depth;
+        self.visiting = visiting;
+        // After a failure the containers that were being read are dropped, and their spans are left unfinished
+        containers.clear();
+        self.containers = containers;
+        if !self.record_spans {
+            if let Some((index, _)) = self.span_stack.get(span_depth) {
+                self.spans.truncate(*index);
+            }
+        }
+        self.span_stack.truncate(span_depth);
+        result
+    }
+
+    fn parse_containers<'a, E: ReadEvents>(
+        &mut self,
+        input: &'a [u8],
+        containers: &mut Vec<Container>,
+        events: &mut E,
+    ) -> AMFResult<'a, Rc<Value>> {
+        let build = !self.visiting;
+        let mut i = input;
+        loop {
+            self.start_span(i);
+            let start = i;
+            let (j, opened) = self.open_value(i)?;
+            i = j;
+            let value = match opened {
+                Opened::Value(value) => {
+                    events.on_value(start, &value);
+                    Some(value)
+                }
+                Opened::Reference(index, value) => {
+                    events.on_reference(index);
+                    Some(value)
+                }
+                Opened::Container(container) => {
+                    // Counted across external decoders, which read the values inside them with their own stack
+                    if matches!(self.max_depth, Some(max) if self.depth >= max) {
+                        return Err(Err::Error(make_error(start, ErrorKind::TooLarge)));
+                    }
+                    self.depth += 1;
+                    events.on_open(&container.kind);
+                    containers.push(container);
+                    None
+                }
+            };
+            if let Some(value) = value {
+                self.end_span(i);
+                match containers.last_mut() {
+                    Some(parent) => parent.push(value, input, build)?,
+                    None => return Ok((i, value)),
+                }
+            }
+
+            // Close the containers that have no values left, giving each to the container it was read in,
+            // until a container needs another value or the outermost value is finished
+            while let Some(container) = containers.last_mut() {
+                let (j, more) = self.next_value(container, i, events)?;
+                i = j;
+                if more {
+                    break;
+                }
+
+                if let Some(container) = containers.pop() {
+                    self.depth -= 1;
+                    events.on_close(&container.kind);
+                    let value = self.close(container);
+                    self.end_span(i);
+                    match containers.last_mut() {
+                        Some(parent) => parent.push(value, input, build)?,
+                        None => return Ok((i, value)),
+                    }
+                }
+            }
         }
     }
 
+    /// Start reading a value, giving the whole value unless it is a container that has values to be read
+    fn open_value<'a>(&mut self, i: &'a [u8]) -> AMFResult<'a, Opened> {
+        let (i, type_) = match self.read_type_marker(i)? {
+            (i, Some(type_)) => (i, type_),
+            (i, None) => return Ok((i, Opened::Value(Rc::new(Value::Unsupported)))),
+        };
+
+        let (i, value) = match type_ {
+            TypeMarker::Undefined => (i, Rc::new(Value::Undefined)),
+            TypeMarker::Null => (i, Rc::new(Value::Null)),
+            TypeMarker::False => (i, Rc::new(Value::Bool(false))),
+            TypeMarker::True => (i, Rc::new(Value::Bool(true))),
+            TypeMarker::Integer => parse_element_int(i)?,
+            TypeMarker::Number => parse_element_number(i)?,
+            TypeMarker::String => self.parse_element_string(i)?,
+            TypeMarker::XML => return self.parse_element_xml(i, false),
+            TypeMarker::Date => return self.parse_element_date(i),
+            TypeMarker::Array => return self.open_array(i),
+            TypeMarker::Object => return self.open_object(i),
+            TypeMarker::XmlString => return self.parse_element_xml(i, true),
+            TypeMarker::ByteArray => return self.parse_element_byte_array(i),
+            TypeMarker::VectorObject => return self.open_object_vector(i),
+            TypeMarker::VectorInt => return self.parse_element_vector_int(i),
+            TypeMarker::VectorUInt => return self.parse_element_vector_uint(i),
+            TypeMarker::VectorDouble => return self.parse_element_vector_double(i),
+            TypeMarker::Dictionary => return self.open_dict(i),
+        };
+        Ok((i, Opened::Value(value)))
+    }
+
+    /// Read up to the next value of a container, giving true if there is another value at the returned input,
+    /// or false once every value has been read
+    fn next_value<'a, E: ReadEvents>(
+        &mut self,
+        container: &mut Container,
+        i: &'a [u8],
+        events: &mut E,
+    ) -> AMFResult<'a, bool> {
+        let count = container.count;
+        match &mut container.kind {
+            ContainerKind::Object {
+                class_def, name, ..
+            } => {
+                // Static properties are always present (though there may be none), dynamic members follow only if the trait is dynamic
+                if let Some(static_name) = class_def.static_properties.get(count) {
+                    let static_name = self.element_name(static_name);
+                    self.name_next_span(|| static_name.to_string());
+                    events.on_name(&static_name);
+                    *name = Some(static_name);
+                    return Ok((i, true));
+                }
+                if !class_def.attributes.contains(Attribute::Dynamic) {
+                    return Ok((i, false));
+                }
+
+                // Dynamic members are name / value pairs terminated by an empty name
+                let (i, member) = self.parse_name(i)?;
+                match member {
+                    Some(member) => {
+                        self.name_next_span(|| member.to_string());
+                        events.on_name(&member);
+                        *name = Some(member);
+                        Ok((i, true))
+                    }
+                    None => Ok((i, false)),
+                }
+            }
+            ContainerKind::Array {
+                dense,
+                len,
+                name,
+                dense_start,
+                ..
+            } => {
+                let mut i = i;
+                if dense_start.is_none() {
+                    let (j, key) = self.parse_name(i)?;
+                    i = j;
+                    match key {
+                        Some(key) => {
+                            self.name_next_span(|| key.to_string());
+                            events.on_name(&key);
+                            *name = Some(key);
+                            return Ok((i, true));
+                        }
+                        None => {
+                            // Each dense value is at least one byte, this prevents OOM errors with v.large arrays
+                            // whose associative part takes up most of the input
+                            if i.len() < *len {
+                                return Err(Err::Error(make_error(i, ErrorKind::TooLarge)));
+                            }
+                            if !self.visiting {
+                                dense.reserve_exact(*len);
+                            }
+                            *dense_start = Some(count);
+                        }
+                    }
+                }
+
+                // Must read `len` dense values
+                Ok((i, count - dense_start.unwrap_or(count) < *len))
+            }
+            ContainerKind::ObjectVector {
+                len, item_start, ..
+            } => {
+                *item_start = i.len();
+                Ok((i, count < *len))
+            }
+            ContainerKind::Dictionary { len, .. } => {
+                // Each pair is read as its key and then its value
+                let index = count / 2;
+                if index == *len {
+                    return Ok((i, false));
+                }
+                if count == index * 2 {
+                    self.name_next_span(|| format!("{}.key", index));
+                } else {
+                    self.name_next_span(|| format!("{}.value", index));
+                }
+                Ok((i, true))
+            }
+        }
+    }
+
+    /// Finish a container whose values have all been read, replacing its placeholder in the object reference table
+    /// While visiting the container isn't built, and its placeholder is given instead
+    fn close(&mut self, container: Container) -> Rc<Value> {
+        let index = container.index;
+        if self.visiting {
+            return Rc::clone(&self.object_reference_table[index]);
+        }
+        let value = Rc::new(container.into_value());
+        self.object_reference_table[index] = Rc::clone(&value);
+        value
+    }
+
     fn parse_element<'a>(&mut self, i: &'a [u8]) -> AMFResult<'a, Element> {
-        let (i, name) = self.parse_string(i)?;
+        let (i, (name, value)) = self.read_element(i, &mut ())?;
+        Ok((i, Element { name, value }))
+    }
 
-        map(
-            |i| self.parse_single_element(i),
-            move |v| Element {
-                name: name.clone(),
-                value: v,
-            },
-        )(i)
+    /// Read the name and value of an element of a body, reporting them to `events`
+    pub(crate) fn read_element<'a, E: ReadEvents>(
+        &mut self,
+        i: &'a [u8],
+        events: &mut E,
+    ) -> AMFResult<'a, (Rc<str>, Rc<Value>)> {
+        let (i, name) = self.parse_name(i)?;
+        let name = match name {
+            Some(name) => name,
+            None => self.element_name(""),
+        };
+        self.name_next_span(|| name.to_string());
+        events.on_name(&name);
+
+        let (i, value) = self.parse_nested(i, events)?;
+        Ok((i, (name, value)))
     }
 
     /// Parse an AMF3 body from a slice into a list of elements
     pub fn parse_body<'a>(&mut self, i: &'a [u8]) -> AMFResult<'a, Vec<Element>> {
+        self.start_body(i);
+        self.reading_body = true;
+        let result = self.parse_elements(i);
+        self.reading_body = false;
+        result
+    }
+
+    /// Parse an AMF3 body from a slice, also giving the number of bytes left after it
+    /// Unlike `parse_with_offsets` bytes after the body are not an error, so they can be reported or read separately
+    pub fn parse_body_checked<'a>(&mut self, i: &'a [u8]) -> Result<ParsedBody, Error<'a>> {
+        match self.parse_body(i) {
+            Ok((rest, elements)) => Ok(ParsedBody {
+                elements,
+                trailing_bytes: rest.len(),
+            }),
+            Err(Err::Error(e)) | Err(Err::Failure(e)) => Err(e),
+            Err(Err::Incomplete(_)) => Err(Error::OutOfBounds),
+        }
+    }
+
+    /// Parse an AMF3 body like `parse_body`, also giving the span of the bytes that each value was read from
+    /// Spans are given for every value including nested ones, in the order they start, with offsets relative to the start of `i`
+    /// The span of a value read by reference only covers the reference, the values inside it are not repeated
+    pub fn parse_with_spans<'a>(
+        &mut self,
+        i: &'a [u8],
+    ) -> AMFResult<'a, (Vec<Element>, Vec<Span>)> {
+        self.record_spans = true;
+        self.spans.clear();
+        self.span_stack.clear();
+        self.span_name = None;
+        let result = self.parse_body(i);
+        self.record_spans = false;
+
+        let spans = core::mem::take(&mut self.spans);
+        result.map(|(i, elements)| (i, (elements, spans)))
+    }
+
+    fn parse_elements<'a>(&mut self, i: &'a [u8]) -> AMFResult<'a, Vec<Element>> {
+        // An empty body is written without the padding that follows each element
+        if i.is_empty() {
+            return Ok((i, Vec::new()));
+        }
         let (i, elements) = separated_list0(tag(PADDING), |i| self.parse_element(i))(i)?;
         let (i, _) = tag(PADDING)(i)?;
         Ok((i, elements))
     }
+
+    /// Parse an AMF3 body from a slice, which must be consumed entirely
+    /// On failure the offset in the slice where parsing failed is also given
+    pub fn parse_with_offsets<'a>(
+        &mut self,
+        i: &'a [u8],
+    ) -> Result<Vec<Element>, (Error<'a>, usize)> {
+        nom_utils::parse_with_offsets(i, |i| self.parse_body(i))
+    }
+}
+
+#[cfg(test)]
+mod vector_len_tests {
+    use crate::amf3::read::check_vector_len;
+    use crate::errors::Error;
+    use nom::error::ErrorKind;
+    use nom::Err;
+
+    #[test]
+    fn huge_lengths_are_too_large() {
+        let i = [0u8; 16];
+        // These would wrap to a small number of bytes if not checked
+        for (len, item_size) in [
+            (usize::MAX / 4 + 1, 4),
+            (usize::MAX / 8 + 1, 8),
+            (usize::MAX, 8),
+        ] {
+            assert_eq!(
+                check_vector_len(&i, len, item_size),
+                Err(Err::Error(Error::Nom(&i, ErrorKind::TooLarge)))
+            );
+        }
+        assert!(check_vector_len(&i, usize::MAX / 4, 4).is_err());
+
+        assert_eq!(check_vector_len(&i, 4, 4), Ok(()));
+        assert_eq!(check_vector_len(&i, 2, 8), Ok(()));
+        assert!(check_vector_len(&i, 3, 8).is_err());
+    }
+}
+
+#[cfg(test)]
+mod read_number_tests {
+    use crate::amf3::length::Length;
+    use crate::amf3::read::{read_i29, read_length, read_u29};
+    use crate::amf3::write::write_u29;
+    use cookie_factory::gen;
+
+    #[test]
+    fn test_read_1byte_number() {
+        assert_eq!(0b00101011, read_i29(&[0b00101011]).unwrap().1)
+    }
+
+    #[test]
+    fn test_read_4byte_number() {
+        let i = &[0b10000000, 0b11000000, 0b10000000, 0b10000000];
+        assert_eq!(2097280, read_i29(i).unwrap().1);
+    }
+
+    #[test]
+    fn read_neg_number() {
+        assert_eq!(-268435455, read_i29(&[192, 128, 128, 1]).unwrap().1);
+    }
+
+    #[test]
+    fn test_read_1byte_number_unsigned() {
+        assert_eq!(0b00101011, read_u29(&[0b00101011]).unwrap().1)
+    }
+
+    #[test]
+    fn test_read_4byte_number_unsigned() {
+        let i = &[0b10000000, 0b11000000, 0b10000000, 0b10000000];
+        assert_eq!(2097280, read_u29(i).unwrap().1);
+    }
+
+    #[test]
+    fn read_large_number_unsigned() {
+        // The 4 byte form gives 3 * 7 bits followed by a full 8 bits, values with the top bit set are not changed
+        assert_eq!(0x1000_0001, read_u29(&[192, 128, 128, 1]).unwrap().1);
+        assert_eq!(0x1000_0000, read_u29(&[0xc0, 0x80, 0x80, 0x00]).unwrap().1);
+        assert_eq!(0x0fff_ffff, read_u29(&[0xbf, 0xff, 0xff, 0xff]).unwrap().1);
+        assert_eq!(0x1fff_ffff, read_u29(&[0xff, 0xff, 0xff, 0xff]).unwrap().1);
+        // The largest length or reference
+        assert_eq!(
+            Length::Size(0x0fff_ffff),
+            read_length(&[0xff, 0xff, 0xff, 0xff]).unwrap().1
+        );
+        assert_eq!(
+            Length::Reference(0x0fff_ffff),
+            read_length(&[0xff, 0xff, 0xff, 0xfe]).unwrap().1
+        );
+    }
+
+    #[test]
+    fn u29_round_trip_at_boundaries() {
+        for n in [
+            0,
+            0x7f,
+            0x80,
+            0x3fff,
+            0x4000,
+            0x1f_ffff,
+            0x20_0000,
+            0x0fff_ffff,
+            0x1000_0000,
+            0x1fff_ffff,
+        ] {
+            let (bytes, _) = gen(write_u29(n), Vec::new()).unwrap();
+            let (rest, value) = read_u29(&bytes).unwrap();
+            assert!(rest.is_empty());
+            assert_eq!(value, n, "{:#x} was read as {:#x}", n, value);
+        }
+    }
 }
@@ -2,9 +2,10 @@ use derive_try_from_primitive::TryFromPrimitive;
 
 /// Type markers used in AMF3
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(TryFromPrimitive, Eq, PartialEq, Debug, Copy, Clone)]
+#[derive(TryFromPrimitive, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Copy, Clone)]
 #[repr(u8)]
-pub(crate) enum TypeMarker {
+#[allow(clippy::upper_case_acronyms)]
+pub enum TypeMarker {
     /// Undefined
     Undefined = 0x00,
     /// Null
@@ -0,0 +1,181 @@
+//! Streaming decoding of AMF3 data
+//!
+//! Rather than building a tree of `Value`s, `AMF3Decoder::parse_with_visitor` reports each value to an
+//! `Amf3Visitor` as it is read. This avoids keeping the whole file in memory when only part of it is needed.
+//!
+//! Values that are sent by reference are reported with `Amf3Visitor::on_reference`, the index counts
+//! every call to `on_object_start`, `on_array_start`, `on_dictionary_start`, `on_vector_object_start`,
+//! `on_external`, `on_xml`, `on_date`, `on_byte_array` and `on_vector_*` in the order they were made.
+use crate::amf3::read::{AMF3Decoder, ContainerKind, ReadEvents};
+use crate::nom_utils::AMFResult;
+use crate::types::{ClassDefinition, Element, Value};
+use crate::PADDING;
+use nom::bytes::complete::tag;
+
+/// Receives the values read by `AMF3Decoder::parse_with_visitor`
+/// Every method does nothing by default, so only the ones of interest need to be implemented
+#[allow(unused_variables)]
+pub trait Amf3Visitor {
+    /// Called with the name of the next value, for top level elements, object properties and the associative part of arrays
+    fn on_property(&mut self, name: &str) {}
+
+    /// Called before the properties of an object
+    fn on_object_start(&mut self, class_def: &ClassDefinition) {}
+    /// Called after the properties of an object
+    fn on_object_end(&mut self) {}
+    /// Called for an externalized object, with the elements given by the registered external decoder
+    fn on_external(&mut self, class_def: &ClassDefinition, elements: &[Element]) {}
+
+    /// Called before the contents of an array, `dense_len` is the number of values that follow the associative part
+    fn on_array_start(&mut self, dense_len: usize) {}
+    /// Called after the contents of an array
+    fn on_array_end(&mut self) {}
+
+    /// Called before the contents of a dictionary, which are `len` pairs of key and value
+    fn on_dictionary_start(&mut self, len: usize, weak_keys: bool) {}
+    /// Called after the contents of a dictionary
+    fn on_dictionary_end(&mut self) {}
+
+    /// Called before the contents of an object vector
+    fn on_vector_object_start(&mut self, len: usize, type_name: &str, fixed_length: bool) {}
+    /// Called after the contents of an object vector
+    fn on_vector_object_end(&mut self) {}
+
+    /// Called for a value that refers to an earlier value, see the module docs for how these are indexed
+    fn on_reference(&mut self, index: usize) {}
+
+    /// Called for undefined
+    fn on_undefined(&mut self) {}
+    /// Called for null
+    fn on_null(&mut self) {}
+    /// Called for true or false
+    fn on_bool(&mut self, value: bool) {}
+    /// Called for an integer
+    fn on_integer(&mut self, value: i32) {}
+    /// Called for a number
+    fn on_number(&mut self, value: f64) {}
+    /// Called for a string
+    fn on_string(&mut self, value: &str) {}
+    /// Called for XML, `is_string` as in `Value::XML`
+    fn on_xml(&mut self, content: &str, is_string: bool) {}
+    /// Called for a date, in milliseconds since the epoch
+    fn on_date(&mut self, millis: f64) {}
+    /// Called for a byte array
+    fn on_byte_array(&mut self, bytes: &[u8]) {}
+    /// Called for an int vector
+    fn on_vector_int(&mut self, values: &[i32], fixed_length: bool) {}
+    /// Called for an unsigned int vector
+    fn on_vector_uint(&mut self, values: &[u32], fixed_length: bool) {}
+    /// Called for a number vector
+    fn on_vector_double(&mut self, values: &[f64], fixed_length: bool) {}
+    /// Called for an unknown type marker, only when `AMF3Decoder::lenient_markers` is set
+    fn on_unknown_marker(&mut self, marker: u8) {}
+}
+
+/// Reports the values read by `AMF3Decoder::parse_nested` to a visitor rather than building them
+struct Visit<'v, V> {
+    visitor: &'v mut V,
+}
+
+impl<V: Amf3Visitor> ReadEvents for Visit<'_, V> {
+    const BUILD: bool = false;
+
+    fn on_value(&mut self, start: &[u8], value: &Value) {
+        match value {
+            Value::Undefined => self.visitor.on_undefined(),
+            Value::Null => self.visitor.on_null(),
+            Value::Bool(value) => self.visitor.on_bool(*value),
+            Value::Integer(value) => self.visitor.on_integer(*value),
+            Value::Number(value) => self.visitor.on_number(*value),
+            Value::String(value) => self.visitor.on_string(value),
+            Value::XML(content, is_string) => self.visitor.on_xml(content, *is_string),
+            Value::Date(millis, _) => self.visitor.on_date(*millis),
+            Value::ByteArray(bytes) => self.visitor.on_byte_array(bytes),
+            Value::VectorInt(values, fixed_length) => {
+                self.visitor.on_vector_int(values, *fixed_length)
+            }
+            Value::VectorUInt(values, fixed_length) => {
+                self.visitor.on_vector_uint(values, *fixed_length)
+            }
+            Value::VectorDouble(values, fixed_length) => {
+                self.visitor.on_vector_double(values, *fixed_length)
+            }
+            Value::Custom(elements, _, Some(class_def)) => {
+                self.visitor.on_external(class_def, elements)
+            }
+            // The marker was read, so the input isn't empty
+            Value::Unsupported => self.visitor.on_unknown_marker(start[0]),
+            // Every other value has values nested in it, so is reported by `on_open` and `on_close`
+            _ => {}
+        }
+    }
+
+    fn on_reference(&mut self, index: usize) {
+        self.visitor.on_reference(index);
+    }
+
+    fn on_name(&mut self, name: &str) {
+        self.visitor.on_property(name);
+    }
+
+    fn on_open(&mut self, kind: &ContainerKind) {
+        match kind {
+            ContainerKind::Object { class_def, .. } => self.visitor.on_object_start(class_def),
+            ContainerKind::Array { len, .. } => self.visitor.on_array_start(*len),
+            ContainerKind::ObjectVector {
+                len,
+                type_name,
+                fixed_length,
+                ..
+            } => self
+                .visitor
+                .on_vector_object_start(*len, type_name, *fixed_length),
+            ContainerKind::Dictionary { len, weak_keys, .. } => {
+                self.visitor.on_dictionary_start(*len, *weak_keys)
+            }
+        }
+    }
+
+    fn on_close(&mut self, kind: &ContainerKind) {
+        match kind {
+            ContainerKind::Object { .. } => self.visitor.on_object_end(),
+            ContainerKind::Array { .. } => self.visitor.on_array_end(),
+            ContainerKind::ObjectVector { .. } => self.visitor.on_vector_object_end(),
+            ContainerKind::Dictionary { .. } => self.visitor.on_dictionary_end(),
+        }
+    }
+}
+
+impl AMF3Decoder {
+    /// Parse an AMF3 body from a slice, passing each value to `visitor` rather than building a list of elements
+    /// The reference tables are still filled in, but with placeholders in place of the values that were read,
+    /// so an external decoder that resolves a reference to an object will only see `Value::Null`
+    /// Values are read with the same explicit stack as `parse_body`, so deeply nested data can't overflow the stack
+    pub fn parse_with_visitor<'a, V: Amf3Visitor>(
+        &mut self,
+        i: &'a [u8],
+        visitor: &mut V,
+    ) -> AMFResult<'a, ()> {
+        self.start_body(i);
+        self.reading_body = true;
+        let result = self.visit_elements(i, &mut Visit { visitor });
+        self.reading_body = false;
+        result
+    }
+
+    fn visit_elements<'a, V: Amf3Visitor>(
+        &mut self,
+        i: &'a [u8],
+        visit: &mut Visit<'_, V>,
+    ) -> AMFResult<'a, ()> {
+        // Each element is followed by padding, this can't look ahead like `parse_body` as the visitor
+        // would be given the start of an element that doesn't exist
+        let mut i = i;
+        while !i.is_empty() {
+            let (j, _) = self.read_element(i, visit)?;
+            let (j, _) = tag(PADDING)(j)?;
+            i = j;
+        }
+        Ok((i, ()))
+    }
+}
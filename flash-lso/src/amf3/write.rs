@@ -1,4 +1,4 @@
-///! Handles encoding AMF3
+//! Handles encoding AMF3
 use crate::amf3::custom_encoder::CustomEncoder;
 use crate::amf3::element_cache::ElementCache;
 use crate::amf3::length::Length;
@@ -20,103 +20,153 @@ use std::rc::Rc;
 /// Handles encoding AMF3
 #[derive(Default)]
 pub struct AMF3Encoder {
-    /// The table used to cache repeated byte strings
-    string_reference_table: ElementCache<Vec<u8>>,
+    /// The table used to cache repeated byte strings, mapping each string written to its index in the table
+    /// A map keeps lookups fast for files with many distinct strings, as every string written is looked up
+    string_reference_table: RefCell<HashMap<Vec<u8>, usize>>,
     /// The table used to cache repeated trait definitions
     trait_reference_table: RefCell<Vec<ClassDefinition>>,
     /// The table used to cache repeated objects
     object_reference_table: ElementCache<Value>,
+    /// Maps shared values (by `Rc` pointer) to their index in the object reference table
+    /// Each entry keeps its value alive, so the address can't be reused by another value until the table is cleared
+    object_pointer_table: RefCell<HashMap<*const Value, (Rc<Value>, usize)>>,
     /// Encoders used for handling externalized types
     pub external_encoders: HashMap<String, Box<dyn CustomEncoder>>,
 }
 
-#[cfg(test)]
-mod write_number_tests {
-    use crate::amf3::write::AMF3Encoder;
-    use cookie_factory::gen;
-
-    #[test]
-    fn test_write_1byte_number() {
-        let e = AMF3Encoder::default();
-        let v = vec![];
-        let (b1, _) = gen(e.write_int(0b00101011), v).unwrap();
-        assert_eq!(b1, &[0b00101011]);
-    }
-
-    #[test]
-    fn test_write_4byte_number() {
-        let e = AMF3Encoder::default();
-        let v = vec![];
-        let (b1, _) = gen(e.write_int(2097280), v).unwrap();
-        assert_eq!(b1, &[0b10000000, 0b11000000, 0b10000000, 0b10000000]);
-    }
-
-    #[test]
-    fn write_neg_number() {
-        let e = AMF3Encoder::default();
-        let v = vec![];
-        let (b1, _) = gen(e.write_int(-268435455), v).unwrap();
-        assert_eq!(b1, &[192, 128, 128, 1]);
-    }
+/// Check if a value is stored in the object reference table when written
+fn is_referenceable(v: &Value) -> bool {
+    matches!(
+        v,
+        Value::Object(_, _)
+            | Value::Custom(_, _, _)
+            | Value::StrictArray(_)
+            | Value::ECMAArray(_, _, _)
+            | Value::Date(_, _)
+            | Value::XML(_, _)
+            | Value::ByteArray(_)
+            | Value::VectorInt(_, _)
+            | Value::VectorUInt(_, _)
+            | Value::VectorDouble(_, _)
+            | Value::VectorObject(_, _, _)
+            | Value::Dictionary(_, _)
+    )
 }
 
-impl AMF3Encoder {
-    #[allow(clippy::unusual_byte_groupings)]
-    pub(crate) fn write_int<'a, 'b: 'a, W: Write + 'a>(&self, i: i32) -> impl SerializeFn<W> + 'a {
-        let n = if i < 0 {
-            i + 0b001_0000000_0000000_0000000_00000000
-        } else {
-            i
-        };
-
+/// The smallest integer that can be written as an I29
+const I29_MIN: i32 = -(1 << 28);
+/// The largest integer that can be written as an I29
+const I29_MAX: i32 = (1 << 28) - 1;
+
+/// Write an AMF3 variable length signed integer (I29), see `read_i29`
+/// Values outside of the 29 bit range (-2^28 to 2^28 - 1) can't be represented and will not be read back unchanged
+#[allow(clippy::unusual_byte_groupings)]
+pub fn write_i29<'a, W: Write + 'a>(i: i32) -> impl SerializeFn<W> + 'a {
+    let n = if i < 0 {
+        i + 0b001_0000000_0000000_0000000_00000000
+    } else {
+        i
+    };
+
+    either(
+        n > 0x1fffff,
+        tuple((
+            be_u8(((n >> (7 * 3 + 1)) | 0b10000000) as u8),
+            be_u8(((n >> (7 * 2 + 1)) | 0b10000000) as u8),
+            be_u8(((n >> (7 + 1)) | 0b10000000) as u8),
+            be_u8((n & 0b11111111) as u8),
+        )),
         either(
-            n > 0x1fffff,
+            n > 0x3fff,
             tuple((
-                be_u8(((n >> (7 * 3 + 1)) | 0b10000000) as u8),
-                be_u8(((n >> (7 * 2 + 1)) | 0b10000000) as u8),
-                be_u8(((n >> (7 + 1)) | 0b10000000) as u8),
-                be_u8((n & 0b11111111) as u8),
+                be_u8(((n >> (7 * 2)) | 0b10000000) as u8),
+                be_u8(((n >> 7) | 0b10000000) as u8),
+                be_u8((n & 0b01111111) as u8),
             )),
             either(
-                n > 0x3fff,
+                n > 0x7f,
                 tuple((
-                    be_u8(((n >> (7 * 2)) | 0b10000000) as u8),
                     be_u8(((n >> 7) | 0b10000000) as u8),
                     be_u8((n & 0b01111111) as u8),
                 )),
-                either(
-                    n > 0x7f,
-                    tuple((
-                        be_u8(((n >> 7) | 0b10000000) as u8),
-                        be_u8((n & 0b01111111) as u8),
-                    )),
-                    be_u8((n & 0b01111111) as u8),
-                ),
+                be_u8((n & 0b01111111) as u8),
             ),
-        )
+        ),
+    )
+}
+
+/// Write an AMF3 variable length unsigned integer (U29), see `read_u29`
+/// Only the low 29 bits of `n` are written
+pub fn write_u29<'a, W: Write + 'a>(n: u32) -> impl SerializeFn<W> + 'a {
+    write_i29((n & 0x1fff_ffff) as i32)
+}
+
+/// Encode one AMF3 value with `encoder`, see `AMF3Encoder::encode_one`
+pub fn write_element(value: &Value, encoder: &mut AMF3Encoder) -> Result<Vec<u8>, GenError> {
+    encoder.encode_one(value)
+}
+
+impl AMF3Encoder {
+    /// Encode exactly one AMF3 value, with no element name or padding, so callers can frame values themselves
+    /// Reference tables are kept between calls, so a value shared (by `Rc`) with one written earlier, or a repeated
+    /// string or class, is written as a reference, the values must then be read in the same order by one
+    /// `AMF3Decoder`, such as with `AMF3Decoder::decode_one`
+    /// Use `reset` before encoding unrelated data
+    pub fn encode_one(&mut self, value: &Value) -> Result<Vec<u8>, GenError> {
+        let (buffer, _size) = cookie_factory::gen(self.write_value(value), Vec::new())?;
+        Ok(buffer)
+    }
+
+    /// Clear the string, trait and object reference tables so the encoder can be reused for unrelated data
+    /// Registered `external_encoders` are kept
+    pub fn reset(&mut self) {
+        self.clear_tables();
+    }
+
+    /// Clear the reference tables through a shared reference, see `reset`
+    pub(crate) fn clear_tables(&self) {
+        self.string_reference_table.borrow_mut().clear();
+        self.trait_reference_table.borrow_mut().clear();
+        self.object_reference_table.clear();
+        self.object_pointer_table.borrow_mut().clear();
+    }
+
+    /// Create an encoder that writes the flex types read by `AMF3Decoder::with_flex_types`, wrapping their value again
+    #[cfg(feature = "flex")]
+    pub fn with_flex_types() -> Self {
+        let mut encoder = Self::default();
+        crate::extra::flex::write::register_collection_encoders(&mut encoder);
+        encoder
+    }
+
+    pub(crate) fn write_int<'a, 'b: 'a, W: Write + 'a>(&self, i: i32) -> impl SerializeFn<W> + 'a {
+        write_i29(i)
     }
 
     fn write_byte_string<'a, 'b: 'a, W: Write + 'a>(
         &self,
         s: &'b [u8],
     ) -> impl SerializeFn<W> + 'a {
-        let len = if !s.is_empty() {
-            self.string_reference_table
-                .to_length(s.to_vec(), s.len() as u32)
-        } else {
+        // The empty string is never sent by reference
+        let len = if s.is_empty() {
             Length::Size(0)
+        } else {
+            let mut table = self.string_reference_table.borrow_mut();
+            if let Some(index) = table.get(s) {
+                Length::Reference(*index)
+            } else {
+                let index = table.len();
+                table.insert(s.to_vec(), index);
+                Length::Size(s.len() as u32)
+            }
         };
 
-        let only_length = len.is_reference() && !s.is_empty();
-
-        if !s.is_empty() {
-            self.string_reference_table.store_slice(s);
-        }
+        let only_length = len.is_reference();
 
         either(
             only_length,
-            len.write(&self),
-            tuple((len.write(&self), slice(s))),
+            len.write(self),
+            tuple((len.write(self), slice(s))),
         )
     }
 
@@ -178,9 +228,9 @@ impl AMF3Encoder {
             self.write_type_marker(TypeMarker::VectorInt),
             either(
                 len.is_reference(),
-                len.write(&self),
+                len.write(self),
                 tuple((
-                    Length::Size(items.len() as u32).write(&self),
+                    Length::Size(items.len() as u32).write(self),
                     be_u8(fixed_length as u8),
                     all(items.iter().copied().map(be_i32)),
                 )),
@@ -202,9 +252,9 @@ impl AMF3Encoder {
             self.write_type_marker(TypeMarker::VectorUInt),
             either(
                 len.is_reference(),
-                len.write(&self),
+                len.write(self),
                 tuple((
-                    Length::Size(items.len() as u32).write(&self),
+                    Length::Size(items.len() as u32).write(self),
                     be_u8(fixed_length as u8),
                     all(items.iter().copied().map(be_u32)),
                 )),
@@ -226,9 +276,9 @@ impl AMF3Encoder {
             self.write_type_marker(TypeMarker::VectorDouble),
             either(
                 len.is_reference(),
-                len.write(&self),
+                len.write(self),
                 tuple((
-                    Length::Size(items.len() as u32).write(&self),
+                    Length::Size(items.len() as u32).write(self),
                     be_u8(fixed_length as u8),
                     all(items.iter().copied().map(be_f64)),
                 )),
@@ -243,16 +293,21 @@ impl AMF3Encoder {
 
         tuple((
             self.write_type_marker(TypeMarker::Date),
-            len.write(&self),
+            len.write(self),
             cond(len.is_size(), be_f64(time)),
         ))
     }
 
     fn write_integer_element<'a, 'b: 'a, W: Write + 'a>(&self, i: i32) -> impl SerializeFn<W> + 'a {
-        tuple((
-            self.write_type_marker(TypeMarker::Integer),
-            self.write_int(i),
-        ))
+        // Integers outside of the 29 bit range are written as numbers, as Flash does
+        either(
+            (I29_MIN..=I29_MAX).contains(&i),
+            tuple((
+                self.write_type_marker(TypeMarker::Integer),
+                self.write_int(i),
+            )),
+            self.write_number_element(f64::from(i)),
+        )
     }
 
     fn write_byte_array_element<'a, 'b: 'a, W: Write + 'a>(
@@ -265,7 +320,7 @@ impl AMF3Encoder {
 
         tuple((
             self.write_type_marker(TypeMarker::ByteArray),
-            len.write(&self),
+            len.write(self),
             cond(len.is_size(), slice(bytes)),
         ))
     }
@@ -276,6 +331,8 @@ impl AMF3Encoder {
         string: bool,
     ) -> impl SerializeFn<W> + 'a {
         let len = Length::Size(bytes.len() as u32);
+        self.object_reference_table
+            .push(Value::XML(bytes.to_string(), string));
 
         tuple((
             either(
@@ -283,7 +340,7 @@ impl AMF3Encoder {
                 self.write_type_marker(TypeMarker::XmlString),
                 self.write_type_marker(TypeMarker::XML),
             ),
-            len.write(&self),
+            len.write(self),
             cond(len.is_size(), slice(bytes.as_bytes())),
         ))
     }
@@ -301,6 +358,22 @@ impl AMF3Encoder {
         ))
     }
 
+    /// Write the value of each static property of `def` in the order of the trait, the reader expects exactly one value
+    /// for each, so a property missing from `children` (such as in an object that was still being read when it was
+    /// referenced) is written as undefined
+    fn write_static_properties<'a, 'b: 'a, W: Write + 'a>(
+        &'a self,
+        children: &'b [Element],
+        def: &'b ClassDefinition,
+    ) -> impl SerializeFn<W> + 'a {
+        all(def.static_properties.iter().map(move |name| {
+            move |out| match children.iter().find(|c| *c.name == **name) {
+                Some(child) => self.write_value_element(&child.value)(out),
+                None => self.write_undefined_element()(out),
+            }
+        }))
+    }
+
     //TODO: conds should be common somehwere
     fn write_trait_reference<'a, 'b: 'a, W: Write + 'a>(
         &'a self,
@@ -326,23 +399,15 @@ impl AMF3Encoder {
                 tuple((
                     cond(
                         def.attributes.is_empty(),
-                        all(children
-                            .iter()
-                            .filter(move |c| def.static_properties.contains(&c.name))
-                            .map(move |e| &e.value)
-                            .map(move |e| self.write_value_element(e))),
+                        self.write_static_properties(children, def),
                     ),
                     cond(
                         def.attributes.contains(Attribute::Dynamic),
                         tuple((
+                            self.write_static_properties(children, def),
                             all(children
                                 .iter()
-                                .filter(move |c| def.static_properties.contains(&c.name))
-                                .map(move |e| &e.value)
-                                .map(move |e| self.write_value_element(e))),
-                            all(children
-                                .iter()
-                                .filter(move |c| !def.static_properties.contains(&c.name))
+                                .filter(move |c| !def.has_static_property(&c.name))
                                 // .map(move |e| &e.value)
                                 .map(move |e| {
                                     tuple((
@@ -358,15 +423,6 @@ impl AMF3Encoder {
         ))
     }
 
-    fn write_object_reference<'a, 'b: 'a, W: Write + 'a>(
-        &'a self,
-        index: u32,
-    ) -> impl SerializeFn<W> + 'a {
-        #[allow(clippy::identity_op)]
-        let size = (index << 1) | 0u32;
-        tuple((self.write_int(size as i32),))
-    }
-
     fn write_object_full<'a, 'b: 'a, W: Write + 'a>(
         &'a self,
         custom_props: Option<&'b [Element]>,
@@ -375,23 +431,12 @@ impl AMF3Encoder {
     ) -> impl SerializeFn<W> + 'a {
         self.trait_reference_table.borrow_mut().push(def.clone());
 
-        let is_external = def.attributes.contains(Attribute::External);
-        let is_dynamic = def.attributes.contains(Attribute::Dynamic);
-
-        let mut encoding = 0b00;
-        if is_external {
-            encoding |= 0b01;
-        }
-        if is_dynamic {
-            encoding |= 0b10;
-        }
+        let encoding = def.encoding();
 
         // Format attribute_count[:4] | encoding[4:2] | class_def_ref flag (1 bit) | class_ref flag (1 bit)
-        let size = ((((((def.static_properties.len() as u32) << 2) | (encoding & 0xff) as u32)
-            << 1)
-            | 1u32)
-            << 1)
-            | 1u32;
+        let size =
+            ((((((def.static_properties.len() as u32) << 2) | encoding as u32) << 1) | 1u32) << 1)
+                | 1u32;
 
         tuple((
             self.write_int(size as i32),
@@ -408,23 +453,15 @@ impl AMF3Encoder {
                 tuple((
                     cond(
                         def.attributes.is_empty(),
-                        all(children
-                            .iter()
-                            .filter(move |c| def.static_properties.contains(&c.name))
-                            .map(move |e| &e.value)
-                            .map(move |e| self.write_value_element(e))),
+                        self.write_static_properties(children, def),
                     ),
                     cond(
                         def.attributes.contains(Attribute::Dynamic),
                         tuple((
+                            self.write_static_properties(children, def),
                             all(children
                                 .iter()
-                                .filter(move |c| def.static_properties.contains(&c.name))
-                                .map(move |e| &e.value)
-                                .map(move |e| self.write_value_element(e))),
-                            all(children
-                                .iter()
-                                .filter(move |c| !def.static_properties.contains(&c.name))
+                                .filter(move |c| !def.has_static_property(&c.name))
                                 // .map(move |e| &e.value)
                                 .map(move |e| {
                                     tuple((
@@ -446,13 +483,15 @@ impl AMF3Encoder {
         custom_props: Option<&'b [Element]>,
         class_def: &'b Option<ClassDefinition>,
     ) -> impl SerializeFn<W> + 'a {
-        let had_object = Length::Size(0);
-
         self.object_reference_table
-            .store(Value::Object(children.to_vec(), class_def.clone()));
+            .push(Value::Object(children.to_vec(), class_def.clone()));
 
         move |out| {
-            let def = class_def.clone().unwrap_or_default();
+            // Objects without a class definition are anonymous and dynamic, as with `Value::to_amf3`
+            let def = class_def.clone().unwrap_or_else(|| ClassDefinition {
+                attributes: Attribute::Dynamic.into(),
+                ..ClassDefinition::default_with_name(String::new())
+            });
             let def2 = def.clone();
 
             let has_trait = self
@@ -463,26 +502,18 @@ impl AMF3Encoder {
 
             let x = tuple((
                 self.write_type_marker(TypeMarker::Object),
-                cond(had_object.is_reference(), move |out| {
-                    self.write_object_reference(had_object.to_position().unwrap() as u32)(out)
+                cond(has_trait.is_some(), move |out| {
+                    self.write_trait_reference(
+                        has_trait.unwrap() as u32,
+                        children,
+                        custom_props,
+                        &def2,
+                    )(out)
+                }),
+                // Built only when needed, as building it adds the trait to the reference table
+                cond(has_trait.is_none(), move |out| {
+                    self.write_object_full(custom_props, children, &def)(out)
                 }),
-                cond(
-                    !had_object.is_reference(),
-                    tuple((
-                        cond(has_trait.is_some(), move |out| {
-                            self.write_trait_reference(
-                                has_trait.unwrap() as u32,
-                                children,
-                                custom_props,
-                                &def2,
-                            )(out)
-                        }),
-                        cond(
-                            has_trait.is_none(),
-                            self.write_object_full(custom_props, children, &def),
-                        ),
-                    )),
-                ),
             ))(out);
 
             x
@@ -493,20 +524,20 @@ impl AMF3Encoder {
         &'a self,
         children: &'b [Rc<Value>],
     ) -> impl SerializeFn<W> + 'a {
-        //TODO: why is this not a reference
         let len = Length::Size(children.len() as u32);
+        self.object_reference_table
+            .push(Value::StrictArray(children.to_vec()));
 
-        //TODO: why does this not offset the cache if StrictArray([]) is saved but always written as Size(0) instead of Ref(n)
         either(
             children.is_empty(),
             tuple((
                 self.write_type_marker(TypeMarker::Array),
-                Length::Size(0).write(&self),
+                Length::Size(0).write(self),
                 self.write_byte_string(&[]), // Empty key
             )),
             tuple((
                 self.write_type_marker(TypeMarker::Array),
-                len.write(&self),
+                len.write(self),
                 cond(
                     len.is_size(),
                     tuple((
@@ -524,11 +555,16 @@ impl AMF3Encoder {
         assoc: &'b [Element],
     ) -> impl SerializeFn<W> + 'a {
         let len = Length::Size(dense.len() as u32);
+        self.object_reference_table.push(Value::ECMAArray(
+            dense.to_vec(),
+            assoc.to_vec(),
+            assoc.len() as u32,
+        ));
 
         //TODO: would this also work for strict arrays if they have [] for assoc part?
         tuple((
             self.write_type_marker(TypeMarker::Array),
-            len.write(&self),
+            len.write(self),
             cond(
                 len.is_size(),
                 tuple((
@@ -553,7 +589,7 @@ impl AMF3Encoder {
 
         tuple((
             self.write_type_marker(TypeMarker::VectorObject),
-            len.write(&self),
+            len.write(self),
             cond(
                 len.is_size(),
                 tuple((
@@ -579,7 +615,7 @@ impl AMF3Encoder {
 
         tuple((
             self.write_type_marker(TypeMarker::Dictionary),
-            len.write(&self),
+            len.write(self),
             cond(
                 len.is_size(),
                 tuple((
@@ -595,11 +631,59 @@ impl AMF3Encoder {
         ))
     }
 
+    /// Write a reference to the value at the given index in the object reference table
+    fn write_reference_element<'a, 'b: 'a, W: Write + 'a>(
+        &'b self,
+        s: &'b Value,
+        index: usize,
+    ) -> impl SerializeFn<W> + 'a {
+        let marker = match s {
+            Value::Object(_, _) | Value::Custom(_, _, _) => TypeMarker::Object,
+            Value::StrictArray(_) | Value::ECMAArray(_, _, _) => TypeMarker::Array,
+            Value::Date(_, _) => TypeMarker::Date,
+            Value::XML(_, true) => TypeMarker::XmlString,
+            Value::XML(_, false) => TypeMarker::XML,
+            Value::ByteArray(_) => TypeMarker::ByteArray,
+            Value::VectorInt(_, _) => TypeMarker::VectorInt,
+            Value::VectorUInt(_, _) => TypeMarker::VectorUInt,
+            Value::VectorDouble(_, _) => TypeMarker::VectorDouble,
+            Value::VectorObject(_, _, _) => TypeMarker::VectorObject,
+            Value::Dictionary(_, _) => TypeMarker::Dictionary,
+            _ => unreachable!("Only values stored in the object reference table can be referenced"),
+        };
+
+        tuple((
+            self.write_type_marker(marker),
+            Length::Reference(index).write(self),
+        ))
+    }
+
+    /// Write a value, if the same `Rc` has already been written then a reference to it will be written instead
     pub(crate) fn write_value_element<'a, 'b: 'a, W: Write + 'a>(
         &'b self,
         s: &'b Rc<Value>,
     ) -> impl SerializeFn<W> + 'a {
-        move |out| self.write_value(s.deref())(out)
+        move |out| {
+            let ptr = Rc::as_ptr(s);
+            let existing = self
+                .object_pointer_table
+                .borrow()
+                .get(&ptr)
+                .map(|(_, index)| *index);
+            if let Some(index) = existing {
+                return self.write_reference_element(s, index)(out);
+            }
+
+            // Values written inline are added to the reference table before any of their children
+            let index = self.object_reference_table.len();
+            let out = self.write_value(s.deref())(out)?;
+            if is_referenceable(s) && self.object_reference_table.len() > index {
+                self.object_pointer_table
+                    .borrow_mut()
+                    .insert(ptr, (Rc::clone(s), index));
+            }
+            Ok(out)
+        }
     }
 
     fn write_value<'a, 'b: 'a, W: Write + 'a>(&'b self, s: &'b Value) -> impl SerializeFn<W> + 'a {
@@ -668,3 +752,132 @@ impl AMF3Encoder {
             .map(move |e| self.write_element_and_padding(e)))
     }
 }
+
+#[cfg(test)]
+mod write_number_tests {
+    use crate::amf3::write::AMF3Encoder;
+    use cookie_factory::gen;
+
+    #[test]
+    fn test_write_1byte_number() {
+        let e = AMF3Encoder::default();
+        let v = vec![];
+        let (b1, _) = gen(e.write_int(0b00101011), v).unwrap();
+        assert_eq!(b1, &[0b00101011]);
+    }
+
+    #[test]
+    fn test_write_4byte_number() {
+        let e = AMF3Encoder::default();
+        let v = vec![];
+        let (b1, _) = gen(e.write_int(2097280), v).unwrap();
+        assert_eq!(b1, &[0b10000000, 0b11000000, 0b10000000, 0b10000000]);
+    }
+
+    #[test]
+    fn write_neg_number() {
+        let e = AMF3Encoder::default();
+        let v = vec![];
+        let (b1, _) = gen(e.write_int(-268435455), v).unwrap();
+        assert_eq!(b1, &[192, 128, 128, 1]);
+    }
+}
+
+#[cfg(test)]
+mod class_encoding_tests {
+    use crate::amf3::custom_encoder::CustomEncoder;
+    use crate::amf3::read::AMF3Decoder;
+    use crate::amf3::write::AMF3Encoder;
+    use crate::nom_utils::AMFResult;
+    use crate::types::{ClassDefinition, Element, Value};
+    use cookie_factory::gen;
+    use nom::number::complete::be_u8;
+    use std::rc::Rc;
+
+    /// Stores the external data of class "C" as a single byte
+    struct ByteEncoder;
+
+    impl CustomEncoder for ByteEncoder {
+        fn encode(
+            &self,
+            elements: &[Element],
+            _class_def: &Option<ClassDefinition>,
+            _encoder: &AMF3Encoder,
+        ) -> Vec<u8> {
+            match *elements[0].value {
+                Value::Integer(n) => vec![n as u8],
+                _ => vec![],
+            }
+        }
+    }
+
+    fn parse_byte<'a>(i: &'a [u8], _amf3: &mut AMF3Decoder) -> AMFResult<'a, Vec<Element>> {
+        let (i, n) = be_u8(i)?;
+        Ok((i, vec![Element::new("byte", Value::Integer(n as i32))]))
+    }
+
+    #[test]
+    fn test_all_encodings_round_trip() {
+        let objects: [(u8, &[u8]); 5] = [
+            // Sealed, with static property "a" = 1
+            (0, &[0x0a, 0x13, 0x03, b'C', 0x03, b'a', 0x04, 0x01]),
+            // Externalizable
+            (1, &[0x0a, 0x07, 0x03, b'C', 0x05]),
+            // Dynamic, with dynamic property "b" = 2
+            (2, &[0x0a, 0x0b, 0x03, b'C', 0x03, b'b', 0x04, 0x02, 0x01]),
+            // Dynamic, with both static and dynamic properties
+            (
+                2,
+                &[
+                    0x0a, 0x1b, 0x03, b'C', 0x03, b'a', 0x04, 0x01, 0x03, b'b', 0x04, 0x02, 0x01,
+                ],
+            ),
+            // Externalizable and dynamic
+            (3, &[0x0a, 0x0f, 0x03, b'C', 0x05]),
+        ];
+
+        for (encoding, bytes) in objects.iter() {
+            let mut decoder = AMF3Decoder::default();
+            decoder
+                .external_decoders
+                .insert("C".to_string(), Rc::new(Box::new(parse_byte)));
+            let (rest, value) = decoder.parse_single_element(bytes).unwrap();
+            assert!(rest.is_empty());
+
+            let class_def = match &*value {
+                Value::Object(_, def) | Value::Custom(_, _, def) => def.clone().unwrap(),
+                v => panic!("Expected an object, got {:?}", v),
+            };
+            assert_eq!(class_def.encoding(), *encoding);
+
+            let mut encoder = AMF3Encoder::default();
+            encoder
+                .external_encoders
+                .insert("C".to_string(), Box::new(ByteEncoder));
+            let (written, _) = gen(encoder.write_value_element(&value), vec![]).unwrap();
+            assert_eq!(&written, bytes);
+        }
+    }
+}
+
+#[cfg(test)]
+mod object_reference_tests {
+    use crate::amf3::write::AMF3Encoder;
+    use crate::types::Value;
+    use cookie_factory::gen;
+    use std::rc::Rc;
+
+    #[test]
+    fn dropped_values_are_not_written_by_reference() {
+        let e = AMF3Encoder::default();
+        let first = Rc::new(Value::ByteArray(vec![1]));
+        let (b1, _) = gen(e.write_value_element(&first), vec![]).unwrap();
+        assert_eq!(b1, &[0x0c, 0x03, 0x01]);
+        drop(first);
+
+        // The address of the dropped value can't be reused until the tables are cleared
+        let second = Rc::new(Value::ByteArray(vec![2]));
+        let (b2, _) = gen(e.write_value_element(&second), vec![]).unwrap();
+        assert_eq!(b2, &[0x0c, 0x03, 0x02]);
+    }
+}
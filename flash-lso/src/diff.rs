@@ -0,0 +1,161 @@
+use crate::types::{bits_eq, ClassDefinition, Element, Lso, Value};
+use alloc::format;
+use alloc::rc::Rc;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+
+/// A single difference between two lsos, see `diff`
+/// Paths are element names and array indices joined with `.`, as in `Lso::walk_mut`
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub enum Change {
+    /// A value that is only in the new lso
+    Added {
+        /// The path of the value in the new lso
+        path: String,
+        /// The value that was added
+        value: Rc<Value>,
+    },
+    /// A value that is only in the old lso
+    Removed {
+        /// The path of the value in the old lso
+        path: String,
+    },
+    /// A value that is in both lsos but differs, and isn't a container that could be compared item by item
+    Modified {
+        /// The path of the value in both lsos
+        path: String,
+        /// The value in the old lso
+        old: Rc<Value>,
+        /// The value in the new lso
+        new: Rc<Value>,
+    },
+}
+
+impl Change {
+    /// Get the path of the value that changed
+    pub fn path(&self) -> &str {
+        match self {
+            Change::Added { path, .. }
+            | Change::Removed { path }
+            | Change::Modified { path, .. } => path,
+        }
+    }
+}
+
+/// Find the changes that turn the body of `old` into the body of `new`, the headers are not compared
+///
+/// Objects (with the same class name), the associative part of ECMA arrays and the root elements are matched by
+/// name, so reordering them is not a change. If a name appears more than once, occurrences are matched in order
+/// Strict arrays, the dense part of ECMA arrays and object vectors (with the same type name) are matched by index,
+/// so an item inserted at the start of an array changes every item after it
+/// Any other pair of values is compared as a whole, with numbers compared by their bits so that NaN is unchanged,
+/// this includes dictionaries, custom values and containers of different types or classes
+/// `AMF3` wrappers are ignored, so wrapping or unwrapping a value is not a change, but dates are compared with their
+/// timezones, use `Lso::normalize_dates` on both first to compare an AMF0 save with an AMF3 one
+///
+/// Within each container, removals and modifications are given in the order of the old values, followed by
+/// additions in the order of the new values
+pub fn diff(old: &Lso, new: &Lso) -> Vec<Change> {
+    let mut changes = Vec::new();
+    diff_elements(None, &old.body, &new.body, &mut changes);
+    changes
+}
+
+fn child_path(path: Option<&str>, name: &str) -> String {
+    match path {
+        Some(path) => format!("{}.{}", path, name),
+        None => name.to_string(),
+    }
+}
+
+fn diff_elements(path: Option<&str>, old: &[Element], new: &[Element], changes: &mut Vec<Change>) {
+    let mut matched = vec![false; new.len()];
+
+    for e in old {
+        let path = child_path(path, &e.name);
+        let other = (0..new.len()).find(|&index| !matched[index] && new[index].name == e.name);
+
+        match other {
+            Some(index) => {
+                matched[index] = true;
+                diff_values(&path, &e.value, &new[index].value, changes);
+            }
+            None => changes.push(Change::Removed { path }),
+        }
+    }
+
+    for (e, matched) in new.iter().zip(matched) {
+        if !matched {
+            changes.push(Change::Added {
+                path: child_path(path, &e.name),
+                value: Rc::clone(&e.value),
+            });
+        }
+    }
+}
+
+fn diff_items(path: &str, old: &[Rc<Value>], new: &[Rc<Value>], changes: &mut Vec<Change>) {
+    for (index, (old, new)) in old.iter().zip(new).enumerate() {
+        diff_values(&format!("{}.{}", path, index), old, new, changes);
+    }
+
+    for index in new.len()..old.len() {
+        changes.push(Change::Removed {
+            path: format!("{}.{}", path, index),
+        });
+    }
+
+    for (index, value) in new.iter().enumerate().skip(old.len()) {
+        changes.push(Change::Added {
+            path: format!("{}.{}", path, index),
+            value: Rc::clone(value),
+        });
+    }
+}
+
+fn diff_values(path: &str, old: &Rc<Value>, new: &Rc<Value>, changes: &mut Vec<Change>) {
+    if Rc::ptr_eq(old, new) {
+        return;
+    }
+
+    let (old, new) = (unwrap_amf3(old), unwrap_amf3(new));
+    match (old.as_ref(), new.as_ref()) {
+        (Value::Object(old_elements, old_def), Value::Object(new_elements, new_def))
+            if class_name(old_def) == class_name(new_def) =>
+        {
+            diff_elements(Some(path), old_elements, new_elements, changes)
+        }
+        (Value::ECMAArray(old_dense, old_assoc, _), Value::ECMAArray(new_dense, new_assoc, _)) => {
+            diff_items(path, old_dense, new_dense, changes);
+            diff_elements(Some(path), old_assoc, new_assoc, changes);
+        }
+        (Value::StrictArray(old_items), Value::StrictArray(new_items)) => {
+            diff_items(path, old_items, new_items, changes)
+        }
+        (
+            Value::VectorObject(old_items, old_type, _),
+            Value::VectorObject(new_items, new_type, _),
+        ) if old_type == new_type => diff_items(path, old_items, new_items, changes),
+        (a, b) if bits_eq(a, b) => {}
+        _ => changes.push(Change::Modified {
+            path: path.to_string(),
+            old: Rc::clone(old),
+            new: Rc::clone(new),
+        }),
+    }
+}
+
+/// Get the class name of an object, objects without a class definition are anonymous
+fn class_name(class_def: &Option<ClassDefinition>) -> &str {
+    class_def.as_ref().map_or("", |def| def.name.as_str())
+}
+
+/// Get the value inside any `AMF3` wrappers, see `Value::unwrap_amf3`
+fn unwrap_amf3(value: &Rc<Value>) -> &Rc<Value> {
+    match value.as_ref() {
+        Value::AMF3(inner) => unwrap_amf3(inner),
+        _ => value,
+    }
+}
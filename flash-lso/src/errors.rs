@@ -1,16 +1,175 @@
+use core::fmt;
 use nom::error::{ErrorKind, FromExternalError, ParseError};
-use thiserror::Error;
+
+/// The AMF3 reference tables
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum ReferenceTable {
+    /// The table of previously read strings
+    String,
+    /// The table of previously read class definitions
+    Trait,
+    /// The table of previously read objects, arrays and other complex values
+    Object,
+}
+
+impl fmt::Display for ReferenceTable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReferenceTable::String => f.write_str("string"),
+            ReferenceTable::Trait => f.write_str("trait"),
+            ReferenceTable::Object => f.write_str("object"),
+        }
+    }
+}
 
 /// Enum for representing decoding errors
-#[derive(Error, Debug, Copy, Clone, Eq, PartialEq)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
 pub enum Error<'a> {
     /// Out of bounds decoding
-    #[error("Out of bounds")]
     OutOfBounds,
 
     /// A nom internal error
-    #[error("Nom internal error")]
     Nom(&'a [u8], ErrorKind),
+
+    /// The length given in the header does not match the number of bytes that follow it
+    LengthMismatch {
+        /// The length given in the header
+        declared: u32,
+        /// The number of bytes following the length field
+        actual: usize,
+    },
+
+    /// The data doesn't start with the `0x00 0xbf` that begins every lso file
+    BadMagic,
+
+    /// The header doesn't contain the "TCSO" signature
+    BadSignature,
+
+    /// A reference to an entry that is not in a reference table, such as one that would be read later
+    BadReference {
+        /// The input following the reference, used to find its offset
+        input: &'a [u8],
+        /// The index that was referenced
+        index: usize,
+        /// The number of entries in the table when the reference was read
+        table_len: usize,
+        /// The table that was referenced
+        table_kind: ReferenceTable,
+    },
+
+    /// The data given to `read::parse_base64` is not valid base64
+    InvalidBase64,
+
+    /// There was no data to read, such as from a file that is empty
+    EmptyInput,
+}
+
+impl fmt::Display for Error<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::OutOfBounds => f.write_str("Out of bounds"),
+            Error::Nom(_, _) => f.write_str("Nom internal error"),
+            Error::LengthMismatch { declared, actual } => write!(
+                f,
+                "Header length mismatch, declared {} bytes but found {}",
+                declared, actual
+            ),
+            Error::BadMagic => f.write_str("Bad magic, not an lso file"),
+            Error::BadSignature => f.write_str("Bad header signature, expected TCSO"),
+            Error::BadReference {
+                index,
+                table_len,
+                table_kind,
+                ..
+            } => write!(
+                f,
+                "Bad {} reference {}, the table has {} entries",
+                table_kind, index, table_len
+            ),
+            Error::InvalidBase64 => f.write_str("Invalid base64"),
+            Error::EmptyInput => f.write_str("Empty input, not an lso file"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error<'_> {}
+
+/// Enum for representing encoding errors
+#[cfg(feature = "std")]
+#[derive(Debug)]
+pub enum WriteError {
+    /// The encoded lso doesn't fit in the 32 bit length field of the header
+    TooLarge,
+
+    /// A value could not be encoded, such as a flex type when no encoder is registered for it
+    Encoding(cookie_factory::GenError),
+}
+
+#[cfg(feature = "std")]
+impl fmt::Display for WriteError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WriteError::TooLarge => {
+                f.write_str("Lso is too large, the length doesn't fit in the header")
+            }
+            WriteError::Encoding(e) => write!(f, "Unable to encode value: {:?}", e),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for WriteError {}
+
+#[cfg(feature = "std")]
+impl From<cookie_factory::GenError> for WriteError {
+    fn from(e: cookie_factory::GenError) -> Self {
+        WriteError::Encoding(e)
+    }
+}
+
+impl<'a> Error<'a> {
+    /// Drop the input held by `Nom` and `BadReference` errors, so that the error can outlive the data it came from
+    /// The input is only used to find the offset of the error, so this should be done after finding it
+    pub(crate) fn without_input(self) -> Error<'static> {
+        match self {
+            Error::OutOfBounds => Error::OutOfBounds,
+            Error::Nom(_, kind) => Error::Nom(&[], kind),
+            Error::LengthMismatch { declared, actual } => {
+                Error::LengthMismatch { declared, actual }
+            }
+            Error::BadMagic => Error::BadMagic,
+            Error::BadSignature => Error::BadSignature,
+            Error::BadReference {
+                index,
+                table_len,
+                table_kind,
+                ..
+            } => Error::BadReference {
+                input: &[],
+                index,
+                table_len,
+                table_kind,
+            },
+            Error::InvalidBase64 => Error::InvalidBase64,
+            Error::EmptyInput => Error::EmptyInput,
+        }
+    }
+
+    /// Create a `BadReference` error for a reference to `index` in `table`
+    pub(crate) fn bad_reference<T>(
+        input: &'a [u8],
+        index: usize,
+        table: &[T],
+        table_kind: ReferenceTable,
+    ) -> nom::Err<Self> {
+        nom::Err::Error(Error::BadReference {
+            input,
+            index,
+            table_len: table.len(),
+            table_kind,
+        })
+    }
 }
 
 impl<'a> ParseError<&'a [u8]> for Error<'a> {
@@ -17,4 +17,45 @@ const CORRELATION_ID_BYTES_FLAG: u8 = 2;
 const OPERATION_FLAG: u8 = 1;
 
 pub mod read;
+#[cfg(feature = "std")]
 pub mod write;
+
+use crate::types::{Element, Value};
+use alloc::rc::Rc;
+use alloc::vec;
+use alloc::vec::Vec;
+
+/// The names of the custom elements that hold the value wrapped by a collection or proxy
+const WRAPPED_VALUE_NAMES: [&str; 2] = ["data", "object"];
+
+/// Get the value wrapped by a flex `ArrayCollection`, `ArrayList` or object proxy, as read by the decoders registered
+/// with `read::register_collection_decoders`
+/// The class name of the wrapper remains available from the class definition of the `Value::Custom`
+pub fn wrapped_value(value: &Value) -> Option<&Rc<Value>> {
+    match value {
+        Value::Custom(custom_elements, _, Some(_)) => match custom_elements.as_slice() {
+            [e] if WRAPPED_VALUE_NAMES.contains(&e.name()) => Some(&e.value),
+            _ => None,
+        },
+        Value::AMF3(inner) => wrapped_value(inner),
+        _ => None,
+    }
+}
+
+/// Wrap a value in the flex type with the given class name, such as `flex.messaging.io.ArrayCollection`, so that it
+/// is written by the encoders registered with `write::register_collection_encoders`
+pub fn wrap_value(class_name: &str, value: Rc<Value>) -> Value {
+    let name = if class_name.ends_with("Proxy") {
+        "object"
+    } else {
+        "data"
+    };
+    Value::externalizable(
+        class_name,
+        vec![Element {
+            name: name.into(),
+            value,
+        }],
+        Vec::new(),
+    )
+}
@@ -10,7 +10,12 @@ use crate::nom_utils::AMFResult;
 use crate::types::Element;
 use nom::number::complete::be_u8;
 
-use std::rc::Rc;
+use alloc::boxed::Box;
+use alloc::format;
+use alloc::rc::Rc;
+use alloc::string::ToString;
+use alloc::vec;
+use alloc::vec::Vec;
 
 fn parse_abstract_message_flags(i: &[u8]) -> AMFResult<'_, Vec<u8>> {
     let mut next_flag = true;
@@ -42,7 +47,7 @@ fn parse_abstract_message<'a>(i: &'a [u8], amf3: &mut AMF3Decoder) -> AMFResult<
             if flags & BODY_FLAG != 0 {
                 let (j, value) = amf3.parse_single_element(k)?;
                 elements.push(Element {
-                    name: "body".to_string(),
+                    name: "body".into(),
                     value,
                 });
                 k = j;
@@ -50,7 +55,7 @@ fn parse_abstract_message<'a>(i: &'a [u8], amf3: &mut AMF3Decoder) -> AMFResult<
             if flags & CLIENT_ID_FLAG != 0 {
                 let (j, value) = amf3.parse_single_element(k)?;
                 elements.push(Element {
-                    name: "client_id".to_string(),
+                    name: "client_id".into(),
                     value,
                 });
                 k = j;
@@ -58,7 +63,7 @@ fn parse_abstract_message<'a>(i: &'a [u8], amf3: &mut AMF3Decoder) -> AMFResult<
             if flags & DESTINATION_ID_FLAG != 0 {
                 let (j, value) = amf3.parse_single_element(k)?;
                 elements.push(Element {
-                    name: "destination".to_string(),
+                    name: "destination".into(),
                     value,
                 });
                 k = j;
@@ -66,7 +71,7 @@ fn parse_abstract_message<'a>(i: &'a [u8], amf3: &mut AMF3Decoder) -> AMFResult<
             if flags & HEADERS_FLAG != 0 {
                 let (j, value) = amf3.parse_single_element(k)?;
                 elements.push(Element {
-                    name: "headers".to_string(),
+                    name: "headers".into(),
                     value,
                 });
                 k = j;
@@ -74,7 +79,7 @@ fn parse_abstract_message<'a>(i: &'a [u8], amf3: &mut AMF3Decoder) -> AMFResult<
             if flags & MESSAGE_ID_FLAG != 0 {
                 let (j, value) = amf3.parse_single_element(k)?;
                 elements.push(Element {
-                    name: "message_id".to_string(),
+                    name: "message_id".into(),
                     value,
                 });
                 k = j;
@@ -82,7 +87,7 @@ fn parse_abstract_message<'a>(i: &'a [u8], amf3: &mut AMF3Decoder) -> AMFResult<
             if flags & TIMESTAMP_FLAG != 0 {
                 let (j, value) = amf3.parse_single_element(k)?;
                 elements.push(Element {
-                    name: "timestamp".to_string(),
+                    name: "timestamp".into(),
                     value,
                 });
                 k = j;
@@ -90,7 +95,7 @@ fn parse_abstract_message<'a>(i: &'a [u8], amf3: &mut AMF3Decoder) -> AMFResult<
             if flags & TTL_FLAG != 0 {
                 let (j, value) = amf3.parse_single_element(k)?;
                 elements.push(Element {
-                    name: "ttl".to_string(),
+                    name: "ttl".into(),
                     value,
                 });
                 k = j;
@@ -100,7 +105,7 @@ fn parse_abstract_message<'a>(i: &'a [u8], amf3: &mut AMF3Decoder) -> AMFResult<
             if (flags & CLIENT_ID_BYTES_FLAG) != 0 {
                 let (j, value) = amf3.parse_single_element(k)?;
                 elements.push(Element {
-                    name: "client_id_bytes".to_string(),
+                    name: "client_id_bytes".into(),
                     value,
                 });
                 k = j;
@@ -108,7 +113,7 @@ fn parse_abstract_message<'a>(i: &'a [u8], amf3: &mut AMF3Decoder) -> AMFResult<
             if (flags & MESSAGE_ID_BYTES_FLAG) != 0 {
                 let (j, value) = amf3.parse_single_element(k)?;
                 elements.push(Element {
-                    name: "message_id_bytes".to_string(),
+                    name: "message_id_bytes".into(),
                     value,
                 });
                 k = j;
@@ -122,7 +127,7 @@ fn parse_abstract_message<'a>(i: &'a [u8], amf3: &mut AMF3Decoder) -> AMFResult<
                 if (flags >> j) != 0 {
                     let (jj, value) = amf3.parse_single_element(k)?;
                     elements.push(Element {
-                        name: format!("children_{}", j),
+                        name: format!("children_{}", j).into(),
                         value,
                     });
                     k = jj;
@@ -148,7 +153,7 @@ fn parse_async_message<'a>(i: &'a [u8], amf3: &mut AMF3Decoder) -> AMFResult<'a,
             if (flags & CORRELATION_ID_FLAG) != 0u8 {
                 let (j, value) = amf3.parse_single_element(k)?;
                 elements.push(Element {
-                    name: "correlation_id".to_string(),
+                    name: "correlation_id".into(),
                     value,
                 });
                 k = j;
@@ -156,7 +161,7 @@ fn parse_async_message<'a>(i: &'a [u8], amf3: &mut AMF3Decoder) -> AMFResult<'a,
             if (flags & CORRELATION_ID_BYTES_FLAG) != 0u8 {
                 let (j, value) = amf3.parse_single_element(k)?;
                 elements.push(Element {
-                    name: "correlation_id_bytes".to_string(),
+                    name: "correlation_id_bytes".into(),
                     value,
                 });
                 k = j;
@@ -169,7 +174,7 @@ fn parse_async_message<'a>(i: &'a [u8], amf3: &mut AMF3Decoder) -> AMFResult<'a,
                 if (flags >> j) & 1 != 0u8 {
                     let (jj, value) = amf3.parse_single_element(k)?;
                     elements.push(Element {
-                        name: format!("children_async_{}", j),
+                        name: format!("children_async_{}", j).into(),
                         value,
                     });
                     k = jj;
@@ -198,7 +203,7 @@ fn parse_acknowledge_message<'a>(
                 if (flags >> j) & 1 != 0 {
                     let (jj, value) = amf3.parse_single_element(k)?;
                     elements.push(Element {
-                        name: format!("children_acknowledge_{}", j),
+                        name: format!("children_acknowledge_{}", j).into(),
                         value,
                     });
                     k = jj;
@@ -225,7 +230,7 @@ fn parse_command_message<'a>(i: &'a [u8], amf3: &mut AMF3Decoder) -> AMFResult<'
             if (flags & OPERATION_FLAG) != 0 {
                 let (j, value) = amf3.parse_single_element(i)?;
                 elements.push(Element {
-                    name: "operation".to_string(),
+                    name: "operation".into(),
                     value,
                 });
                 k = j;
@@ -238,7 +243,7 @@ fn parse_command_message<'a>(i: &'a [u8], amf3: &mut AMF3Decoder) -> AMFResult<'
                 if (flags >> j) & 1 != 0 {
                     let (jj, value) = amf3.parse_single_element(k)?;
                     elements.push(Element {
-                        name: format!("children_command_{}", j),
+                        name: format!("children_command_{}", j).into(),
                         value,
                     });
                     k = jj;
@@ -255,7 +260,7 @@ fn parse_array_collection<'a>(i: &'a [u8], amf3: &mut AMF3Decoder) -> AMFResult<
     let (i, value) = amf3.parse_single_element(i)?;
 
     let el = vec![Element {
-        name: "data".to_string(),
+        name: "data".into(),
         value,
     }];
 
@@ -267,7 +272,7 @@ fn parse_object_proxy<'a>(i: &'a [u8], amf3: &mut AMF3Decoder) -> AMFResult<'a,
     let (i, value) = amf3.parse_single_element(i)?;
 
     let el = vec![Element {
-        name: "object".to_string(),
+        name: "object".into(),
         value,
     }];
 
@@ -310,6 +315,15 @@ pub fn register_decoders(decoder: &mut AMF3Decoder) {
         Rc::new(Box::new(parse_acknowledge_message)),
     );
 
+    register_collection_decoders(decoder);
+}
+
+/// Register only the decoders for the flex types that wrap a single value into the given AMF3Decoder
+/// These are `ArrayCollection` and `ArrayList`, which wrap an array, and `ObjectProxy`, `ManagedObjectProxy` and
+/// `SerializationProxy`, which wrap an object
+/// The wrapped value is read as the only custom element of a `Value::Custom`, see `flex::wrapped_value`
+#[inline]
+pub fn register_collection_decoders(decoder: &mut AMF3Decoder) {
     decoder.external_decoders.insert(
         "flex.messaging.io.ArrayCollection".to_string(),
         Rc::new(Box::new(parse_array_collection)),
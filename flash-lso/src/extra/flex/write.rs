@@ -17,9 +17,9 @@ use std::io::Write;
 struct ArrayCollection;
 
 impl CustomEncoder for ArrayCollection {
-    fn encode<'a>(
+    fn encode(
         &self,
-        elements: &'a [Element],
+        elements: &[Element],
         _class_def: &Option<ClassDefinition>,
         encoder: &AMF3Encoder,
     ) -> Vec<u8> {
@@ -35,7 +35,7 @@ impl ArrayCollection {
         elements: &'b [Element],
         encoder: &'a AMF3Encoder,
     ) -> impl SerializeFn<W> + 'a {
-        let data = elements.get(0).unwrap();
+        let data = elements.first().unwrap();
         encoder.write_value_element(&data.value)
     }
 }
@@ -43,9 +43,9 @@ impl ArrayCollection {
 struct ObjectProxy;
 
 impl CustomEncoder for ObjectProxy {
-    fn encode<'a>(
+    fn encode(
         &self,
-        elements: &'a [Element],
+        elements: &[Element],
         _class_def: &Option<ClassDefinition>,
         encoder: &AMF3Encoder,
     ) -> Vec<u8> {
@@ -61,7 +61,7 @@ impl ObjectProxy {
         elements: &'b [Element],
         encoder: &'a AMF3Encoder,
     ) -> impl SerializeFn<W> + 'a {
-        let data = elements.get(0).unwrap();
+        let data = elements.first().unwrap();
         encoder.write_value_element(&data.value)
     }
 }
@@ -79,9 +79,9 @@ fn write_flags<'a, 'b: 'a, W: Write + 'a>(flags: &'a [u8]) -> impl SerializeFn<W
 struct AbstractMessage;
 
 impl CustomEncoder for AbstractMessage {
-    fn encode<'a>(
+    fn encode(
         &self,
-        elements: &'a [Element],
+        elements: &[Element],
         _class_def: &Option<ClassDefinition>,
         encoder: &AMF3Encoder,
     ) -> Vec<u8> {
@@ -103,7 +103,7 @@ fn write_abstract_message<'a, 'b: 'a, W: Write + 'a>(
 
             if let Some(v) = elements
                 .iter()
-                .find(|e| e.name == "body")
+                .find(|e| e.name() == "body")
                 .map(|e| e.value.clone())
             {
                 flag |= BODY_FLAG;
@@ -111,7 +111,7 @@ fn write_abstract_message<'a, 'b: 'a, W: Write + 'a>(
             }
             if let Some(v) = elements
                 .iter()
-                .find(|e| e.name == "client_id")
+                .find(|e| e.name() == "client_id")
                 .map(|e| e.value.clone())
             {
                 flag |= CLIENT_ID_FLAG;
@@ -119,7 +119,7 @@ fn write_abstract_message<'a, 'b: 'a, W: Write + 'a>(
             }
             if let Some(v) = elements
                 .iter()
-                .find(|e| e.name == "destination")
+                .find(|e| e.name() == "destination")
                 .map(|e| e.value.clone())
             {
                 flag |= DESTINATION_ID_FLAG;
@@ -127,7 +127,7 @@ fn write_abstract_message<'a, 'b: 'a, W: Write + 'a>(
             }
             if let Some(v) = elements
                 .iter()
-                .find(|e| e.name == "headers")
+                .find(|e| e.name() == "headers")
                 .map(|e| e.value.clone())
             {
                 flag |= HEADERS_FLAG;
@@ -135,7 +135,7 @@ fn write_abstract_message<'a, 'b: 'a, W: Write + 'a>(
             }
             if let Some(v) = elements
                 .iter()
-                .find(|e| e.name == "message_id")
+                .find(|e| e.name() == "message_id")
                 .map(|e| e.value.clone())
             {
                 flag |= MESSAGE_ID_FLAG;
@@ -143,7 +143,7 @@ fn write_abstract_message<'a, 'b: 'a, W: Write + 'a>(
             }
             if let Some(v) = elements
                 .iter()
-                .find(|e| e.name == "timestamp")
+                .find(|e| e.name() == "timestamp")
                 .map(|e| e.value.clone())
             {
                 flag |= TIMESTAMP_FLAG;
@@ -151,7 +151,7 @@ fn write_abstract_message<'a, 'b: 'a, W: Write + 'a>(
             }
             if let Some(v) = elements
                 .iter()
-                .find(|e| e.name == "ttl")
+                .find(|e| e.name() == "ttl")
                 .map(|e| e.value.clone())
             {
                 flag |= TTL_FLAG;
@@ -160,7 +160,7 @@ fn write_abstract_message<'a, 'b: 'a, W: Write + 'a>(
 
             if let Some(v) = elements
                 .iter()
-                .find(|e| e.name == "children_1")
+                .find(|e| e.name() == "children_1")
                 .map(|e| e.value.clone())
             {
                 flag |= 0b0100_0000;
@@ -174,7 +174,7 @@ fn write_abstract_message<'a, 'b: 'a, W: Write + 'a>(
 
             if let Some(v) = elements
                 .iter()
-                .find(|e| e.name == "client_id_bytes")
+                .find(|e| e.name() == "client_id_bytes")
                 .map(|e| e.value.clone())
             {
                 flag |= CLIENT_ID_BYTES_FLAG;
@@ -182,7 +182,7 @@ fn write_abstract_message<'a, 'b: 'a, W: Write + 'a>(
             }
             if let Some(v) = elements
                 .iter()
-                .find(|e| e.name == "message_id_bytes")
+                .find(|e| e.name() == "message_id_bytes")
                 .map(|e| e.value.clone())
             {
                 flag |= MESSAGE_ID_BYTES_FLAG;
@@ -192,7 +192,7 @@ fn write_abstract_message<'a, 'b: 'a, W: Write + 'a>(
             for n in 2..7 {
                 if let Some(v) = elements
                     .iter()
-                    .find(|e| e.name == format!("children_{}", n))
+                    .find(|e| e.name() == format!("children_{}", n))
                     .map(|e| e.value.clone())
                 {
                     flag |= 0b1 << n;
@@ -210,7 +210,7 @@ fn write_abstract_message<'a, 'b: 'a, W: Write + 'a>(
             loop {
                 if let Some(v) = elements
                     .iter()
-                    .find(|e| e.name == format!("children_{}", n + base))
+                    .find(|e| e.name() == format!("children_{}", n + base))
                     .map(|e| e.value.clone())
                 {
                     flag |= 0b1 << n;
@@ -245,9 +245,9 @@ fn write_abstract_message<'a, 'b: 'a, W: Write + 'a>(
 struct AsyncMessage;
 
 impl CustomEncoder for AsyncMessage {
-    fn encode<'a>(
+    fn encode(
         &self,
-        elements: &'a [Element],
+        elements: &[Element],
         _class_def: &Option<ClassDefinition>,
         encoder: &AMF3Encoder,
     ) -> Vec<u8> {
@@ -269,7 +269,7 @@ fn write_async_message<'a, 'b: 'a, W: Write + 'a>(
 
             if let Some(v) = elements
                 .iter()
-                .find(|e| e.name == "correlation_id")
+                .find(|e| e.name() == "correlation_id")
                 .map(|e| e.value.clone())
             {
                 flag |= CORRELATION_ID_FLAG;
@@ -278,7 +278,7 @@ fn write_async_message<'a, 'b: 'a, W: Write + 'a>(
 
             if let Some(v) = elements
                 .iter()
-                .find(|e| e.name == "correlation_id_bytes")
+                .find(|e| e.name() == "correlation_id_bytes")
                 .map(|e| e.value.clone())
             {
                 flag |= CORRELATION_ID_BYTES_FLAG;
@@ -288,7 +288,7 @@ fn write_async_message<'a, 'b: 'a, W: Write + 'a>(
             for n in 2..7 {
                 if let Some(v) = elements
                     .iter()
-                    .find(|e| e.name == format!("children_async_{}", n))
+                    .find(|e| e.name() == format!("children_async_{}", n))
                     .map(|e| e.value.clone())
                 {
                     flag |= 0b1 << n;
@@ -305,7 +305,7 @@ fn write_async_message<'a, 'b: 'a, W: Write + 'a>(
             loop {
                 if let Some(v) = elements
                     .iter()
-                    .find(|e| e.name == format!("children_async_{}", n + base))
+                    .find(|e| e.name() == format!("children_async_{}", n + base))
                     .map(|e| e.value.clone())
                 {
                     flag |= 0b1 << n;
@@ -341,9 +341,9 @@ fn write_async_message<'a, 'b: 'a, W: Write + 'a>(
 struct AcknowledgeMessage;
 
 impl CustomEncoder for AcknowledgeMessage {
-    fn encode<'a>(
+    fn encode(
         &self,
-        elements: &'a [Element],
+        elements: &[Element],
         _class_def: &Option<ClassDefinition>,
         encoder: &AMF3Encoder,
     ) -> Vec<u8> {
@@ -367,7 +367,7 @@ fn write_acknowledge_message<'a, 'b: 'a, W: Write + 'a>(
             loop {
                 if let Some(v) = elements
                     .iter()
-                    .find(|e| e.name == format!("children_acknowledge_{}", n + base))
+                    .find(|e| e.name() == format!("children_acknowledge_{}", n + base))
                     .map(|e| e.value.clone())
                 {
                     flag |= 0b1 << n;
@@ -403,9 +403,9 @@ fn write_acknowledge_message<'a, 'b: 'a, W: Write + 'a>(
 struct CommandMessage;
 
 impl CustomEncoder for CommandMessage {
-    fn encode<'a>(
+    fn encode(
         &self,
-        elements: &'a [Element],
+        elements: &[Element],
         _class_def: &Option<ClassDefinition>,
         encoder: &AMF3Encoder,
     ) -> Vec<u8> {
@@ -427,7 +427,7 @@ fn write_command_message<'a, 'b: 'a, W: Write + 'a>(
 
             if let Some(v) = elements
                 .iter()
-                .find(|e| e.name == "operation")
+                .find(|e| e.name() == "operation")
                 .map(|e| e.value.clone())
             {
                 flag |= OPERATION_FLAG;
@@ -437,7 +437,7 @@ fn write_command_message<'a, 'b: 'a, W: Write + 'a>(
             for n in 1..7 {
                 if let Some(v) = elements
                     .iter()
-                    .find(|e| e.name == format!("children_command_{}", n))
+                    .find(|e| e.name() == format!("children_command_{}", n))
                     .map(|e| e.value.clone())
                 {
                     flag |= 0b1 << n;
@@ -454,7 +454,7 @@ fn write_command_message<'a, 'b: 'a, W: Write + 'a>(
             loop {
                 if let Some(v) = elements
                     .iter()
-                    .find(|e| e.name == format!("children_command_{}", n + base))
+                    .find(|e| e.name() == format!("children_command_{}", n + base))
                     .map(|e| e.value.clone())
                 {
                     flag |= 0b1 << n;
@@ -490,16 +490,6 @@ fn write_command_message<'a, 'b: 'a, W: Write + 'a>(
 /// Register the flex encoders into the given AMF3Encoder
 #[inline]
 pub fn register_encoders(encoder: &mut AMF3Encoder) {
-    encoder.external_encoders.insert(
-        "flex.messaging.io.ArrayCollection".to_string(),
-        Box::new(ArrayCollection {}),
-    );
-
-    encoder.external_encoders.insert(
-        "flex.messaging.io.ArrayList".to_string(),
-        Box::new(ArrayCollection {}),
-    );
-
     encoder.external_encoders.insert(
         "flex.messaging.io.AbstractMessage".to_string(),
         Box::new(AbstractMessage {}),
@@ -540,6 +530,23 @@ pub fn register_encoders(encoder: &mut AMF3Encoder) {
         Box::new(AcknowledgeMessage {}),
     );
 
+    register_collection_encoders(encoder);
+}
+
+/// Register only the encoders for the flex types that wrap a single value into the given AMF3Encoder
+/// These match the decoders registered by `flex::read::register_collection_decoders`
+#[inline]
+pub fn register_collection_encoders(encoder: &mut AMF3Encoder) {
+    encoder.external_encoders.insert(
+        "flex.messaging.io.ArrayCollection".to_string(),
+        Box::new(ArrayCollection {}),
+    );
+
+    encoder.external_encoders.insert(
+        "flex.messaging.io.ArrayList".to_string(),
+        Box::new(ArrayCollection {}),
+    );
+
     encoder.external_encoders.insert(
         "flex.messaging.io.ObjectProxy".to_string(),
         Box::new(ObjectProxy {}),
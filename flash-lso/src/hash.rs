@@ -0,0 +1,238 @@
+use crate::types::{ClassDefinition, Element, Value};
+use crate::Map;
+use alloc::rc::Rc;
+use alloc::vec::Vec;
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+// Tags written before each value, so that different types with the same content hash differently
+const TAG_NUMBER: u8 = 0;
+const TAG_BOOL: u8 = 1;
+const TAG_STRING: u8 = 2;
+const TAG_OBJECT: u8 = 3;
+const TAG_NULL: u8 = 4;
+const TAG_UNDEFINED: u8 = 5;
+const TAG_ECMA_ARRAY: u8 = 6;
+const TAG_STRICT_ARRAY: u8 = 7;
+const TAG_DATE: u8 = 8;
+const TAG_UNSUPPORTED: u8 = 9;
+const TAG_XML: u8 = 10;
+const TAG_BYTE_ARRAY: u8 = 11;
+const TAG_VECTOR_INT: u8 = 12;
+const TAG_VECTOR_UINT: u8 = 13;
+const TAG_VECTOR_DOUBLE: u8 = 14;
+const TAG_VECTOR_OBJECT: u8 = 15;
+const TAG_DICTIONARY: u8 = 16;
+const TAG_CUSTOM: u8 = 17;
+
+/// 64 bit FNV-1a, used instead of `DefaultHasher` as its output is not guaranteed to be stable between releases
+/// All integers are fed in little endian so the hash is the same on every platform
+/// Each nested value is hashed on its own and its digest fed to the hash of the value holding it, so a value shared
+/// in many places (which a few bytes of AMF references can do exponentially often) is only hashed once
+pub(crate) struct ContentHasher<'v> {
+    state: u64,
+    sort_properties: bool,
+    /// The digests of the nested values hashed so far, by `Rc` pointer
+    digests: Map<*const Value, u64>,
+    /// Keeps the values in `digests` borrowed, so their addresses can't be reused while hashing
+    _values: core::marker::PhantomData<&'v Value>,
+}
+
+impl<'v> ContentHasher<'v> {
+    pub(crate) fn new(sort_properties: bool) -> Self {
+        Self {
+            state: FNV_OFFSET_BASIS,
+            sort_properties,
+            digests: Map::default(),
+            _values: core::marker::PhantomData,
+        }
+    }
+
+    pub(crate) fn finish(&self) -> u64 {
+        self.state
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for b in bytes {
+            self.state ^= u64::from(*b);
+            self.state = self.state.wrapping_mul(FNV_PRIME);
+        }
+    }
+
+    fn write_len(&mut self, len: usize) {
+        self.write(&(len as u64).to_le_bytes());
+    }
+
+    fn write_str(&mut self, s: &str) {
+        self.write_len(s.len());
+        self.write(s.as_bytes());
+    }
+
+    fn write_number(&mut self, n: f64) {
+        // -0.0 and 0.0 compare equal and NaN payloads carry no meaning, so give them the same bits
+        let n = if n == 0.0 {
+            0.0
+        } else if n.is_nan() {
+            f64::NAN
+        } else {
+            n
+        };
+        self.write(&n.to_bits().to_le_bytes());
+    }
+
+    pub(crate) fn write_elements(&mut self, elements: &'v [Element]) {
+        self.write_len(elements.len());
+        if self.sort_properties {
+            let mut sorted: Vec<&Element> = elements.iter().collect();
+            sorted.sort_by(|a, b| a.name.cmp(&b.name));
+            for e in sorted {
+                self.write_element(e);
+            }
+        } else {
+            for e in elements {
+                self.write_element(e);
+            }
+        }
+    }
+
+    fn write_element(&mut self, element: &'v Element) {
+        self.write_str(&element.name);
+        self.write_rc(&element.value);
+    }
+
+    /// Feed the digest of a nested value, hashing it only the first time it is seen
+    fn write_rc(&mut self, value: &'v Rc<Value>) {
+        // AMF3 wrappers hash the same as their contents, wherever they are
+        let mut value = value;
+        while let Value::AMF3(inner) = value.as_ref() {
+            value = inner;
+        }
+
+        let ptr = Rc::as_ptr(value);
+        let digest = match self.digests.get(&ptr) {
+            Some(digest) => *digest,
+            None => {
+                let outer = core::mem::replace(&mut self.state, FNV_OFFSET_BASIS);
+                self.write_value(value);
+                let digest = core::mem::replace(&mut self.state, outer);
+                self.digests.insert(ptr, digest);
+                digest
+            }
+        };
+        self.write(&digest.to_le_bytes());
+    }
+
+    fn write_class_name(&mut self, class_def: &Option<ClassDefinition>) {
+        match class_def {
+            Some(class_def) => {
+                self.write(&[1]);
+                self.write_str(&class_def.name);
+            }
+            None => self.write(&[0]),
+        }
+    }
+
+    fn write_value(&mut self, value: &'v Value) {
+        match value {
+            Value::Number(n) => {
+                self.write(&[TAG_NUMBER]);
+                self.write_number(*n);
+            }
+            Value::Integer(n) => {
+                self.write(&[TAG_NUMBER]);
+                self.write_number(f64::from(*n));
+            }
+            Value::Bool(b) => self.write(&[TAG_BOOL, u8::from(*b)]),
+            Value::String(s) => {
+                self.write(&[TAG_STRING]);
+                self.write_str(s);
+            }
+            Value::Object(elements, class_def) => {
+                self.write(&[TAG_OBJECT]);
+                self.write_class_name(class_def);
+                self.write_elements(elements);
+            }
+            Value::Null => self.write(&[TAG_NULL]),
+            Value::Undefined => self.write(&[TAG_UNDEFINED]),
+            Value::ECMAArray(dense, assoc, _) => {
+                self.write(&[TAG_ECMA_ARRAY]);
+                self.write_values(dense);
+                self.write_elements(assoc);
+            }
+            Value::StrictArray(items) => {
+                self.write(&[TAG_STRICT_ARRAY]);
+                self.write_values(items);
+            }
+            Value::Date(time, tz) => {
+                self.write(&[TAG_DATE]);
+                self.write_number(*time);
+                match tz {
+                    Some(tz) => {
+                        self.write(&[1]);
+                        self.write(&tz.to_le_bytes());
+                    }
+                    None => self.write(&[0]),
+                }
+            }
+            Value::Unsupported => self.write(&[TAG_UNSUPPORTED]),
+            Value::XML(content, is_string) => {
+                self.write(&[TAG_XML, u8::from(*is_string)]);
+                self.write_str(content);
+            }
+            Value::AMF3(inner) => self.write_value(inner),
+            Value::ByteArray(bytes) => {
+                self.write(&[TAG_BYTE_ARRAY]);
+                self.write_len(bytes.len());
+                self.write(bytes);
+            }
+            Value::VectorInt(items, fixed_length) => {
+                self.write(&[TAG_VECTOR_INT, u8::from(*fixed_length)]);
+                self.write_len(items.len());
+                for n in items {
+                    self.write(&n.to_le_bytes());
+                }
+            }
+            Value::VectorUInt(items, fixed_length) => {
+                self.write(&[TAG_VECTOR_UINT, u8::from(*fixed_length)]);
+                self.write_len(items.len());
+                for n in items {
+                    self.write(&n.to_le_bytes());
+                }
+            }
+            Value::VectorDouble(items, fixed_length) => {
+                self.write(&[TAG_VECTOR_DOUBLE, u8::from(*fixed_length)]);
+                self.write_len(items.len());
+                for n in items {
+                    self.write_number(*n);
+                }
+            }
+            Value::VectorObject(items, type_name, fixed_length) => {
+                self.write(&[TAG_VECTOR_OBJECT, u8::from(*fixed_length)]);
+                self.write_str(type_name);
+                self.write_values(items);
+            }
+            Value::Dictionary(pairs, weak_keys) => {
+                self.write(&[TAG_DICTIONARY, u8::from(*weak_keys)]);
+                self.write_len(pairs.len());
+                for (k, v) in pairs {
+                    self.write_rc(k);
+                    self.write_rc(v);
+                }
+            }
+            Value::Custom(custom_elements, elements, class_def) => {
+                self.write(&[TAG_CUSTOM]);
+                self.write_class_name(class_def);
+                self.write_elements(custom_elements);
+                self.write_elements(elements);
+            }
+        }
+    }
+
+    fn write_values(&mut self, values: &'v [Rc<Value>]) {
+        self.write_len(values.len());
+        for v in values {
+            self.write_rc(v);
+        }
+    }
+}
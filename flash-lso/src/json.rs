@@ -0,0 +1,287 @@
+//! Export of Lso contents as plain JSON
+//!
+//! Unlike the serde derives on the types in this crate, which produce a lossless but verbose
+//! representation, this produces the JSON that a person would naturally write for the data, making
+//! it easy to inspect with tools like `jq`. This conversion is lossy in the following ways:
+//!
+//! - `Integer` and `Number` both become JSON numbers, non-finite numbers become `null`
+//! - `Null`, `Undefined` and `Unsupported` all become `null`
+//! - `ByteArray` becomes a base64 (standard alphabet, padded) string
+//! - `Date` becomes an ISO-8601 string in UTC (e.g. `2014-09-02T12:27:07.254Z`), the AMF0 timezone is dropped
+//! - `XML` becomes a string, the XMLString / XMLDocument distinction is dropped
+//! - Class definitions, vector fixed-length flags and dictionary weak-key flags are dropped
+//! - Objects become JSON objects, if a name appears more than once then the last value is kept
+//! - `ECMAArray` becomes an array if it has no associative part, otherwise an object where the dense part is keyed by index
+//! - `Dictionary` always becomes an array of `{"key": ..., "value": ...}` objects, as keys can be any value
+//! - `Custom` becomes an object containing both the custom and standard elements
+//! - `AMF3` wrapped values are replaced by their contents
+//!
+//! Values that were read by reference (or share an `Rc`) are inlined at every place they occur, so
+//! a value referenced N times will appear N times in the output.
+
+use crate::types::{Element, Lso, Value};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use serde_json::{Map, Number};
+
+/// Convert an Lso body into a JSON object keyed by element name, see the module docs for details of the mapping
+pub fn to_json(lso: &Lso) -> serde_json::Value {
+    elements_to_json(&lso.body)
+}
+
+/// Convert a single value to JSON, see the module docs for details of the mapping
+pub fn value_to_json(value: &Value) -> serde_json::Value {
+    match value {
+        Value::Number(n) => number_to_json(*n),
+        Value::Integer(i) => serde_json::Value::from(*i),
+        Value::Bool(b) => serde_json::Value::Bool(*b),
+        Value::String(s) => serde_json::Value::String(s.clone()),
+        Value::XML(content, _) => serde_json::Value::String(content.clone()),
+        Value::Null | Value::Undefined | Value::Unsupported => serde_json::Value::Null,
+        Value::Object(elements, _) => elements_to_json(elements),
+        Value::ECMAArray(dense, assoc, _) => {
+            if assoc.is_empty() {
+                serde_json::Value::Array(dense.iter().map(|v| value_to_json(v)).collect())
+            } else {
+                let mut map = Map::new();
+                for (index, v) in dense.iter().enumerate() {
+                    map.insert(index.to_string(), value_to_json(v));
+                }
+                for e in assoc {
+                    map.insert(e.name.to_string(), value_to_json(&e.value));
+                }
+                serde_json::Value::Object(map)
+            }
+        }
+        Value::StrictArray(items) | Value::VectorObject(items, _, _) => {
+            serde_json::Value::Array(items.iter().map(|v| value_to_json(v)).collect())
+        }
+        Value::Date(millis, _) => {
+            if millis.is_finite() {
+                serde_json::Value::String(format_iso8601(*millis))
+            } else {
+                serde_json::Value::Null
+            }
+        }
+        Value::AMF3(inner) => value_to_json(inner),
+        Value::ByteArray(bytes) => serde_json::Value::String(STANDARD.encode(bytes)),
+        Value::VectorInt(items, _) => items.iter().copied().collect(),
+        Value::VectorUInt(items, _) => items.iter().copied().collect(),
+        Value::VectorDouble(items, _) => {
+            serde_json::Value::Array(items.iter().map(|n| number_to_json(*n)).collect())
+        }
+        Value::Dictionary(pairs, _) => serde_json::Value::Array(
+            pairs
+                .iter()
+                .map(|(k, v)| {
+                    let mut map = Map::new();
+                    map.insert("key".to_string(), value_to_json(k));
+                    map.insert("value".to_string(), value_to_json(v));
+                    serde_json::Value::Object(map)
+                })
+                .collect(),
+        ),
+        Value::Custom(custom_elements, elements, _) => {
+            let mut map = Map::new();
+            for e in custom_elements.iter().chain(elements.iter()) {
+                map.insert(e.name.to_string(), value_to_json(&e.value));
+            }
+            serde_json::Value::Object(map)
+        }
+    }
+}
+
+fn elements_to_json(elements: &[Element]) -> serde_json::Value {
+    let mut map = Map::new();
+    for e in elements {
+        map.insert(e.name.to_string(), value_to_json(&e.value));
+    }
+    serde_json::Value::Object(map)
+}
+
+fn number_to_json(n: f64) -> serde_json::Value {
+    Number::from_f64(n)
+        .map(serde_json::Value::Number)
+        .unwrap_or(serde_json::Value::Null)
+}
+
+/// Format milliseconds since the unix epoch as an ISO-8601 UTC timestamp
+fn format_iso8601(millis: f64) -> String {
+    let total_millis = millis.floor() as i64;
+    let days = total_millis.div_euclid(86_400_000);
+    let millis_of_day = total_millis.rem_euclid(86_400_000);
+
+    // Convert days since epoch to a civil date, see http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:03}Z",
+        year,
+        month,
+        day,
+        millis_of_day / 3_600_000,
+        (millis_of_day / 60_000) % 60,
+        (millis_of_day / 1000) % 60,
+        millis_of_day % 1000
+    )
+}
+
+#[cfg(test)]
+mod json_tests {
+    use crate::json::{to_json, value_to_json};
+    use crate::types::{AMFVersion, ClassDefinition, Element, Lso, Value};
+    use serde_json::json;
+    use std::rc::Rc;
+
+    #[test]
+    fn test_scalars() {
+        assert_eq!(json!(1.5), value_to_json(&Value::Number(1.5)));
+        assert_eq!(json!(null), value_to_json(&Value::Number(f64::NAN)));
+        assert_eq!(json!(7), value_to_json(&Value::Integer(7)));
+        assert_eq!(json!(true), value_to_json(&Value::Bool(true)));
+        assert_eq!(json!("abc"), value_to_json(&Value::String("abc".into())));
+        assert_eq!(json!(null), value_to_json(&Value::Null));
+        assert_eq!(json!(null), value_to_json(&Value::Undefined));
+        assert_eq!(json!(null), value_to_json(&Value::Unsupported));
+        assert_eq!(
+            json!("<a/>"),
+            value_to_json(&Value::XML("<a/>".into(), false))
+        );
+        assert_eq!(
+            json!(3),
+            value_to_json(&Value::AMF3(Rc::new(Value::Integer(3))))
+        );
+    }
+
+    #[test]
+    fn test_date() {
+        assert_eq!(
+            json!("2014-09-02T12:27:07.254Z"),
+            value_to_json(&Value::Date(1409660827254.0, None))
+        );
+        assert_eq!(
+            json!("1969-12-31T23:59:59.999Z"),
+            value_to_json(&Value::Date(-1.0, Some(0)))
+        );
+    }
+
+    #[test]
+    fn test_byte_array() {
+        assert_eq!(json!(""), value_to_json(&Value::ByteArray(vec![])));
+        assert_eq!(json!("AQ=="), value_to_json(&Value::ByteArray(vec![1])));
+        assert_eq!(json!("AQI="), value_to_json(&Value::ByteArray(vec![1, 2])));
+        assert_eq!(
+            json!("AQID/w=="),
+            value_to_json(&Value::ByteArray(vec![1, 2, 3, 255]))
+        );
+    }
+
+    #[test]
+    fn test_arrays() {
+        let items = vec![Rc::new(Value::Integer(1)), Rc::new(Value::Bool(false))];
+        assert_eq!(
+            json!([1, false]),
+            value_to_json(&Value::StrictArray(items.clone()))
+        );
+        assert_eq!(
+            json!([1, false]),
+            value_to_json(&Value::VectorObject(items.clone(), "*".into(), false))
+        );
+        assert_eq!(
+            json!([1, false]),
+            value_to_json(&Value::ECMAArray(items.clone(), vec![], 0))
+        );
+        assert_eq!(
+            json!({"0": 1, "1": false, "a": "b"}),
+            value_to_json(&Value::ECMAArray(
+                items,
+                vec![Element::new("a", Value::String("b".into()))],
+                1
+            ))
+        );
+        assert_eq!(
+            json!([-1, 2]),
+            value_to_json(&Value::VectorInt(vec![-1, 2], true))
+        );
+        assert_eq!(
+            json!([1, 2]),
+            value_to_json(&Value::VectorUInt(vec![1, 2], false))
+        );
+        assert_eq!(
+            json!([0.5, null]),
+            value_to_json(&Value::VectorDouble(vec![0.5, f64::INFINITY], false))
+        );
+    }
+
+    #[test]
+    fn test_objects() {
+        let elements = vec![
+            Element::new("hp", Value::Integer(100)),
+            Element::new("name", Value::String("x".into())),
+        ];
+        assert_eq!(
+            json!({"hp": 100, "name": "x"}),
+            value_to_json(&Value::Object(
+                elements.clone(),
+                Some(ClassDefinition::default_with_name("Player".into()))
+            ))
+        );
+        assert_eq!(
+            json!({"source": [], "hp": 100, "name": "x"}),
+            value_to_json(&Value::Custom(
+                vec![Element::new("source", Value::StrictArray(vec![]))],
+                elements,
+                None
+            ))
+        );
+    }
+
+    #[test]
+    fn test_dictionary() {
+        let dict = Value::Dictionary(
+            vec![
+                (
+                    Rc::new(Value::String("a".into())),
+                    Rc::new(Value::Integer(1)),
+                ),
+                (Rc::new(Value::Object(vec![], None)), Rc::new(Value::Null)),
+            ],
+            false,
+        );
+        assert_eq!(
+            json!([{"key": "a", "value": 1}, {"key": {}, "value": null}]),
+            value_to_json(&dict)
+        );
+    }
+
+    #[test]
+    fn test_shared_references_are_inlined() {
+        let shared = Rc::new(Value::Object(
+            vec![Element::new("x", Value::Number(1.0))],
+            None,
+        ));
+        let lso = Lso::new(
+            vec![
+                Element {
+                    name: "a".into(),
+                    value: Rc::clone(&shared),
+                },
+                Element {
+                    name: "b".into(),
+                    value: shared,
+                },
+            ],
+            "test",
+            AMFVersion::AMF3,
+        );
+        assert_eq!(json!({"a": {"x": 1.0}, "b": {"x": 1.0}}), to_json(&lso));
+    }
+}
@@ -1,5 +1,9 @@
 //! Library for reading and writing the Adobe Flash Local Shared Object (LSO) file format and the contained AMF0/AMF3 data
+//!
+//! The `std` feature is enabled by default, without it the crate is `no_std` and only needs `alloc`, reading is still
+//! available but writing and the `serde`, `xml`, `compression` and `indexmap` features require `std`
 
+#![cfg_attr(not(feature = "std"), no_std)]
 #![warn(
     anonymous_parameters,
     nonstandard_style,
@@ -13,30 +17,57 @@
     missing_docs
 )]
 
+#[cfg(feature = "std")]
 const HEADER_VERSION: [u8; 2] = [0x00, 0xbf];
+#[cfg(feature = "std")]
 const HEADER_SIGNATURE: [u8; 10] = [0x54, 0x43, 0x53, 0x4f, 0x00, 0x04, 0x00, 0x00, 0x00, 0x00];
 const PADDING: [u8; 1] = [0x00];
 
+#[cfg(feature = "std")]
 const FORMAT_VERSION_AMF0: u8 = 0x0;
+#[cfg(feature = "std")]
 const FORMAT_VERSION_AMF3: u8 = 0x3;
 
+extern crate alloc;
+
 #[cfg(feature = "serde")]
 #[macro_use]
 extern crate serde;
 
+/// The map used for internal lookup tables, such as the values already converted by `to_amf3`
+/// `HashMap` is only available with std, so `BTreeMap` is used without it
+/// As features must be additive this is never used in public signatures, which use `BTreeMap` in every build
+#[cfg(feature = "std")]
+pub(crate) type Map<K, V> = std::collections::HashMap<K, V>;
+#[cfg(not(feature = "std"))]
+pub(crate) type Map<K, V> = alloc::collections::BTreeMap<K, V>;
+
+/// The set counterpart of `Map`
+#[cfg(feature = "std")]
+pub(crate) type Set<T> = std::collections::HashSet<T>;
+#[cfg(not(feature = "std"))]
+pub(crate) type Set<T> = alloc::collections::BTreeSet<T>;
+
 /// Reading and Writing of the AMF0 file format
 pub mod amf0;
 /// Reading and Writing of the AMF3 file format
 pub mod amf3;
 
+/// Structural comparison of Lso contents
+pub mod diff;
 /// Decoding error type
 pub mod errors;
+mod hash;
+/// Lossy export of Lso contents to JSON
+#[cfg(feature = "serde")]
+pub mod json;
 mod nom_utils;
 /// Reading of the Lso container format
 pub mod read;
 /// Types used for representing Lso contents
 pub mod types;
-/// Writing of the Lso container format
+/// Writing of the Lso container format, only available with the `std` feature
+#[cfg(feature = "std")]
 pub mod write;
 
 /// Extra functionality such as decoders for popular external class formats
@@ -1,16 +1,46 @@
 use crate::errors::Error;
-use cookie_factory::bytes::be_u16;
-use cookie_factory::combinator::string;
-use cookie_factory::sequence::tuple;
-use cookie_factory::SerializeFn;
+#[cfg(feature = "std")]
+use cookie_factory::{bytes::be_u16, combinator::string, sequence::tuple, SerializeFn};
 use nom::bytes::complete::take;
-use nom::combinator::map_res;
+use nom::combinator::{all_consuming, map_res};
 
 use nom::IResult;
+#[cfg(feature = "std")]
 use std::io::Write;
 
 pub(crate) type AMFResult<'a, T> = IResult<&'a [u8], T, Error<'a>>;
 
+/// Run `parser` over the whole of `input`, on failure also giving the offset in `input` where parsing failed
+/// Errors that don't refer to a position in the input are given offset 0
+pub(crate) fn parse_with_offsets<'a, T>(
+    input: &'a [u8],
+    parser: impl FnMut(&'a [u8]) -> AMFResult<'a, T>,
+) -> Result<T, (Error<'a>, usize)> {
+    match all_consuming(parser)(input) {
+        Ok((_, value)) => Ok(value),
+        Err(nom::Err::Error(e)) | Err(nom::Err::Failure(e)) => {
+            let offset = match e {
+                Error::Nom(remaining, _)
+                | Error::BadReference {
+                    input: remaining, ..
+                } => offset_in(input, remaining),
+                _ => 0,
+            };
+            Err((e, offset))
+        }
+        Err(nom::Err::Incomplete(_)) => Err((Error::OutOfBounds, input.len())),
+    }
+}
+
+/// Get the offset of `remaining` in `input`, `remaining` should be a sub-slice of `input`
+fn offset_in(input: &[u8], remaining: &[u8]) -> usize {
+    (remaining.as_ptr() as usize)
+        .checked_sub(input.as_ptr() as usize)
+        .filter(|offset| *offset <= input.len())
+        .unwrap_or(0)
+}
+
+#[cfg(feature = "std")]
 pub(crate) fn either<Fa, Fb, W: Write>(b: bool, t: Fa, f: Fb) -> impl SerializeFn<W>
 where
     Fa: SerializeFn<W>,
@@ -25,10 +55,11 @@ where
     }
 }
 
+#[cfg(feature = "std")]
 pub(crate) fn write_string<'a, 'b: 'a, W: Write + 'a>(s: &'b str) -> impl SerializeFn<W> + 'a {
     tuple((be_u16(s.len() as u16), string(s)))
 }
 
 pub(crate) fn take_str(i: &[u8], length: u16) -> AMFResult<'_, &str> {
-    map_res(take(length), std::str::from_utf8)(i)
+    map_res(take(length), core::str::from_utf8)(i)
 }
@@ -1,18 +1,27 @@
-use std::convert::TryInto;
+use alloc::string::ToString;
+use alloc::vec::Vec;
+use core::convert::{TryFrom, TryInto};
 
 use nom::branch::alt;
-use nom::bytes::complete::tag;
+use nom::bytes::complete::{tag, take};
 use nom::number::complete::be_u32;
 
 use crate::amf0;
+use crate::amf0::read::AMF0Decoder;
+use crate::amf3;
 use crate::amf3::read::AMF3Decoder;
-use crate::nom_utils::AMFResult;
+use crate::errors::Error;
+use crate::nom_utils::{self, AMFResult};
 use crate::types::{AMFVersion, Header, Lso};
 use nom::combinator::all_consuming;
+use nom::Err;
 
 const HEADER_VERSION: [u8; 2] = [0x00, 0xbf];
-const HEADER_SIGNATURE: [u8; 10] = [0x54, 0x43, 0x53, 0x4f, 0x00, 0x04, 0x00, 0x00, 0x00, 0x00];
-const PADDING: [u8; 1] = [0x00];
+const HEADER_SIGNATURE: [u8; 4] = [0x54, 0x43, 0x53, 0x4f];
+/// Follows the signature, documented as padding so only checked by a strict `Reader`
+const HEADER_RESERVED: [u8; 6] = [0x00, 0x04, 0x00, 0x00, 0x00, 0x00];
+/// Follows the name, only checked by a strict `Reader`
+const NAME_PADDING: [u8; 3] = [0x00, 0x00, 0x00];
 
 const FORMAT_VERSION_AMF0: u8 = 0x0;
 const FORMAT_VERSION_AMF3: u8 = 0x3;
@@ -34,19 +43,41 @@ const FORMAT_VERSION_AMF3: u8 = 0x3;
 pub struct Reader {
     /// Handles reading Value::AMF3() wrapped types
     pub amf3_decoder: AMF3Decoder,
+    /// If true, the length given in the header must match the number of bytes following it, and the padding in the
+    /// header must be zeroes as written by Flash
+    /// Defaults to false, as some tools write inaccurate lengths
+    pub strict: bool,
 }
 
 impl Reader {
+    /// Skip header padding, which must match `expected` if this reader is strict
+    fn parse_padding<'a>(&self, i: &'a [u8], expected: &'static [u8]) -> AMFResult<'a, ()> {
+        let (i, _) = if self.strict {
+            tag(expected)(i)?
+        } else {
+            take(expected.len())(i)?
+        };
+        Ok((i, ()))
+    }
+
     fn parse_header<'a>(&self, i: &'a [u8]) -> AMFResult<'a, Header> {
-        let (i, _) = tag(HEADER_VERSION)(i)?;
+        if i.is_empty() {
+            return Err(Err::Error(Error::EmptyInput));
+        }
+        let (i, magic) = take(HEADER_VERSION.len())(i)?;
+        if magic != HEADER_VERSION {
+            return Err(Err::Error(Error::BadMagic));
+        }
         let (i, l) = be_u32(i)?;
-        let (i, _) = tag(HEADER_SIGNATURE)(i)?;
+        let (i, signature) = take(HEADER_SIGNATURE.len())(i)?;
+        if signature != HEADER_SIGNATURE {
+            return Err(Err::Error(Error::BadSignature));
+        }
+        let (i, _) = self.parse_padding(i, &HEADER_RESERVED)?;
 
         let (i, name) = amf0::read::parse_string(i)?;
 
-        let (i, _) = tag(PADDING)(i)?;
-        let (i, _) = tag(PADDING)(i)?;
-        let (i, _) = tag(PADDING)(i)?;
+        let (i, _) = self.parse_padding(i, &NAME_PADDING)?;
 
         let (i, version) = alt((tag(&[FORMAT_VERSION_AMF0]), tag(&[FORMAT_VERSION_AMF3])))(i)?;
 
@@ -64,14 +95,27 @@ impl Reader {
     }
 
     fn parse_inner<'a>(&mut self, i: &'a [u8]) -> AMFResult<'a, Lso> {
+        // The header length covers every byte following the length field
+        let actual_length = i.len().saturating_sub(HEADER_VERSION.len() + 4);
         let (i, header) = self.parse_header(i)?;
+
+        if self.strict && header.length as usize != actual_length {
+            return Err(Err::Error(Error::LengthMismatch {
+                declared: header.length,
+                actual: actual_length,
+            }));
+        }
+
         match header.format_version {
             AMFVersion::AMF0 => {
-                let (i, body) = amf0::read::parse_body(i)?;
+                let (i, body) =
+                    AMF0Decoder::with_amf3_decoder(&mut self.amf3_decoder).parse_body(i)?;
                 Ok((i, Lso { header, body }))
             }
 
             AMFVersion::AMF3 => {
+                // References are local to a single file
+                self.amf3_decoder.reset();
                 let (i, body) = self.amf3_decoder.parse_body(i)?;
                 Ok((i, Lso { header, body }))
             }
@@ -79,7 +123,202 @@ impl Reader {
     }
 
     /// Read a given buffer as an Lso
+    /// An empty buffer fails with `Error::EmptyInput`, a file with only a header is read as an Lso with an empty body
     pub fn parse<'a>(&mut self, i: &'a [u8]) -> AMFResult<'a, Lso> {
         all_consuming(|i| self.parse_inner(i))(i)
     }
+
+    /// Read a given buffer as an Lso, on failure also giving the offset in the buffer where parsing failed
+    pub fn parse_with_offsets<'a>(&mut self, i: &'a [u8]) -> Result<Lso, (Error<'a>, usize)> {
+        nom_utils::parse_with_offsets(i, |i| self.parse_inner(i))
+    }
+
+    /// Read a buffer as an Lso with the decoders of this reader, decompressing it first if the whole file is zlib or
+    /// gzip compressed, see `read::parse_maybe_compressed`
+    #[cfg(feature = "compression")]
+    pub fn parse_maybe_compressed<'a>(
+        &mut self,
+        i: &'a [u8],
+    ) -> Result<(Lso, Option<Compression>), (Error<'a>, usize)> {
+        for compression in [Compression::Zlib, Compression::Gzip] {
+            if !compression.matches(i) {
+                continue;
+            }
+
+            if let Some(data) = compression.decompress(i) {
+                return self
+                    .parse_with_offsets(&data)
+                    .map(|lso| (lso, Some(compression)))
+                    .map_err(|(e, offset)| (e.without_input(), offset));
+            }
+        }
+
+        self.parse_with_offsets(i).map(|lso| (lso, None))
+    }
+}
+
+/// Read only the header of an lso, giving the header and the offset in `i` where the body begins
+/// The body is not read or checked, so this is much cheaper than `Reader::parse` when only the name or AMF version is needed
+/// Header padding is not checked and the declared length is not compared against the data, as with a non-strict `Reader`
+pub fn parse_header(i: &[u8]) -> Result<(Header, usize), Error<'_>> {
+    match Reader::default().parse_header(i) {
+        Ok((rest, header)) => Ok((header, i.len() - rest.len())),
+        Err(Err::Error(e)) | Err(Err::Failure(e)) => Err(e),
+        Err(Err::Incomplete(_)) => Err(Error::OutOfBounds),
+    }
+}
+
+/// Read a buffer holding several Lso files one after another, as written by some backup tools
+/// The length in the header of each file is used to find where the next one starts, so it must be accurate and each
+/// file must be read completely within it. Files are read in order until the end of the buffer, an empty buffer gives
+/// no files. If a header declares more bytes than are left, this fails with `Error::LengthMismatch`
+pub fn parse_all(i: &[u8]) -> Result<Vec<Lso>, Error<'_>> {
+    let mut lsos = Vec::new();
+    let mut i = i;
+    while !i.is_empty() {
+        let (header, _) = parse_header(i)?;
+        // The header length covers every byte following the length field
+        let actual = i.len() - (HEADER_VERSION.len() + 4);
+        let length = header.length as usize;
+        if length > actual {
+            return Err(Error::LengthMismatch {
+                declared: header.length,
+                actual,
+            });
+        }
+
+        let (record, rest) = i.split_at(HEADER_VERSION.len() + 4 + length);
+        match Reader::default().parse(record) {
+            Ok((_, lso)) => lsos.push(lso),
+            Err(Err::Error(e)) | Err(Err::Failure(e)) => return Err(e),
+            Err(Err::Incomplete(_)) => return Err(Error::OutOfBounds),
+        }
+        i = rest;
+    }
+    Ok(lsos)
+}
+
+/// Detect the AMF version of a buffer without reading all of it, giving None if it doesn't look like either version
+/// For a full lso this is the format version in the header, otherwise the buffer is treated as a body without a header
+/// and its first element is inspected: a body of either version starts with a non-empty name followed by a type marker,
+/// but the name is written differently, so usually only one of them can match
+/// None is also given if the first element is valid in both versions, or if the buffer is empty
+/// Only the first element is checked, so a body detected as one version may still fail to parse
+pub fn detect_version(i: &[u8]) -> Option<AMFVersion> {
+    use amf0::type_marker::TypeMarker as AMF0Marker;
+    use amf3::type_marker::TypeMarker as AMF3Marker;
+
+    if let Ok((header, _)) = parse_header(i) {
+        return Some(header.format_version);
+    }
+
+    let amf0 = match amf0::read::parse_string(i) {
+        Ok((j, name)) => {
+            !name.is_empty() && j.first().is_some_and(|&m| AMF0Marker::try_from(m).is_ok())
+        }
+        Err(_) => false,
+    };
+    // A string reference can't be the first name of a body, as the string table is empty
+    let amf3 = match AMF3Decoder::default().parse_string(i) {
+        Ok((j, name)) => {
+            !name.is_empty() && j.first().is_some_and(|&m| AMF3Marker::try_from(m).is_ok())
+        }
+        Err(_) => false,
+    };
+
+    match (amf0, amf3) {
+        (true, false) => Some(AMFVersion::AMF0),
+        (false, true) => Some(AMFVersion::AMF3),
+        _ => None,
+    }
+}
+
+/// The compression used for a whole lso file, see `parse_maybe_compressed`
+#[cfg(feature = "compression")]
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Compression {
+    /// A zlib stream (RFC 1950)
+    Zlib,
+    /// A gzip file (RFC 1952)
+    Gzip,
+}
+
+#[cfg(feature = "compression")]
+impl Compression {
+    /// Check if the given data starts with the header for this compression
+    fn matches(self, i: &[u8]) -> bool {
+        match self {
+            // Deflate with a valid header checksum
+            Compression::Zlib => {
+                i.len() >= 2
+                    && i[0] & 0x0f == 8
+                    && (u16::from(i[0]) << 8 | u16::from(i[1])) % 31 == 0
+            }
+            // Magic followed by deflate
+            Compression::Gzip => i.starts_with(&[0x1f, 0x8b, 0x08]),
+        }
+    }
+
+    /// Decompress the given data, giving None if it is not valid
+    fn decompress(self, i: &[u8]) -> Option<Vec<u8>> {
+        use std::io::Read;
+
+        let mut out = Vec::new();
+        let result = match self {
+            Compression::Zlib => flate2::read::ZlibDecoder::new(i).read_to_end(&mut out),
+            Compression::Gzip => flate2::read::GzDecoder::new(i).read_to_end(&mut out),
+        };
+        result.ok().map(|_| out)
+    }
+}
+
+/// Read each of `inputs` as an Lso, giving a result for every input in order
+/// Each input is read by a fresh `Reader`, so a malformed input doesn't affect the ones after it
+/// On failure the offset where parsing failed is given as in `Reader::parse_with_offsets`, the input in `Error::Nom` or
+/// `Error::BadReference` will be empty as the data is dropped once it has been read
+pub fn parse_many<I: IntoIterator<Item = Vec<u8>>>(
+    inputs: I,
+) -> Vec<Result<Lso, (Error<'static>, usize)>> {
+    inputs
+        .into_iter()
+        .map(|data| {
+            Reader::default()
+                .parse_with_offsets(&data)
+                .map_err(|(e, offset)| (e.without_input(), offset))
+        })
+        .collect()
+}
+
+/// Read a base64 encoded Lso, as passed in the query strings of some web pages
+/// Both the standard and URL safe alphabets are accepted, with or without padding, surrounding whitespace is ignored
+/// On failure the input in `Error::Nom` or `Error::BadReference` will be empty as the decoded data is dropped
+#[cfg(feature = "base64")]
+pub fn parse_base64(s: &str) -> Result<Lso, Error<'static>> {
+    use base64::alphabet;
+    use base64::engine::{DecodePaddingMode, GeneralPurpose, GeneralPurposeConfig};
+    use base64::Engine;
+
+    let config =
+        GeneralPurposeConfig::new().with_decode_padding_mode(DecodePaddingMode::Indifferent);
+    let data = [alphabet::STANDARD, alphabet::URL_SAFE]
+        .iter()
+        .find_map(|alphabet| GeneralPurpose::new(alphabet, config).decode(s.trim()).ok())
+        .ok_or(Error::InvalidBase64)?;
+
+    Reader::default()
+        .parse_with_offsets(&data)
+        .map_err(|(e, _)| e.without_input())
+}
+
+/// Read a buffer as an Lso, decompressing it first if the whole file is zlib or gzip compressed
+/// Data is only treated as compressed if it starts with a compression header and also decompresses successfully,
+/// otherwise it is parsed as is. An uncompressed lso always starts with `0x00 0xbf` so can't be mistaken for either
+///
+/// On success the compression that was removed is also given, so that the file can be compressed again after writing
+/// On failure the offset where parsing failed is given as in `Reader::parse_with_offsets`, if the data was
+/// decompressed this is an offset in the decompressed data and the input in `Error::Nom` or `Error::BadReference` will be empty
+#[cfg(feature = "compression")]
+/// To read values that need registered decoders, such as flex types, use `Reader::parse_maybe_compressed`
+pub fn parse_maybe_compressed(i: &[u8]) -> Result<(Lso, Option<Compression>), (Error<'_>, usize)> {
+    Reader::default().parse_maybe_compressed(i)
 }
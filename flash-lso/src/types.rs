@@ -1,11 +1,22 @@
-use cookie_factory::lib::std::fmt::Formatter;
+use crate::hash::ContentHasher;
+use crate::{Map, Set};
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::rc::Rc;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
 use core::fmt;
+use core::fmt::Formatter;
+use core::hash::{Hash, Hasher};
+use core::iter::FromIterator;
+use core::mem::{size_of, size_of_val};
+use core::ops::{Deref, Index};
 use derive_try_from_primitive::TryFromPrimitive;
 use enumset::EnumSet;
 use enumset::EnumSetType;
-use nom::lib::std::iter::FromIterator;
-use std::ops::Deref;
-use std::rc::Rc;
+#[cfg(feature = "indexmap")]
+use indexmap::IndexMap;
 
 /// A container for lso files
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
@@ -32,17 +43,357 @@ impl Lso {
             body,
         }
     }
+
+    /// Visit every value in this lso along with its path, allowing it to be modified
+    /// Paths are element names and array indices joined with `.`, e.g. `player.items.0`, the keys and
+    /// values of dictionaries are visited as `<index>.key` and `<index>.value`
+    /// Values are visited before their contents, so changes made to a container are seen when its contents are visited
+    ///
+    /// As values are stored behind `Rc`, any value that is shared with another is cloned before it is visited (see `Rc::make_mut`),
+    /// so shared values (such as those read by reference) will no longer be shared afterwards, even if they are not changed
+    pub fn walk_mut<F: FnMut(&str, &mut Value)>(&mut self, mut f: F) {
+        for e in self.body.iter_mut() {
+            walk_value_mut(&e.name, &mut e.value, &mut f);
+        }
+    }
+
+    /// Get every `ByteArray` in this lso along with its path, in the same form as the paths given by `walk_mut`, such
+    /// as to extract images embedded in a save
+    /// Byte arrays are given in the order `walk_mut` visits them, a byte array shared by reference is given at each
+    /// of its paths. Some hold a serialized AMF3 value, which can be checked with `amf3::read::is_amf3_value`
+    pub fn byte_arrays(&self) -> Vec<(String, &[u8])> {
+        let mut byte_arrays = Vec::new();
+        for e in self.body.iter() {
+            walk_value(&e.name, &e.value, &mut |path, value| {
+                if let Value::ByteArray(bytes) = value {
+                    byte_arrays.push((path.to_string(), bytes.as_slice()));
+                }
+            });
+        }
+        byte_arrays
+    }
+
+    /// Get a fingerprint of the body of this lso, for checking if a save has been changed
+    /// The header is not included, so renaming a file or changing its version does not change the hash
+    ///
+    /// The hash is computed over a canonical form of the body, so it is the same on every platform and release:
+    /// - `Integer` and `Number` values with the same numeric value hash the same, as do `0.0` and `-0.0`
+    /// - `AMF3` wrapped values hash the same as their contents
+    /// - `ByteArray` contents are hashed as their length followed by the raw bytes
+    /// - `Date` values are hashed as their time (normalized like a `Number`) followed by their timezone if present,
+    ///   so an AMF0 date (which always has a timezone) differs from the same AMF3 date
+    /// - Objects are hashed by class name and properties, the rest of a class definition is ignored
+    /// - Nested values are hashed into their own digests, which are fed into the hash of the value holding them
+    ///
+    /// A value shared by reference is only hashed once and its digest reused wherever it appears, so the work is
+    /// linear in the number of distinct values, and a shared value hashes the same as an equal copy in each place
+    ///
+    /// If `sort_properties` is set, the root elements and the properties of all object-like values are hashed in
+    /// name order, so reordering them does not change the hash, otherwise order is significant
+    /// This is a 64 bit non-cryptographic hash (FNV-1a), so it can detect accidental changes but not deliberate tampering
+    pub fn content_hash(&self, sort_properties: bool) -> u64 {
+        let mut hasher = ContentHasher::new(sort_properties);
+        hasher.write_elements(&self.body);
+        hasher.finish()
+    }
+
+    /// Merge the root elements of `other` into this lso, the header of this lso is kept
+    /// Elements whose names are only in `other` are added after the existing elements, in the order they appear in `other`
+    /// Elements with a name in both are combined according to `strategy`, if a name appears more than once in this lso
+    /// only the first element with that name is combined
+    ///
+    /// Arrays, vectors and dictionaries are never combined, with `DeepMergeObjects` the array from `other` replaces
+    /// the existing one rather than being concatenated to it
+    pub fn merge(&mut self, other: &Lso, strategy: MergeStrategy) {
+        merge_elements(&mut self.body, &other.body, strategy);
+    }
+
+    /// Get the name of every class used by an object, custom value or typed vector in this lso
+    /// Each name is given once, in the order it first appears, the empty name of anonymous objects is not included
+    pub fn class_names(&self) -> Vec<String> {
+        let mut names = Vec::new();
+        for e in self.body.iter() {
+            collect_class_names(&e.value, &mut names);
+        }
+        names
+    }
+
+    /// Rename classes by replacing each class name that is a key in `names` with its value
+    /// Every object and custom value holds its own copy of its class definition, so all of them are renamed,
+    /// as is the type name of each `VectorObject`
+    /// Values are visited with `walk_mut`, so values that were shared will no longer be shared
+    pub fn rename_classes(&mut self, names: &BTreeMap<String, String>) {
+        self.walk_mut(|_, value| match value {
+            Value::Object(_, Some(class_def)) | Value::Custom(_, _, Some(class_def)) => {
+                if let Some(name) = names.get(&class_def.name) {
+                    class_def.name = name.clone();
+                }
+            }
+            Value::VectorObject(_, type_name, _) => {
+                if let Some(name) = names.get(type_name) {
+                    *type_name = name.clone();
+                }
+            }
+            _ => {}
+        });
+    }
+
+    /// Replace every `AMF3` wrapper in this lso with the value inside it
+    /// This loses where the AMF0 body switched to AMF3, so it can't be written back as it was read: an AMF0 writer
+    /// can't encode the AMF3 only values (such as integers, vectors and byte arrays) that were inside the wrappers,
+    /// and writes them as unsupported values
+    /// Values are visited with `walk_mut`, so values that were shared will no longer be shared
+    pub fn flatten_amf3(&mut self) {
+        self.walk_mut(|_, value| {
+            while let Value::AMF3(inner) = value {
+                // Drop the wrapper first, so the inner value is moved out rather than cloned if it isn't shared
+                let inner = Rc::clone(inner);
+                *value = Value::Null;
+                *value = Rc::try_unwrap(inner).unwrap_or_else(|inner| inner.as_ref().clone());
+            }
+        });
+    }
+
+    /// Rewrite this lso into a canonical form, so that lsos with the same contents are written as the same bytes, such as
+    /// to store saves by their hash (see also `content_hash`)
+    /// - the root elements and the properties of objects, the associative part of ECMA arrays and the regular elements
+    ///   of custom values are sorted by name, elements with the same name keep their order
+    /// - the static properties of class definitions are sorted by name, so that they are written in the same order
+    ///   as the sorted properties
+    /// - the declared length of each ECMA array is set to its number of associative elements, as in `Value::ecma_array`
+    /// - dates lose their timezone, as with `normalize_dates`
+    /// - in AMF3 lsos, whole `Number`s in the range of an AMF3 integer become `Integer`, as with `Value::to_amf3`,
+    ///   AMF0 has no integers so AMF0 lsos keep their numbers
+    ///
+    /// Arrays, vectors, dictionaries and the custom elements of custom values (which are given to an external
+    /// encoder) keep their order. Every change above is lossy, the original order, lengths, timezones and number
+    /// types can't be recovered, so this is for comparing and hashing saves rather than for editing them
+    /// Values are visited with `walk_mut`, so values that were shared will no longer be shared
+    pub fn canonicalize(&mut self) {
+        let integers = self.header.format_version == AMFVersion::AMF3;
+        sort_elements(&mut self.body);
+        self.walk_mut(|_, value| {
+            if let Value::Number(n) = *value {
+                if integers && is_amf3_integer(n) {
+                    *value = Value::Integer(n as i32);
+                }
+                return;
+            }
+
+            match value {
+                Value::Object(elements, class_def) | Value::Custom(_, elements, class_def) => {
+                    sort_elements(elements);
+                    if let Some(class_def) = class_def {
+                        class_def.static_properties.sort();
+                    }
+                }
+                Value::ECMAArray(_, assoc, len) => {
+                    sort_elements(assoc);
+                    *len = assoc.len() as u32;
+                }
+                Value::Date(_, tz) => *tz = None,
+                _ => {}
+            }
+        });
+    }
+
+    /// Remove the timezone of every `Date` in this lso, so that dates read from AMF0 compare equal to the same
+    /// dates read from AMF3, see `Value::same_instant`
+    /// The time of a date is always UTC, so only the timezone is lost. AMF3 dates have no timezone so are written
+    /// unchanged, but AMF0 dates are then written with a timezone of 0 rather than the one they were read with
+    /// Values are visited with `walk_mut`, so values that were shared will no longer be shared
+    pub fn normalize_dates(&mut self) {
+        self.walk_mut(|_, value| {
+            if let Value::Date(_, tz) = value {
+                *tz = None;
+            }
+        });
+    }
+}
+
+fn collect_class_names(value: &Value, names: &mut Vec<String>) {
+    let mut add = |name: &str| {
+        if !name.is_empty() && !names.iter().any(|n| n == name) {
+            names.push(name.to_string());
+        }
+    };
+    match value {
+        Value::Object(_, Some(class_def)) | Value::Custom(_, _, Some(class_def)) => {
+            add(&class_def.name)
+        }
+        Value::VectorObject(_, type_name, _) => add(type_name),
+        _ => {}
+    }
+
+    match value {
+        Value::Object(elements, _) => {
+            for e in elements {
+                collect_class_names(&e.value, names);
+            }
+        }
+        Value::ECMAArray(dense, assoc, _) => {
+            for v in dense {
+                collect_class_names(v, names);
+            }
+            for e in assoc {
+                collect_class_names(&e.value, names);
+            }
+        }
+        Value::StrictArray(items) | Value::VectorObject(items, _, _) => {
+            for v in items {
+                collect_class_names(v, names);
+            }
+        }
+        Value::Custom(custom_elements, elements, _) => {
+            for e in custom_elements.iter().chain(elements.iter()) {
+                collect_class_names(&e.value, names);
+            }
+        }
+        Value::Dictionary(pairs, _) => {
+            for (k, v) in pairs {
+                collect_class_names(k, names);
+                collect_class_names(v, names);
+            }
+        }
+        Value::AMF3(inner) => collect_class_names(inner, names),
+        _ => {}
+    }
+}
+
+/// How `Lso::merge` combines elements that have the same name in both lsos
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MergeStrategy {
+    /// Keep the existing element
+    KeepSelf,
+    /// Replace the existing element with the one being merged
+    KeepOther,
+    /// If both elements are objects, merge the properties of the other object into the existing one in the same way,
+    /// keeping the existing class definition, otherwise replace the existing element as with `KeepOther`
+    /// Properties added to a sealed AMF3 object must be added to its static properties to be written, and objects that
+    /// are shared are copied before they are changed, as with `Lso::walk_mut`
+    DeepMergeObjects,
+}
+
+fn merge_elements(elements: &mut Vec<Element>, other: &[Element], strategy: MergeStrategy) {
+    for e in other {
+        let existing = match elements.iter_mut().find(|existing| existing.name == e.name) {
+            Some(existing) => existing,
+            None => {
+                elements.push(e.clone());
+                continue;
+            }
+        };
+
+        match strategy {
+            MergeStrategy::KeepSelf => {}
+            MergeStrategy::KeepOther => existing.value = Rc::clone(&e.value),
+            MergeStrategy::DeepMergeObjects => {
+                if let (Value::Object(_, _), Value::Object(other_children, _)) =
+                    (existing.value.as_ref(), e.value.as_ref())
+                {
+                    if let Value::Object(children, _) = Rc::make_mut(&mut existing.value) {
+                        merge_elements(children, other_children, strategy);
+                    }
+                } else {
+                    existing.value = Rc::clone(&e.value);
+                }
+            }
+        }
+    }
+}
+
+/// Visit a value and its contents along with their paths, as with `walk_value_mut` but without modifying them
+fn walk_value<'a, F: FnMut(&str, &'a Value)>(path: &str, value: &'a Value, f: &mut F) {
+    f(path, value);
+
+    let walk_elements = |elements: &'a [Element], f: &mut F| {
+        for e in elements {
+            walk_value(&format!("{}.{}", path, e.name), &e.value, f);
+        }
+    };
+    let walk_items = |items: &'a [Rc<Value>], f: &mut F| {
+        for (index, item) in items.iter().enumerate() {
+            walk_value(&format!("{}.{}", path, index), item, f);
+        }
+    };
+
+    match value {
+        Value::Object(elements, _) => walk_elements(elements, f),
+        Value::ECMAArray(dense, assoc, _) => {
+            walk_items(dense, f);
+            walk_elements(assoc, f);
+        }
+        Value::StrictArray(items) | Value::VectorObject(items, _, _) => walk_items(items, f),
+        Value::Custom(custom_elements, elements, _) => {
+            walk_elements(custom_elements, f);
+            walk_elements(elements, f);
+        }
+        Value::Dictionary(pairs, _) => {
+            for (index, (k, v)) in pairs.iter().enumerate() {
+                walk_value(&format!("{}.{}.key", path, index), k, f);
+                walk_value(&format!("{}.{}.value", path, index), v, f);
+            }
+        }
+        Value::AMF3(inner) => walk_value(path, inner, f),
+        _ => {}
+    }
+}
+
+fn walk_value_mut<F: FnMut(&str, &mut Value)>(path: &str, value: &mut Rc<Value>, f: &mut F) {
+    let value = Rc::make_mut(value);
+    f(path, value);
+
+    let walk_elements = |elements: &mut [Element], f: &mut F| {
+        for e in elements.iter_mut() {
+            walk_value_mut(&format!("{}.{}", path, e.name), &mut e.value, f);
+        }
+    };
+    let walk_items = |items: &mut [Rc<Value>], f: &mut F| {
+        for (index, item) in items.iter_mut().enumerate() {
+            walk_value_mut(&format!("{}.{}", path, index), item, f);
+        }
+    };
+
+    match value {
+        Value::Object(elements, _) => walk_elements(elements, f),
+        Value::ECMAArray(dense, assoc, _) => {
+            walk_items(dense, f);
+            walk_elements(assoc, f);
+        }
+        Value::StrictArray(items) | Value::VectorObject(items, _, _) => walk_items(items, f),
+        Value::Custom(custom_elements, elements, _) => {
+            walk_elements(custom_elements, f);
+            walk_elements(elements, f);
+        }
+        Value::Dictionary(pairs, _) => {
+            for (index, (k, v)) in pairs.iter_mut().enumerate() {
+                walk_value_mut(&format!("{}.{}.key", path, index), k, f);
+                walk_value_mut(&format!("{}.{}.value", path, index), v, f);
+            }
+        }
+        Value::AMF3(inner) => walk_value_mut(path, inner, f),
+        _ => {}
+    }
 }
 
 impl IntoIterator for Lso {
     type Item = Element;
-    type IntoIter = std::vec::IntoIter<Self::Item>;
+    type IntoIter = vec::IntoIter<Self::Item>;
 
     fn into_iter(self) -> Self::IntoIter {
         self.body.into_iter()
     }
 }
 
+impl<'a> IntoIterator for &'a Lso {
+    type Item = &'a Element;
+    type IntoIter = core::slice::Iter<'a, Element>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.body.iter()
+    }
+}
+
 /// The version of AMF being used
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[derive(TryFromPrimitive, Eq, PartialEq, Debug, Copy, Clone)]
@@ -92,7 +443,8 @@ impl Header {
 #[derive(Clone, Debug, PartialEq)]
 pub struct Element {
     /// The name of the element
-    pub name: String,
+    /// Elements with the same name can share it, see `AMF3Decoder::intern_names`
+    pub name: Rc<str>,
     /// The value of the element
     pub value: Rc<Value>,
 }
@@ -100,7 +452,7 @@ pub struct Element {
 impl Element {
     /// Create a new Element
     #[inline]
-    pub fn new(name: impl Into<String>, value: impl Into<Value>) -> Self {
+    pub fn new(name: impl Into<Rc<str>>, value: impl Into<Value>) -> Self {
         Self {
             name: name.into(),
             value: Rc::new(value.into()),
@@ -114,16 +466,24 @@ impl Element {
 
     /// Get the name of this element
     pub fn name(&self) -> &str {
-        self.name.as_str()
+        &self.name
     }
 }
 
 //TODO: should amf3 assoc arrays be their own type with a dense and assoc section
 /// A single or compound value
+///
+/// With the `serde` feature, nested values are serialized through their `Rc`, so a value that is shared between
+/// several places (such as one read by reference) is serialized once for each place, and deserializes into separate copies
+/// that are no longer shared. Writing a deserialized lso may then write each copy inline rather than as a reference
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[derive(Debug, Clone, PartialEq)]
 pub enum Value {
     /// Represent the type number (amf0) and double (amf3)
+    ///
+    /// Numbers are read and written as their exact bit pattern, so NaN (including its sign and payload), infinities
+    /// and negative zero are written back as the same 8 bytes. As NaN is not equal to itself, compare values that may
+    /// contain NaN with `approx_eq` or by their bytes rather than with `==`
     Number(f64),
     /// Represents the type boolean (amf0) and both the true/false type (amf3)
     Bool(bool),
@@ -137,10 +497,13 @@ pub enum Value {
     Undefined,
     /// Represent ECMA-Arrays (amf0) and associative arrays (amf3, even if they contain a dense part)
     /// Final value represents the length of the array in amf0, this can differ from the actual number of elements
+    /// as the elements are read until the end marker, it is written back unchanged
     ECMAArray(Vec<Rc<Value>>, Vec<Element>, u32),
     /// Represent a strict array (amf0) or a dense array (amf3)
     StrictArray(Vec<Rc<Value>>),
     /// Represent a timezone in the format (seconds since epoch, timezone or UTC if missing (amf3) )
+    /// The timezone is read from and written to amf0 dates unchanged, missing is written as 0,
+    /// it is ignored when writing amf3 as amf3 dates have no timezone
     Date(f64, Option<u16>),
     /// Represent the unsupported type
     Unsupported,
@@ -150,6 +513,7 @@ pub enum Value {
     AMF3(Rc<Value>),
     // AMF3
     /// Represent the integer type (u29) (amf3)
+    /// Values outside of the 29 bit range are written as a `Number`, so are read back as one
     Integer(i32),
     /// Represent the bytearray type (amf3)
     ByteArray(Vec<u8>),
@@ -168,17 +532,1496 @@ pub enum Value {
     /// Represent the dictionary type (amf3)
     /// Format is ((key, value), has_weak_keys)
     Dictionary(Vec<(Rc<Value>, Rc<Value>)>, bool),
-    /// Represent a external object, such as from flex
+    /// Represent a external object, such as from flex, see `Value::externalizable` to create one for writing
     /// (custom_elements, regular elements, class def)
+    /// - custom_elements are the elements read by the external decoder registered for the class, and are given to the
+    ///   external encoder for the class when writing
+    /// - regular elements are always empty when read, if the class is not external they are written as the properties
+    ///   of an ordinary object
+    /// - class def is the trait the object was read with, its `Attribute::External` decides if the object is written
+    ///   through the external encoder, see `Value::is_externalized`
+    ///
+    /// Only one of the two lists of elements is written, see `Value::unwritten_elements`
     Custom(Vec<Element>, Vec<Element>, Option<ClassDefinition>),
 }
 
+/// Remove the elements that fail `f` and prune the rest, see `Value::retain`
+fn retain_elements<F: FnMut(&str, &Value) -> bool>(
+    elements: &mut Vec<Element>,
+    class_def: Option<&mut ClassDefinition>,
+    f: &mut F,
+) {
+    let mut removed = Vec::new();
+    elements.retain(|e| {
+        let keep = f(&e.name, &e.value);
+        if !keep {
+            removed.push(e.name.clone());
+        }
+        keep
+    });
+    if let Some(def) = class_def {
+        def.static_properties
+            .retain(|p| !removed.iter().any(|r| **r == **p));
+    }
+
+    for e in elements.iter_mut() {
+        retain_rc(&mut e.value, f);
+    }
+}
+
+/// Prune a nested value, only containers that may hold elements are made unique
+fn retain_rc<F: FnMut(&str, &Value) -> bool>(value: &mut Rc<Value>, f: &mut F) {
+    if let Value::Object(..)
+    | Value::Custom(..)
+    | Value::ECMAArray(..)
+    | Value::StrictArray(_)
+    | Value::VectorObject(..)
+    | Value::Dictionary(..)
+    | Value::AMF3(_) = value.as_ref()
+    {
+        Rc::make_mut(value).retain_inner(f);
+    }
+}
+
+/// Convert a value with `Value::to_amf3`, giving the same result for each use of a shared value
+fn amf3_rc(value: &Rc<Value>, converted: &mut Map<*const Value, Rc<Value>>) -> Rc<Value> {
+    let ptr = Rc::as_ptr(value);
+    if let Some(v) = converted.get(&ptr) {
+        return Rc::clone(v);
+    }
+    let v = Rc::new(value.amf3_value(converted));
+    converted.insert(ptr, Rc::clone(&v));
+    v
+}
+
+fn amf3_rcs(values: &[Rc<Value>], converted: &mut Map<*const Value, Rc<Value>>) -> Vec<Rc<Value>> {
+    values.iter().map(|v| amf3_rc(v, converted)).collect()
+}
+
+/// Sort elements by name, keeping the order of elements with the same name
+fn sort_elements(elements: &mut [Element]) {
+    elements.sort_by(|a, b| a.name.cmp(&b.name));
+}
+
+/// Check if a number can be written as an AMF3 integer without losing its value
+fn is_amf3_integer(n: f64) -> bool {
+    const MIN: f64 = -(1 << 28) as f64;
+    const MAX: f64 = ((1 << 28) - 1) as f64;
+    n.fract() == 0.0 && (MIN..=MAX).contains(&n) && !(n == 0.0 && n.is_sign_negative())
+}
+
+/// Get a class definition that will write every element of an object in AMF3
+/// Sealed objects only write their static properties, so AMF0 objects need all their elements listed
+fn amf3_class_def(elements: &[Element], class_def: Option<&ClassDefinition>) -> ClassDefinition {
+    match class_def {
+        None => ClassDefinition {
+            attributes: Attribute::Dynamic.into(),
+            ..ClassDefinition::default_with_name(String::new())
+        },
+        Some(def)
+            if def.attributes.is_empty()
+                && !elements.iter().all(|e| def.has_static_property(&e.name)) =>
+        {
+            ClassDefinition {
+                static_properties: elements.iter().map(|e| e.name.to_string()).collect(),
+                ..def.clone()
+            }
+        }
+        Some(def) => def.clone(),
+    }
+}
+
+/// Check if two numbers differ by at most `epsilon`, treating NaN as equal to NaN and infinities as equal only to themselves
+fn f64_approx_eq(a: f64, b: f64, epsilon: f64) -> bool {
+    (a.is_nan() && b.is_nan()) || a == b || (a - b).abs() <= epsilon
+}
+
+/// Check that `b` holds the same items as `a` in any order, each item of `a` is matched to a different item of `b`
+fn multiset_eq<T>(a: &[T], b: &[T], eq: impl Fn(&T, &T) -> bool) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    let mut matched = vec![false; b.len()];
+    a.iter().all(|a| {
+        match b
+            .iter()
+            .enumerate()
+            .position(|(index, b)| !matched[index] && eq(a, b))
+        {
+            Some(index) => {
+                matched[index] = true;
+                true
+            }
+            None => false,
+        }
+    })
+}
+
+/// Compare class definitions with their static properties in any order
+fn class_def_eq_unordered(a: &Option<ClassDefinition>, b: &Option<ClassDefinition>) -> bool {
+    match (a, b) {
+        (Some(a), Some(b)) => {
+            a.name == b.name
+                && a.attributes == b.attributes
+                && multiset_eq(&a.static_properties, &b.static_properties, |a, b| a == b)
+        }
+        (a, b) => a == b,
+    }
+}
+
+impl Value {
+    /// Create an `Object` from its elements
+    ///
+    /// ```
+    /// use flash_lso::types::{Element, Value};
+    /// let object = Value::object(vec![Element::new("name", "value")], None);
+    /// assert_eq!(object.len(), Some(1));
+    /// ```
+    pub fn object(
+        elements: impl IntoIterator<Item = Element>,
+        class_def: Option<ClassDefinition>,
+    ) -> Value {
+        Value::Object(elements.into_iter().collect(), class_def)
+    }
+
+    /// Create a `StrictArray` from its items
+    ///
+    /// ```
+    /// use flash_lso::types::Value;
+    /// let array = Value::strict_array(vec![1, 2, 3]);
+    /// assert_eq!(array.len(), Some(3));
+    /// ```
+    pub fn strict_array<V: Into<Value>>(items: impl IntoIterator<Item = V>) -> Value {
+        Value::StrictArray(items.into_iter().map(|v| Rc::new(v.into())).collect())
+    }
+
+    /// Create an `ECMAArray` from its dense and associative parts
+    /// The declared length is set to the number of associative elements, as it is when reading AMF3
+    ///
+    /// ```
+    /// use flash_lso::types::{Element, Value};
+    /// let array = Value::ecma_array(vec![1, 2], vec![Element::new("name", "value")]);
+    /// assert_eq!(array.len(), Some(3));
+    /// assert!(matches!(array, Value::ECMAArray(_, _, 1)));
+    /// ```
+    pub fn ecma_array<V: Into<Value>>(
+        dense: impl IntoIterator<Item = V>,
+        assoc: impl IntoIterator<Item = Element>,
+    ) -> Value {
+        let assoc: Vec<Element> = assoc.into_iter().collect();
+        let len = assoc.len() as u32;
+        Value::ECMAArray(
+            dense.into_iter().map(|v| Rc::new(v.into())).collect(),
+            assoc,
+            len,
+        )
+    }
+
+    /// Create a `VectorInt`, `fixed` is true if the vector has a fixed length
+    ///
+    /// ```
+    /// use flash_lso::types::Value;
+    /// assert_eq!(Value::vector_int(vec![1, 2], false), Value::VectorInt(vec![1, 2], false));
+    /// ```
+    pub fn vector_int(items: impl IntoIterator<Item = i32>, fixed: bool) -> Value {
+        Value::VectorInt(items.into_iter().collect(), fixed)
+    }
+
+    /// Create a `VectorUInt`, `fixed` is true if the vector has a fixed length
+    ///
+    /// ```
+    /// use flash_lso::types::Value;
+    /// assert_eq!(Value::vector_uint(vec![1, 2], true), Value::VectorUInt(vec![1, 2], true));
+    /// ```
+    pub fn vector_uint(items: impl IntoIterator<Item = u32>, fixed: bool) -> Value {
+        Value::VectorUInt(items.into_iter().collect(), fixed)
+    }
+
+    /// Create a `VectorDouble`, `fixed` is true if the vector has a fixed length
+    ///
+    /// ```
+    /// use flash_lso::types::Value;
+    /// assert_eq!(Value::vector_double(vec![0.5], false), Value::VectorDouble(vec![0.5], false));
+    /// ```
+    pub fn vector_double(items: impl IntoIterator<Item = f64>, fixed: bool) -> Value {
+        Value::VectorDouble(items.into_iter().collect(), fixed)
+    }
+
+    /// Create a `VectorObject` holding values of the class `type_name`, `fixed` is true if the vector has a fixed length
+    ///
+    /// ```
+    /// use flash_lso::types::Value;
+    /// let vector = Value::vector_object(vec!["a", "b"], "String", false);
+    /// assert_eq!(vector.len(), Some(2));
+    /// ```
+    pub fn vector_object<V: Into<Value>>(
+        items: impl IntoIterator<Item = V>,
+        type_name: impl Into<String>,
+        fixed: bool,
+    ) -> Value {
+        Value::VectorObject(
+            items.into_iter().map(|v| Rc::new(v.into())).collect(),
+            type_name.into(),
+            fixed,
+        )
+    }
+
+    /// Create a `Dictionary` from key / value pairs, `weak_keys` is true if the dictionary has weak keys
+    ///
+    /// ```
+    /// use flash_lso::types::Value;
+    /// let dictionary = Value::dictionary(vec![("key", 1)], false);
+    /// assert_eq!(dictionary.len(), Some(1));
+    /// ```
+    pub fn dictionary<K: Into<Value>, V: Into<Value>>(
+        pairs: impl IntoIterator<Item = (K, V)>,
+        weak_keys: bool,
+    ) -> Value {
+        Value::Dictionary(
+            pairs
+                .into_iter()
+                .map(|(k, v)| (Rc::new(k.into()), Rc::new(v.into())))
+                .collect(),
+            weak_keys,
+        )
+    }
+
+    /// Create a `Custom` value from the elements read by its external decoder and its regular elements
+    ///
+    /// ```
+    /// use flash_lso::types::{ClassDefinition, Element, Value};
+    /// let custom = Value::custom(
+    ///     vec![Element::new("source", "data")],
+    ///     Vec::new(),
+    ///     Some(ClassDefinition::default_with_name("flex.messaging.io.ArrayCollection".to_string())),
+    /// );
+    /// assert_eq!(custom.len(), Some(1));
+    /// ```
+    pub fn custom(
+        custom_elements: impl IntoIterator<Item = Element>,
+        elements: impl IntoIterator<Item = Element>,
+        class_def: Option<ClassDefinition>,
+    ) -> Value {
+        Value::Custom(
+            custom_elements.into_iter().collect(),
+            elements.into_iter().collect(),
+            class_def,
+        )
+    }
+
+    /// Create a `Custom` value of an externalizable class, that is written by the external encoder registered for
+    /// `class_name` (see `AMF3Encoder::external_encoders`), which is given `custom_elements`
+    /// The class definition has `Attribute::External` and no static properties, as externalizable classes are written
+    /// entirely by their encoder. `elements` are kept with the value but are not written, see `unwritten_elements`
+    ///
+    /// ```
+    /// use flash_lso::types::{Element, Value};
+    /// let collection = Value::externalizable(
+    ///     "flex.messaging.io.ArrayCollection",
+    ///     vec![Element::new("data", Value::strict_array(vec![1, 2]))],
+    ///     Vec::new(),
+    /// );
+    /// assert!(collection.is_externalized());
+    /// ```
+    pub fn externalizable(
+        class_name: impl Into<String>,
+        custom_elements: impl IntoIterator<Item = Element>,
+        elements: impl IntoIterator<Item = Element>,
+    ) -> Value {
+        Value::custom(
+            custom_elements,
+            elements,
+            Some(ClassDefinition {
+                attributes: Attribute::External.into(),
+                ..ClassDefinition::default_with_name(class_name.into())
+            }),
+        )
+    }
+
+    /// Get the elements of a `Custom` value that an AMF3 writer will leave out, which should be empty for a value
+    /// that is meant to be written: for an externalizable class only the custom elements are written, otherwise the
+    /// value is written as an ordinary object of its regular elements
+    /// Custom elements without `Attribute::External` usually mean the class definition was built by hand and is
+    /// missing the attribute. Other values are written entirely, so give nothing
+    pub fn unwritten_elements(&self) -> &[Element] {
+        match self {
+            Value::Custom(_, elements, _) if self.is_externalized() => elements,
+            Value::Custom(custom_elements, _, _) => custom_elements,
+            Value::AMF3(inner) => inner.unwritten_elements(),
+            _ => &[],
+        }
+    }
+
+    /// Get the number of items in a container value, or None if this is not a container
+    /// For `ECMAArray` this is the sum of the dense and associative parts, for `Custom` the sum of
+    /// the custom and regular elements, for `Dictionary` the number of key / value pairs
+    /// `AMF3` wrapped values give the length of their contents
+    pub fn len(&self) -> Option<usize> {
+        match self {
+            Value::Object(elements, _) => Some(elements.len()),
+            Value::StrictArray(items) | Value::VectorObject(items, _, _) => Some(items.len()),
+            Value::ECMAArray(dense, assoc, _) => Some(dense.len() + assoc.len()),
+            Value::VectorInt(items, _) => Some(items.len()),
+            Value::VectorUInt(items, _) => Some(items.len()),
+            Value::VectorDouble(items, _) => Some(items.len()),
+            Value::Dictionary(pairs, _) => Some(pairs.len()),
+            Value::Custom(custom_elements, elements, _) => {
+                Some(custom_elements.len() + elements.len())
+            }
+            Value::AMF3(inner) => inner.len(),
+            _ => None,
+        }
+    }
+
+    /// Check if a container value has no items, or None if this is not a container, see `len`
+    pub fn is_empty(&self) -> Option<bool> {
+        self.len().map(|len| len == 0)
+    }
+
+    /// Get the properties of an `Object` or the regular elements of a `Custom`, or None for other values
+    /// The custom elements of a `Custom` are written by its external decoder, so are not included here
+    /// `AMF3` wrapped values give the elements of their contents
+    pub fn elements(&self) -> Option<&[Element]> {
+        match self {
+            Value::Object(elements, _) | Value::Custom(_, elements, _) => Some(elements),
+            Value::AMF3(inner) => inner.elements(),
+            _ => None,
+        }
+    }
+
+    /// Get the contents of a `ByteArray`, or None if this is any other value
+    /// `AMF3` wrapped values give the contents of their value
+    pub fn as_bytes(&self) -> Option<&[u8]> {
+        match self.unwrap_amf3() {
+            Value::ByteArray(bytes) => Some(bytes),
+            _ => None,
+        }
+    }
+
+    /// Check if this and `other` are both dates of the same time, ignoring their timezones
+    /// The time of a date is always UTC and the timezone is only kept by AMF0, so the same date read from AMF0 and AMF3
+    /// differs by its timezone, see `Lso::normalize_dates`. Gives false if either value is not a `Date`
+    /// `AMF3` wrapped values are compared by their contents
+    pub fn same_instant(&self, other: &Value) -> bool {
+        match (self.unwrap_amf3(), other.unwrap_amf3()) {
+            (Value::Date(a, _), Value::Date(b, _)) => a == b,
+            _ => false,
+        }
+    }
+
+    /// Get the value of the property or element with the given name, or None if there is none
+    /// This searches the properties of an `Object`, the associative part of an `ECMAArray` and the regular elements
+    /// of a `Custom`, if a name appears more than once the last value is given as with `as_map`
+    /// `AMF3` wrapped values search their contents
+    pub fn get(&self, name: &str) -> Option<&Value> {
+        self.get_rc(name).map(|v| v.as_ref())
+    }
+
+    fn get_rc(&self, name: &str) -> Option<&Rc<Value>> {
+        match self {
+            Value::Object(elements, _)
+            | Value::ECMAArray(_, elements, _)
+            | Value::Custom(_, elements, _) => elements
+                .iter()
+                .rev()
+                .find(|e| e.name() == name)
+                .map(|e| &e.value),
+            Value::AMF3(inner) => inner.get_rc(name),
+            _ => None,
+        }
+    }
+
+    /// Get the item at `index` of a `StrictArray`, `VectorObject` or the dense part of an `ECMAArray`, or None if
+    /// there is no such item. The items of `VectorInt`, `VectorUInt` and `VectorDouble` aren't values, so can't be given
+    /// `AMF3` wrapped values give the items of their contents
+    pub fn get_index(&self, index: usize) -> Option<&Value> {
+        self.get_index_rc(index).map(|v| v.as_ref())
+    }
+
+    fn get_index_rc(&self, index: usize) -> Option<&Rc<Value>> {
+        match self {
+            Value::StrictArray(items)
+            | Value::VectorObject(items, _, _)
+            | Value::ECMAArray(items, _, _) => items.get(index),
+            Value::AMF3(inner) => inner.get_index_rc(index),
+            _ => None,
+        }
+    }
+
+    /// Get the value at a path such as `a.b[0].c` below this value, or None if there is nothing there or the path is invalid
+    /// Each `.` separated segment is a name looked up as with `get`, followed by any number of `[index]` looked up as
+    /// with `get_index`. A segment that is only a number is also used as an index if there is no property with that name,
+    /// so the paths given by `Lso::walk_mut` and `Span::path` work here too
+    /// The contents of dictionaries and names containing `.` or `[` can't be reached, and an empty path gives None as
+    /// this value isn't held in an `Rc`
+    ///
+    /// ```
+    /// use flash_lso::types::{Element, Value};
+    /// let items = Value::strict_array(vec![Value::object(vec![Element::new("c", 5)], None)]);
+    /// let b = Value::object(vec![Element::new("b", items)], None);
+    /// let value = Value::object(vec![Element::new("a", b)], None);
+    /// assert_eq!(value.path("a.b[0].c").map(|v| v.as_ref()), Some(&Value::Integer(5)));
+    /// assert_eq!(value.path("a.b.0.c"), value.path("a.b[0].c"));
+    /// assert_eq!(value.path("a.b[1].c"), None);
+    /// ```
+    pub fn path(&self, expr: &str) -> Option<&Rc<Value>> {
+        let mut current: Option<&Rc<Value>> = None;
+        for segment in expr.split('.') {
+            let (name, mut indices) = segment.split_at(segment.find('[').unwrap_or(segment.len()));
+
+            if !name.is_empty() {
+                let parent = current.map_or(self, |v| v.as_ref());
+                current = match parent.get_rc(name) {
+                    Some(v) => Some(v),
+                    None => parent.get_index_rc(name.parse().ok()?),
+                };
+                current?;
+            } else if indices.is_empty() {
+                return None;
+            }
+
+            while !indices.is_empty() {
+                let rest = indices.strip_prefix('[')?;
+                let end = rest.find(']')?;
+                let index = rest[..end].parse().ok()?;
+                current = Some(current.map_or(self, |v| v.as_ref()).get_index_rc(index)?);
+                indices = &rest[end + 1..];
+            }
+        }
+        current
+    }
+
+    /// Deserialize each item of a `StrictArray` or `VectorObject` into `T` with `amf3::from_value`, such as a struct with
+    /// `#[derive(Deserialize)]`, failing if this is any other value or an item can't be deserialized
+    /// `AMF3` wrapped values give the items of their contents
+    #[cfg(feature = "serde")]
+    pub fn to_vec<'de, T: serde::Deserialize<'de>>(
+        &'de self,
+    ) -> Result<Vec<T>, crate::amf3::de::FromValueError> {
+        match self.unwrap_amf3() {
+            Value::StrictArray(_) | Value::VectorObject(_, _, _) => crate::amf3::from_value(self),
+            _ => Err(serde::de::Error::custom(
+                "expected a StrictArray or VectorObject",
+            )),
+        }
+    }
+
+    /// Get a copy of the items of a `VectorInt`, or None for other values
+    /// `AMF3` wrapped values give the items of their contents
+    pub fn to_vec_i32(&self) -> Option<Vec<i32>> {
+        match self.unwrap_amf3() {
+            Value::VectorInt(items, _) => Some(items.clone()),
+            _ => None,
+        }
+    }
+
+    /// Get a copy of the items of a `VectorUInt`, or None for other values
+    /// `AMF3` wrapped values give the items of their contents
+    pub fn to_vec_u32(&self) -> Option<Vec<u32>> {
+        match self.unwrap_amf3() {
+            Value::VectorUInt(items, _) => Some(items.clone()),
+            _ => None,
+        }
+    }
+
+    /// Get a copy of the items of a `VectorDouble`, or None for other values
+    /// `AMF3` wrapped values give the items of their contents
+    pub fn to_vec_f64(&self) -> Option<Vec<f64>> {
+        match self.unwrap_amf3() {
+            Value::VectorDouble(items, _) => Some(items.clone()),
+            _ => None,
+        }
+    }
+
+    /// Get the named elements of an object-like value sorted by name, without modifying the value
+    /// For `ECMAArray` this is the associative part, for `Custom` both the custom and regular elements
+    /// Elements with the same name keep their original relative order, as the sort is stable
+    /// Other values have no named elements, so this will be empty
+    pub fn sorted_elements(&self) -> Vec<&Element> {
+        let mut elements: Vec<&Element> = match self {
+            Value::Object(elements, _) | Value::ECMAArray(_, elements, _) => {
+                elements.iter().collect()
+            }
+            Value::Custom(custom_elements, elements, _) => {
+                custom_elements.iter().chain(elements.iter()).collect()
+            }
+            _ => Vec::new(),
+        };
+        elements.sort_by(|a, b| a.name.cmp(&b.name));
+        elements
+    }
+
+    /// Get the named elements of an `Object` or the associative part of an `ECMAArray` as a map from name to value,
+    /// or None for other values, the dense part of an `ECMAArray` is not included
+    /// Entries are in the order they first appear, if a name appears more than once then the last value is kept
+    /// `AMF3` wrapped values give the map of their contents
+    #[cfg(feature = "indexmap")]
+    pub fn as_map(&self) -> Option<IndexMap<String, Rc<Value>>> {
+        match self {
+            Value::Object(elements, _) | Value::ECMAArray(_, elements, _) => Some(
+                elements
+                    .iter()
+                    .map(|e| (e.name.to_string(), Rc::clone(&e.value)))
+                    .collect(),
+            ),
+            Value::AMF3(inner) => inner.as_map(),
+            _ => None,
+        }
+    }
+
+    /// Get the entries of a `Dictionary` whose keys are all strings as a list of (key, value) pairs, or None for other values
+    /// If `integer_keys` is set, `Integer` keys are also accepted and given as their decimal string, as ActionScript
+    /// does when using a number as a property name
+    /// If any key is of another type (including a mix of strings and integers when `integer_keys` is not set), this gives None
+    /// Entries are in the order they were read, duplicate keys (such as `"1"` and `1`) are all kept, so use
+    /// `collect` into a map to keep only the last value of each key
+    /// `AMF3` wrapped values give the entries of their contents
+    pub fn dict_string_keys(&self, integer_keys: bool) -> Option<Vec<(String, Rc<Value>)>> {
+        match self {
+            Value::Dictionary(pairs, _) => pairs
+                .iter()
+                .map(|(k, v)| {
+                    let key = match k.as_ref() {
+                        Value::String(s) => s.clone(),
+                        Value::Integer(i) if integer_keys => i.to_string(),
+                        _ => return None,
+                    };
+                    Some((key, Rc::clone(v)))
+                })
+                .collect(),
+            Value::AMF3(inner) => inner.dict_string_keys(integer_keys),
+            _ => None,
+        }
+    }
+
+    /// Compare two values, allowing numbers to differ by up to `epsilon`
+    /// Unlike `PartialEq`, `Integer` and `Number` are compared by their numeric value, NaN is equal to NaN and
+    /// `AMF3` wrapped values are compared by their contents
+    /// Containers are compared item by item in order, with element names, class definitions and all other values
+    /// compared exactly
+    pub fn approx_eq(&self, other: &Value, epsilon: f64) -> bool {
+        let values_eq = |a: &[Rc<Value>], b: &[Rc<Value>]| {
+            a.len() == b.len() && a.iter().zip(b).all(|(a, b)| a.approx_eq(b, epsilon))
+        };
+        let elements_eq = |a: &[Element], b: &[Element]| {
+            a.len() == b.len()
+                && a.iter()
+                    .zip(b)
+                    .all(|(a, b)| a.name == b.name && a.value.approx_eq(&b.value, epsilon))
+        };
+
+        match (self, other) {
+            (Value::AMF3(a), b) => a.approx_eq(b, epsilon),
+            (a, Value::AMF3(b)) => a.approx_eq(b, epsilon),
+            (Value::Number(a), Value::Number(b)) => f64_approx_eq(*a, *b, epsilon),
+            (Value::Number(a), Value::Integer(b)) | (Value::Integer(b), Value::Number(a)) => {
+                f64_approx_eq(*a, f64::from(*b), epsilon)
+            }
+            (Value::Date(a, a_tz), Value::Date(b, b_tz)) => {
+                a_tz == b_tz && f64_approx_eq(*a, *b, epsilon)
+            }
+            (Value::VectorDouble(a, a_fixed), Value::VectorDouble(b, b_fixed)) => {
+                a_fixed == b_fixed
+                    && a.len() == b.len()
+                    && a.iter().zip(b).all(|(a, b)| f64_approx_eq(*a, *b, epsilon))
+            }
+            (Value::Object(a, a_def), Value::Object(b, b_def)) => {
+                a_def == b_def && elements_eq(a, b)
+            }
+            (
+                Value::ECMAArray(a_dense, a_assoc, a_len),
+                Value::ECMAArray(b_dense, b_assoc, b_len),
+            ) => a_len == b_len && values_eq(a_dense, b_dense) && elements_eq(a_assoc, b_assoc),
+            (Value::StrictArray(a), Value::StrictArray(b)) => values_eq(a, b),
+            (Value::VectorObject(a, a_name, a_fixed), Value::VectorObject(b, b_name, b_fixed)) => {
+                a_name == b_name && a_fixed == b_fixed && values_eq(a, b)
+            }
+            (Value::Dictionary(a, a_weak), Value::Dictionary(b, b_weak)) => {
+                a_weak == b_weak
+                    && a.len() == b.len()
+                    && a.iter().zip(b).all(|((a_key, a_value), (b_key, b_value))| {
+                        a_key.approx_eq(b_key, epsilon) && a_value.approx_eq(b_value, epsilon)
+                    })
+            }
+            (Value::Custom(a_custom, a, a_def), Value::Custom(b_custom, b, b_def)) => {
+                a_def == b_def && elements_eq(a_custom, b_custom) && elements_eq(a, b)
+            }
+            (a, b) => a == b,
+        }
+    }
+
+    /// Compare two values, ignoring the order of named elements
+    /// The elements of an `Object`, the associative part of an `ECMAArray`, each part of a `Custom` value and the entries of
+    /// a `Dictionary` are compared as multisets, so a name that appears more than once must appear the same number of
+    /// times, with matching values, in both
+    /// Static properties of class definitions are also compared in any order, dense arrays and vectors are compared in
+    /// order and all other values are compared as with `PartialEq`
+    pub fn eq_unordered(&self, other: &Value) -> bool {
+        let values_eq = |a: &[Rc<Value>], b: &[Rc<Value>]| {
+            a.len() == b.len() && a.iter().zip(b).all(|(a, b)| a.eq_unordered(b))
+        };
+        let elements_eq = |a: &[Element], b: &[Element]| {
+            multiset_eq(a, b, |a, b| {
+                a.name == b.name && a.value.eq_unordered(&b.value)
+            })
+        };
+
+        match (self, other) {
+            (Value::AMF3(a), Value::AMF3(b)) => a.eq_unordered(b),
+            (Value::Object(a, a_def), Value::Object(b, b_def)) => {
+                class_def_eq_unordered(a_def, b_def) && elements_eq(a, b)
+            }
+            (
+                Value::ECMAArray(a_dense, a_assoc, a_len),
+                Value::ECMAArray(b_dense, b_assoc, b_len),
+            ) => a_len == b_len && values_eq(a_dense, b_dense) && elements_eq(a_assoc, b_assoc),
+            (Value::StrictArray(a), Value::StrictArray(b)) => values_eq(a, b),
+            (Value::VectorObject(a, a_name, a_fixed), Value::VectorObject(b, b_name, b_fixed)) => {
+                a_name == b_name && a_fixed == b_fixed && values_eq(a, b)
+            }
+            (Value::Dictionary(a, a_weak), Value::Dictionary(b, b_weak)) => {
+                a_weak == b_weak
+                    && multiset_eq(a, b, |(a_key, a_value), (b_key, b_value)| {
+                        a_key.eq_unordered(b_key) && a_value.eq_unordered(b_value)
+                    })
+            }
+            (Value::Custom(a_custom, a, a_def), Value::Custom(b_custom, b, b_def)) => {
+                class_def_eq_unordered(a_def, b_def)
+                    && elements_eq(a_custom, b_custom)
+                    && elements_eq(a, b)
+            }
+            (a, b) => a == b,
+        }
+    }
+
+    /// Recursively remove the elements of `Object`, `Custom` and `ECMAArray` values for which `f` returns false,
+    /// given the name and value of each element
+    /// Each element is checked before its contents, so the contents of a removed element are not visited
+    /// The names of removed elements are also removed from the static properties of the class definition, so that
+    /// sealed objects can still be written, the declared length of an `ECMAArray` is kept as it is
+    /// Other containers are searched for nested elements, but their items and dictionary entries are never removed
+    ///
+    /// As values are stored behind `Rc`, any nested container that is shared with another is cloned before it is
+    /// pruned (see `Rc::make_mut`), so shared values (such as those read by reference) will no longer be shared afterwards,
+    /// even if nothing is removed from them
+    ///
+    /// ```
+    /// use flash_lso::types::{Element, Value};
+    ///
+    /// let mut user = Value::object(
+    ///     vec![Element::new("name", "x"), Element::new("password", "hunter2")],
+    ///     None,
+    /// );
+    /// user.retain(|name, _| name != "password");
+    /// assert_eq!(user, Value::object(vec![Element::new("name", "x")], None));
+    /// ```
+    pub fn retain<F: FnMut(&str, &Value) -> bool>(&mut self, mut f: F) {
+        self.retain_inner(&mut f);
+    }
+
+    fn retain_inner<F: FnMut(&str, &Value) -> bool>(&mut self, f: &mut F) {
+        match self {
+            Value::Object(elements, class_def) => retain_elements(elements, class_def.as_mut(), f),
+            Value::Custom(custom_elements, elements, class_def) => {
+                retain_elements(custom_elements, None, f);
+                retain_elements(elements, class_def.as_mut(), f);
+            }
+            Value::ECMAArray(dense, assoc, _) => {
+                for item in dense.iter_mut() {
+                    retain_rc(item, f);
+                }
+                retain_elements(assoc, None, f);
+            }
+            Value::StrictArray(items) | Value::VectorObject(items, _, _) => {
+                for item in items.iter_mut() {
+                    retain_rc(item, f);
+                }
+            }
+            Value::Dictionary(pairs, _) => {
+                for (k, v) in pairs.iter_mut() {
+                    retain_rc(k, f);
+                    retain_rc(v, f);
+                }
+            }
+            Value::AMF3(inner) => retain_rc(inner, f),
+            _ => {}
+        }
+    }
+
+    /// Drop this value in a loop rather than recursively, so values nested deeper than the stack allows can be freed
+    /// Values read by a decoder are limited by its `max_depth`, so this is only needed after raising that limit or for
+    /// values built by hand. Nested values that are still shared elsewhere are left to their other owners
+    pub fn drop_iteratively(self) {
+        let mut stack = Vec::new();
+        self.into_children(&mut stack);
+        while let Some(child) = stack.pop() {
+            if let Ok(child) = Rc::try_unwrap(child) {
+                child.into_children(&mut stack);
+            }
+        }
+    }
+
+    /// Move the values held directly by this one to `stack`, see `drop_iteratively`
+    fn into_children(self, stack: &mut Vec<Rc<Value>>) {
+        match self {
+            Value::Object(elements, _) => stack.extend(elements.into_iter().map(|e| e.value)),
+            Value::ECMAArray(dense, assoc, _) => {
+                stack.extend(dense);
+                stack.extend(assoc.into_iter().map(|e| e.value));
+            }
+            Value::StrictArray(items) | Value::VectorObject(items, _, _) => stack.extend(items),
+            Value::Custom(custom_elements, elements, _) => {
+                stack.extend(custom_elements.into_iter().map(|e| e.value));
+                stack.extend(elements.into_iter().map(|e| e.value));
+            }
+            Value::Dictionary(pairs, _) => {
+                for (k, v) in pairs {
+                    stack.push(k);
+                    stack.push(v);
+                }
+            }
+            Value::AMF3(inner) => stack.push(inner),
+            _ => {}
+        }
+    }
+
+    /// Count the values in this value, including itself and all nested values
+    /// The items of `VectorInt`, `VectorUInt` and `VectorDouble` aren't values, so each of these counts once,
+    /// an `AMF3` wrapper counts as a value as well as its contents
+    /// A shared value (the same `Rc`) is counted once however many places it appears in, so the work done is
+    /// proportional to the memory used even for values built to share a few values in very many places
+    /// Values are visited with a stack on the heap, so this can't overflow the stack however deeply they are nested
+    pub fn count_nodes(&self) -> usize {
+        let mut count = 1;
+        self.for_each_shared_value(|_| count += 1);
+        count
+    }
+
+    /// Estimate the number of bytes of heap memory held by this value and all nested values
+    /// This counts the contents of strings, byte arrays and vectors, element and class names and the allocations
+    /// for nested values, but not the size of this value itself or unused capacity
+    /// A shared value (the same `Rc`) is counted once, as it is only held in memory once, while each place it appears
+    /// counts the size of the `Rc` (and the element) pointing to it. As with `count_nodes` this doesn't recurse
+    pub fn heap_size(&self) -> usize {
+        // Each nested value is held in its own allocation, along with the reference counts
+        let rc_size = size_of::<Value>() + 2 * size_of::<usize>();
+        let mut size = self.own_heap_size();
+        self.for_each_shared_value(|value| size += rc_size + value.own_heap_size());
+        size
+    }
+
+    /// The part of `heap_size` held directly by this value, not counting the allocations of the values nested in it
+    fn own_heap_size(&self) -> usize {
+        let elements_size = |elements: &[Element]| -> usize {
+            elements
+                .iter()
+                .map(|e| size_of::<Element>() + e.name.len())
+                .sum()
+        };
+        let values_size = |values: &[Rc<Value>]| size_of_val(values);
+        let class_def_size = |class_def: &Option<ClassDefinition>| -> usize {
+            class_def.as_ref().map_or(0, |def| {
+                def.name.len()
+                    + def
+                        .static_properties
+                        .iter()
+                        .map(|p| size_of::<String>() + p.len())
+                        .sum::<usize>()
+            })
+        };
+
+        match self {
+            Value::String(s) | Value::XML(s, _) => s.len(),
+            Value::ByteArray(bytes) => bytes.len(),
+            Value::VectorInt(items, _) => items.len() * size_of::<i32>(),
+            Value::VectorUInt(items, _) => items.len() * size_of::<u32>(),
+            Value::VectorDouble(items, _) => items.len() * size_of::<f64>(),
+            Value::Object(elements, class_def) => {
+                elements_size(elements) + class_def_size(class_def)
+            }
+            Value::ECMAArray(dense, assoc, _) => values_size(dense) + elements_size(assoc),
+            Value::StrictArray(items) => values_size(items),
+            Value::VectorObject(items, type_name, _) => values_size(items) + type_name.len(),
+            Value::Dictionary(pairs, _) => pairs.len() * 2 * size_of::<Rc<Value>>(),
+            Value::Custom(custom_elements, elements, class_def) => {
+                elements_size(custom_elements) + elements_size(elements) + class_def_size(class_def)
+            }
+            _ => 0,
+        }
+    }
+
+    /// Call `f` once for each distinct `Rc` nested in this value, however many places it appears in, see `count_nodes`
+    fn for_each_shared_value<'a>(&'a self, mut f: impl FnMut(&'a Value)) {
+        let mut seen = Set::default();
+        let mut stack = Vec::new();
+        self.push_children(&mut stack);
+        while let Some(value) = stack.pop() {
+            if seen.insert(Rc::as_ptr(value)) {
+                f(value);
+                value.push_children(&mut stack);
+            }
+        }
+    }
+
+    /// Push the values held directly by this one to `stack`, see `for_each_shared_value`
+    fn push_children<'a>(&'a self, stack: &mut Vec<&'a Rc<Value>>) {
+        match self {
+            Value::Object(elements, _) => stack.extend(elements.iter().map(|e| &e.value)),
+            Value::ECMAArray(dense, assoc, _) => {
+                stack.extend(dense);
+                stack.extend(assoc.iter().map(|e| &e.value));
+            }
+            Value::StrictArray(items) | Value::VectorObject(items, _, _) => stack.extend(items),
+            Value::Custom(custom_elements, elements, _) => {
+                stack.extend(custom_elements.iter().map(|e| &e.value));
+                stack.extend(elements.iter().map(|e| &e.value));
+            }
+            Value::Dictionary(pairs, _) => {
+                for (k, v) in pairs {
+                    stack.push(k);
+                    stack.push(v);
+                }
+            }
+            Value::AMF3(inner) => stack.push(inner),
+            _ => {}
+        }
+    }
+
+    /// Render as a multi-line tree, with one line per value and the contents of containers on the lines below them
+    /// Containers are labelled with their class name or type and the number of entries they hold, scalars are
+    /// rendered as with `Display`
+    /// Each level of nesting is indented by `indent` spaces, the contents of containers more than `max_depth` levels
+    /// deep are shown as `...`
+    ///
+    /// ```
+    /// use flash_lso::types::{Element, Value};
+    ///
+    /// let value = Value::object(
+    ///     vec![
+    ///         Element::new("hp", 100),
+    ///         Element::new("items", Value::strict_array(vec![1])),
+    ///     ],
+    ///     None,
+    /// );
+    /// assert_eq!(
+    ///     value.pretty(2, 8),
+    ///     "Object (2 properties)\n  hp: 100\n  items: Array (1 item)\n    0: 1"
+    /// );
+    /// ```
+    pub fn pretty(&self, indent: usize, max_depth: usize) -> String {
+        let mut printer = PrettyPrinter {
+            out: String::new(),
+            indent,
+            max_depth,
+        };
+        printer.value(0, None, self);
+        // Every line ends with a newline, including the last
+        printer.out.pop();
+        printer.out
+    }
+
+    /// Convert a value read from AMF0 to its AMF3 equivalent, so that it can be written in an AMF3 file
+    /// - Whole `Number`s in the range of an AMF3 integer (-2^28 to 2^28 - 1) become `Integer`, except -0
+    /// - Dates lose their timezone, AMF3 dates are always UTC
+    /// - `AMF3` wrapped values are unwrapped
+    /// - `Unsupported` becomes `Undefined`
+    /// - Objects without a class definition become anonymous dynamic objects, typed objects with elements that aren't
+    ///   static properties have every element listed as a static property, so that no elements are lost when written
+    ///
+    /// AMF0 long strings are already read as `String`, so need no conversion
+    /// Containers are converted recursively, values that are shared within this value stay shared
+    pub fn to_amf3(&self) -> Value {
+        self.amf3_value(&mut Map::new())
+    }
+
+    fn amf3_value(&self, converted: &mut Map<*const Value, Rc<Value>>) -> Value {
+        let convert_elements = |elements: &[Element], converted: &mut Map<_, _>| {
+            elements
+                .iter()
+                .map(|e| Element {
+                    name: e.name.clone(),
+                    value: amf3_rc(&e.value, converted),
+                })
+                .collect()
+        };
+
+        match self {
+            Value::Number(n) if is_amf3_integer(*n) => Value::Integer(*n as i32),
+            Value::Date(time, _) => Value::Date(*time, None),
+            Value::AMF3(inner) => amf3_rc(inner, converted).as_ref().clone(),
+            Value::Unsupported => Value::Undefined,
+            Value::Object(elements, class_def) => Value::Object(
+                convert_elements(elements, converted),
+                Some(amf3_class_def(elements, class_def.as_ref())),
+            ),
+            Value::StrictArray(items) => Value::StrictArray(amf3_rcs(items, converted)),
+            Value::ECMAArray(dense, assoc, len) => Value::ECMAArray(
+                amf3_rcs(dense, converted),
+                convert_elements(assoc, converted),
+                *len,
+            ),
+            Value::VectorObject(items, type_name, fixed_length) => {
+                Value::VectorObject(amf3_rcs(items, converted), type_name.clone(), *fixed_length)
+            }
+            Value::Dictionary(pairs, weak_keys) => Value::Dictionary(
+                pairs
+                    .iter()
+                    .map(|(k, v)| (amf3_rc(k, converted), amf3_rc(v, converted)))
+                    .collect(),
+                *weak_keys,
+            ),
+            Value::Custom(custom_elements, elements, class_def) => Value::Custom(
+                convert_elements(custom_elements, converted),
+                convert_elements(elements, converted),
+                class_def.clone(),
+            ),
+            _ => self.clone(),
+        }
+    }
+
+    /// Get the value inside an `AMF3` wrapper, or this value if it isn't wrapped
+    pub fn unwrap_amf3(&self) -> &Value {
+        match self {
+            Value::AMF3(inner) => inner,
+            _ => self,
+        }
+    }
+
+    /// Check if this is an object whose class definition has `Attribute::External`
+    /// Such objects were read by an external decoder, and an external encoder for the class must be registered to write them
+    pub fn is_externalized(&self) -> bool {
+        match self {
+            Value::Object(_, Some(class_def)) | Value::Custom(_, _, Some(class_def)) => {
+                class_def.attributes.contains(Attribute::External)
+            }
+            _ => false,
+        }
+    }
+
+    /// If this is an XML value, get whether it is an XMLString (true) or an XMLDocument (false)
+    pub fn is_xml_string(&self) -> Option<bool> {
+        match self {
+            Value::XML(_, is_string) => Some(*is_string),
+            _ => None,
+        }
+    }
+
+    /// If this is an XML value, parse its content into a DOM
+    /// The stored string is left untouched, so writing this value is unaffected by parsing
+    #[cfg(feature = "xml")]
+    pub fn parse_xml(&self) -> Option<Result<roxmltree::Document<'_>, roxmltree::Error>> {
+        match self {
+            Value::XML(content, _) => Some(roxmltree::Document::parse(content)),
+            _ => None,
+        }
+    }
+}
+
+/// Containers nested deeper than this are shown as `{...}` or `[...]` by `Display`
+const MAX_DISPLAY_DEPTH: usize = 8;
+
+/// A value being displayed at a given nesting depth
+struct DisplayValue<'a>(&'a Value, usize);
+
+/// The key of an entry in an object-like value being displayed
+enum DisplayKey<'a> {
+    /// The name of an element, shown unquoted
+    Name(&'a str),
+    /// A dictionary key
+    Value(&'a Value),
+}
+
+impl DisplayValue<'_> {
+    fn list<'b>(
+        &self,
+        f: &mut Formatter<'_>,
+        items: impl Iterator<Item = &'b Value>,
+        elements: impl Iterator<Item = &'b Element>,
+    ) -> fmt::Result {
+        if self.1 >= MAX_DISPLAY_DEPTH {
+            return f.write_str("[...]");
+        }
+
+        f.write_str("[")?;
+        let mut first = true;
+        for item in items {
+            if !first {
+                f.write_str(", ")?;
+            }
+            first = false;
+            write!(f, "{}", DisplayValue(item, self.1 + 1))?;
+        }
+        for e in elements {
+            if !first {
+                f.write_str(", ")?;
+            }
+            first = false;
+            write!(f, "{}: {}", e.name, DisplayValue(&e.value, self.1 + 1))?;
+        }
+        f.write_str("]")
+    }
+
+    fn object<'b>(
+        &self,
+        f: &mut Formatter<'_>,
+        entries: impl Iterator<Item = (DisplayKey<'b>, &'b Value)>,
+    ) -> fmt::Result {
+        if self.1 >= MAX_DISPLAY_DEPTH {
+            return f.write_str("{...}");
+        }
+
+        f.write_str("{")?;
+        for (index, (key, value)) in entries.enumerate() {
+            if index > 0 {
+                f.write_str(", ")?;
+            }
+            match key {
+                DisplayKey::Name(name) => f.write_str(name)?,
+                DisplayKey::Value(key) => write!(f, "{}", DisplayValue(key, self.1 + 1))?,
+            }
+            write!(f, ": {}", DisplayValue(value, self.1 + 1))?;
+        }
+        f.write_str("}")
+    }
+}
+
+impl fmt::Display for DisplayValue<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let depth = self.1;
+        match self.0 {
+            Value::Number(n) | Value::Date(n, _) => write!(f, "{}", n),
+            Value::Integer(i) => write!(f, "{}", i),
+            Value::Bool(b) => write!(f, "{}", b),
+            Value::String(s) | Value::XML(s, _) => write!(f, "{:?}", s),
+            Value::Null => f.write_str("null"),
+            Value::Undefined => f.write_str("undefined"),
+            Value::Unsupported => f.write_str("unsupported"),
+            Value::ByteArray(bytes) => write!(f, "<{} bytes>", bytes.len()),
+            Value::AMF3(inner) => write!(f, "{}", DisplayValue(inner, depth)),
+            Value::Object(elements, _) => self.object(f, elements.iter().map(element_entry)),
+            Value::Custom(custom_elements, elements, _) => self.object(
+                f,
+                custom_elements
+                    .iter()
+                    .chain(elements.iter())
+                    .map(element_entry),
+            ),
+            Value::Dictionary(pairs, _) => self.object(
+                f,
+                pairs.iter().map(|(k, v)| (DisplayKey::Value(k), v.deref())),
+            ),
+            Value::ECMAArray(dense, assoc, _) => {
+                self.list(f, dense.iter().map(Rc::deref), assoc.iter())
+            }
+            Value::StrictArray(items) | Value::VectorObject(items, _, _) => {
+                self.list(f, items.iter().map(Rc::deref), [].iter())
+            }
+            Value::VectorInt(items, _) => write_numbers(f, items, depth),
+            Value::VectorUInt(items, _) => write_numbers(f, items, depth),
+            Value::VectorDouble(items, _) => write_numbers(f, items, depth),
+        }
+    }
+}
+
+fn element_entry(e: &Element) -> (DisplayKey<'_>, &Value) {
+    (DisplayKey::Name(&e.name), e.value())
+}
+
+fn write_numbers<T: fmt::Display>(f: &mut Formatter<'_>, items: &[T], depth: usize) -> fmt::Result {
+    if depth >= MAX_DISPLAY_DEPTH {
+        return f.write_str("[...]");
+    }
+
+    f.write_str("[")?;
+    for (index, item) in items.iter().enumerate() {
+        if index > 0 {
+            f.write_str(", ")?;
+        }
+        write!(f, "{}", item)?;
+    }
+    f.write_str("]")
+}
+
+/// A compact, single line rendering in the style of a JSON literal, e.g. `{hp: 100, name: "x", items: [1, 2]}`
+/// Byte arrays are shown as their length, dates as milliseconds since the epoch and class definitions are omitted
+/// Containers nested more than 8 levels deep are shown as `{...}` or `[...]`, use `Debug` to see everything
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", DisplayValue(self, 0))
+    }
+}
+
+/// Format a count of things, e.g. `1 item` or `2 items`
+fn count(n: usize, singular: &str, plural: &str) -> String {
+    format!("{} {}", n, if n == 1 { singular } else { plural })
+}
+
+/// Writes the tree rendered by `Value::pretty`
+struct PrettyPrinter {
+    out: String,
+    indent: usize,
+    max_depth: usize,
+}
+
+impl PrettyPrinter {
+    /// Write a line at the given depth, prefixed with the key of the value if it has one
+    fn line(&mut self, depth: usize, key: Option<&dyn fmt::Display>, text: &dyn fmt::Display) {
+        use core::fmt::Write;
+
+        let indent = depth * self.indent;
+        let _ = match key {
+            Some(key) => writeln!(self.out, "{:indent$}{}: {}", "", key, text, indent = indent),
+            None => writeln!(self.out, "{:indent$}{}", "", text, indent = indent),
+        };
+    }
+
+    fn value(&mut self, depth: usize, key: Option<&dyn fmt::Display>, value: &Value) {
+        let label = match value {
+            Value::AMF3(inner) => return self.value(depth, key, inner),
+            Value::Object(elements, class_def) => {
+                let name = class_def
+                    .as_ref()
+                    .map(|def| def.name.as_str())
+                    .filter(|name| !name.is_empty())
+                    .unwrap_or("Object");
+                format!(
+                    "{} ({})",
+                    name,
+                    count(elements.len(), "property", "properties")
+                )
+            }
+            Value::Custom(custom_elements, elements, class_def) => format!(
+                "{} (external, {})",
+                class_def.as_ref().map_or("Custom", |def| def.name.as_str()),
+                count(
+                    custom_elements.len() + elements.len(),
+                    "property",
+                    "properties"
+                )
+            ),
+            Value::ECMAArray(dense, assoc, _) if assoc.is_empty() => {
+                format!("ECMAArray ({})", count(dense.len(), "item", "items"))
+            }
+            Value::ECMAArray(dense, assoc, _) => format!(
+                "ECMAArray ({}, {})",
+                count(dense.len(), "item", "items"),
+                count(assoc.len(), "property", "properties")
+            ),
+            Value::StrictArray(items) => format!("Array ({})", count(items.len(), "item", "items")),
+            Value::VectorInt(items, _) => {
+                format!("Vector.<int> ({})", count(items.len(), "item", "items"))
+            }
+            Value::VectorUInt(items, _) => {
+                format!("Vector.<uint> ({})", count(items.len(), "item", "items"))
+            }
+            Value::VectorDouble(items, _) => {
+                format!("Vector.<Number> ({})", count(items.len(), "item", "items"))
+            }
+            Value::VectorObject(items, type_name, _) => format!(
+                "Vector.<{}> ({})",
+                type_name,
+                count(items.len(), "item", "items")
+            ),
+            Value::Dictionary(pairs, _) => {
+                format!("Dictionary ({})", count(pairs.len(), "entry", "entries"))
+            }
+            scalar => return self.line(depth, key, scalar),
+        };
+        self.line(depth, key, &label);
+
+        let child = depth + 1;
+        if value.len().unwrap_or(0) > 0 && depth >= self.max_depth {
+            return self.line(child, None, &"...");
+        }
+
+        let elements = |printer: &mut Self, elements: &[Element]| {
+            for e in elements {
+                printer.value(child, Some(&e.name), e.value());
+            }
+        };
+        match value {
+            Value::Object(items, _) => elements(self, items),
+            Value::Custom(custom_elements, items, _) => {
+                elements(self, custom_elements);
+                elements(self, items);
+            }
+            Value::ECMAArray(dense, assoc, _) => {
+                for (index, item) in dense.iter().enumerate() {
+                    self.value(child, Some(&index), item);
+                }
+                elements(self, assoc);
+            }
+            Value::StrictArray(items) | Value::VectorObject(items, _, _) => {
+                for (index, item) in items.iter().enumerate() {
+                    self.value(child, Some(&index), item);
+                }
+            }
+            Value::VectorInt(items, _) => self.numbers(child, items),
+            Value::VectorUInt(items, _) => self.numbers(child, items),
+            Value::VectorDouble(items, _) => self.numbers(child, items),
+            Value::Dictionary(pairs, _) => {
+                for (k, v) in pairs {
+                    self.value(child, Some(k.as_ref()), v);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn numbers<T: fmt::Display>(&mut self, depth: usize, items: &[T]) {
+        for (index, item) in items.iter().enumerate() {
+            self.line(depth, Some(&index), item);
+        }
+    }
+}
+
 impl FromIterator<Value> for Vec<Rc<Value>> {
     fn from_iter<T: IntoIterator<Item = Value>>(iter: T) -> Self {
         iter.into_iter().map(Rc::new).collect()
     }
 }
 
+impl From<f64> for Value {
+    fn from(n: f64) -> Self {
+        Value::Number(n)
+    }
+}
+
+/// `i32` becomes `Value::Integer`, note that AMF3 can only store integers in 29 bits, larger ones are written as numbers
+impl From<i32> for Value {
+    fn from(n: i32) -> Self {
+        Value::Integer(n)
+    }
+}
+
+/// `i64` becomes `Value::Number`, as it doesn't fit in an integer, values above 2^53 lose precision
+impl From<i64> for Value {
+    fn from(n: i64) -> Self {
+        Value::Number(n as f64)
+    }
+}
+
+impl From<bool> for Value {
+    fn from(b: bool) -> Self {
+        Value::Bool(b)
+    }
+}
+
+impl From<String> for Value {
+    fn from(s: String) -> Self {
+        Value::String(s)
+    }
+}
+
+impl From<&str> for Value {
+    fn from(s: &str) -> Self {
+        Value::String(s.to_string())
+    }
+}
+
+/// `Vec<u8>` becomes `Value::ByteArray`
+impl From<Vec<u8>> for Value {
+    fn from(bytes: Vec<u8>) -> Self {
+        Value::ByteArray(bytes)
+    }
+}
+
+/// Get a property or element by name as with `Value::get`, such as `value["player"]["hp"]`
+/// A missing name (or a value that has no named elements) gives `Value::Null` rather than panicking, so lookups can
+/// be chained without checking each step, use `get` to tell a missing value apart from one that is null
+impl Index<&str> for Value {
+    type Output = Value;
+
+    fn index(&self, name: &str) -> &Value {
+        self.get(name).unwrap_or(&Value::Null)
+    }
+}
+
+/// Get an array item by index as with `Value::get_index`, such as `value["items"][0]`
+/// An index out of bounds (or a value that has no items) gives `Value::Null` rather than panicking, as with `Index<&str>`
+impl Index<usize> for Value {
+    type Output = Value;
+
+    fn index(&self, index: usize) -> &Value {
+        self.get_index(index).unwrap_or(&Value::Null)
+    }
+}
+
+/// A value that can be used as the key of a `HashMap` or `HashSet`, such as to find identical subtrees
+///
+/// Values are compared structurally as with `==`, except that numbers (including the times of dates and the items of
+/// `VectorDouble`) are compared by their bit pattern, so NaN is equal to a NaN with the same bits and `0.0` is not equal
+/// to `-0.0`. As with `==`, an `Integer` is never equal to a `Number`, class definitions are compared in full and
+/// values are compared by content rather than by whether they are shared
+///
+/// ```
+/// use flash_lso::types::{HashableValue, Value};
+/// use std::collections::HashSet;
+///
+/// let values = [Value::Number(f64::NAN), Value::Number(f64::NAN), Value::Integer(1), Value::Number(1.0)];
+/// let unique: HashSet<HashableValue> = values.iter().cloned().map(HashableValue::from).collect();
+/// assert_eq!(unique.len(), 3);
+/// ```
+#[derive(Debug, Clone)]
+pub struct HashableValue(pub Rc<Value>);
+
+impl From<Value> for HashableValue {
+    fn from(value: Value) -> Self {
+        HashableValue(Rc::new(value))
+    }
+}
+
+impl From<Rc<Value>> for HashableValue {
+    fn from(value: Rc<Value>) -> Self {
+        HashableValue(value)
+    }
+}
+
+impl PartialEq for HashableValue {
+    fn eq(&self, other: &Self) -> bool {
+        bits_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for HashableValue {}
+
+impl Hash for HashableValue {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        hash_bits(&self.0, state);
+    }
+}
+
+/// Compare values as `==` does, but with numbers compared by their bits, see `HashableValue`
+pub(crate) fn bits_eq(a: &Value, b: &Value) -> bool {
+    let values_eq = |a: &[Rc<Value>], b: &[Rc<Value>]| {
+        a.len() == b.len() && a.iter().zip(b).all(|(a, b)| bits_eq(a, b))
+    };
+    let elements_eq = |a: &[Element], b: &[Element]| {
+        a.len() == b.len()
+            && a.iter()
+                .zip(b)
+                .all(|(a, b)| a.name == b.name && bits_eq(&a.value, &b.value))
+    };
+
+    match (a, b) {
+        (Value::Number(a), Value::Number(b)) => a.to_bits() == b.to_bits(),
+        (Value::Date(a, a_tz), Value::Date(b, b_tz)) => a.to_bits() == b.to_bits() && a_tz == b_tz,
+        (Value::VectorDouble(a, a_fixed), Value::VectorDouble(b, b_fixed)) => {
+            a_fixed == b_fixed
+                && a.len() == b.len()
+                && a.iter().zip(b).all(|(a, b)| a.to_bits() == b.to_bits())
+        }
+        (Value::Object(a, a_def), Value::Object(b, b_def)) => a_def == b_def && elements_eq(a, b),
+        (Value::ECMAArray(a_dense, a_assoc, a_len), Value::ECMAArray(b_dense, b_assoc, b_len)) => {
+            a_len == b_len && values_eq(a_dense, b_dense) && elements_eq(a_assoc, b_assoc)
+        }
+        (Value::StrictArray(a), Value::StrictArray(b)) => values_eq(a, b),
+        (Value::VectorObject(a, a_type, a_fixed), Value::VectorObject(b, b_type, b_fixed)) => {
+            a_type == b_type && a_fixed == b_fixed && values_eq(a, b)
+        }
+        (Value::Dictionary(a, a_weak), Value::Dictionary(b, b_weak)) => {
+            a_weak == b_weak
+                && a.len() == b.len()
+                && a.iter()
+                    .zip(b)
+                    .all(|((a_k, a_v), (b_k, b_v))| bits_eq(a_k, b_k) && bits_eq(a_v, b_v))
+        }
+        (Value::Custom(a_custom, a, a_def), Value::Custom(b_custom, b, b_def)) => {
+            a_def == b_def && elements_eq(a_custom, b_custom) && elements_eq(a, b)
+        }
+        (Value::AMF3(a), Value::AMF3(b)) => bits_eq(a, b),
+        // The remaining types hold no numbers
+        (a, b) => a == b,
+    }
+}
+
+/// Hash a value consistently with `bits_eq`
+fn hash_bits<H: Hasher>(value: &Value, state: &mut H) {
+    let hash_values = |values: &[Rc<Value>], state: &mut H| {
+        state.write_usize(values.len());
+        for v in values {
+            hash_bits(v, state);
+        }
+    };
+    let hash_elements = |elements: &[Element], state: &mut H| {
+        state.write_usize(elements.len());
+        for e in elements {
+            e.name.hash(state);
+            hash_bits(&e.value, state);
+        }
+    };
+
+    core::mem::discriminant(value).hash(state);
+    match value {
+        Value::Number(n) => n.to_bits().hash(state),
+        Value::Integer(i) => i.hash(state),
+        Value::Bool(b) => b.hash(state),
+        Value::String(s) => s.hash(state),
+        Value::Object(elements, class_def) => {
+            hash_elements(elements, state);
+            class_def.hash(state);
+        }
+        Value::Null | Value::Undefined | Value::Unsupported => {}
+        Value::ECMAArray(dense, assoc, len) => {
+            hash_values(dense, state);
+            hash_elements(assoc, state);
+            len.hash(state);
+        }
+        Value::StrictArray(items) => hash_values(items, state),
+        Value::Date(time, tz) => {
+            time.to_bits().hash(state);
+            tz.hash(state);
+        }
+        Value::XML(content, string) => {
+            content.hash(state);
+            string.hash(state);
+        }
+        Value::ByteArray(bytes) => bytes.hash(state),
+        Value::VectorInt(items, fixed) => {
+            items.hash(state);
+            fixed.hash(state);
+        }
+        Value::VectorUInt(items, fixed) => {
+            items.hash(state);
+            fixed.hash(state);
+        }
+        Value::VectorDouble(items, fixed) => {
+            state.write_usize(items.len());
+            for n in items {
+                n.to_bits().hash(state);
+            }
+            fixed.hash(state);
+        }
+        Value::VectorObject(items, type_name, fixed) => {
+            hash_values(items, state);
+            type_name.hash(state);
+            fixed.hash(state);
+        }
+        Value::Dictionary(pairs, weak_keys) => {
+            state.write_usize(pairs.len());
+            for (k, v) in pairs {
+                hash_bits(k, state);
+                hash_bits(v, state);
+            }
+            weak_keys.hash(state);
+        }
+        Value::Custom(custom_elements, elements, class_def) => {
+            hash_elements(custom_elements, state);
+            hash_elements(elements, state);
+            class_def.hash(state);
+        }
+        Value::AMF3(inner) => hash_bits(inner, state),
+    }
+}
+
+/// Sort a list of elements by name
+/// As objects can contain multiple elements with the same name, the sort is stable so these keep
+/// their original relative order
+/// Note that element order is significant when writing, so this will change the output bytes
+pub fn sort_elements_by_name(elements: &mut [Element]) {
+    elements.sort_by(|a, b| a.name.cmp(&b.name));
+}
+
 /// A class definition (trait) used in AMF3
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[derive(Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
@@ -210,6 +2053,36 @@ impl ClassDefinition {
             static_properties: Vec::new(),
         }
     }
+
+    /// Check if this describes the same trait as `other`, meaning it has the same name, the same
+    /// static property names in the same order and the same attributes
+    /// Unlike `PartialEq` this will stay limited to the parts of a trait that define the shape of an object,
+    /// even if more information is added to `ClassDefinition`
+    pub fn is_compatible_with(&self, other: &Self) -> bool {
+        self.name == other.name
+            && self.static_properties == other.static_properties
+            && self.attributes == other.attributes
+    }
+
+    /// Check if `name` is one of the static properties of this class definition
+    pub(crate) fn has_static_property(&self, name: &str) -> bool {
+        self.static_properties
+            .iter()
+            .any(|property| property == name)
+    }
+
+    /// Get the encoding bits of the AMF3 trait header for this class definition
+    /// This is 0 for sealed, 1 for externalizable, 2 for dynamic and 3 for both externalizable and dynamic
+    pub fn encoding(&self) -> u8 {
+        let mut encoding = 0b00;
+        if self.attributes.contains(Attribute::External) {
+            encoding |= 0b01;
+        }
+        if self.attributes.contains(Attribute::Dynamic) {
+            encoding |= 0b10;
+        }
+        encoding
+    }
 }
 
 /// Encodes the possible attributes that can be given to a trait
@@ -221,3 +2094,18 @@ pub enum Attribute {
     /// If a trait is external then it requires custom serialization and deserialization support
     External,
 }
+
+impl Attribute {
+    /// Get the attributes for the encoding bits of an AMF3 trait header, see `ClassDefinition::encoding`
+    /// Only the low two bits are used, each of the four encodings maps to a distinct set of attributes
+    pub fn from_encoding(encoding: u8) -> EnumSet<Attribute> {
+        let mut attributes = EnumSet::empty();
+        if encoding & 0b01 != 0 {
+            attributes |= Attribute::External;
+        }
+        if encoding & 0b10 != 0 {
+            attributes |= Attribute::Dynamic;
+        }
+        attributes
+    }
+}
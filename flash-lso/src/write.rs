@@ -1,14 +1,17 @@
 //! Handles writing of LSO files
-use std::io::Write;
+use std::io::{self, Write};
 
 use cookie_factory::bytes::be_u32;
 use cookie_factory::combinator::cond;
 use cookie_factory::combinator::slice;
 use cookie_factory::gen;
 use cookie_factory::sequence::tuple;
-use cookie_factory::SerializeFn;
+use cookie_factory::{GenError, SerializeFn};
+use std::convert::TryFrom;
 
+use crate::amf0::write::AMF0Encoder;
 use crate::amf3::write::AMF3Encoder;
+use crate::errors::WriteError;
 use crate::nom_utils::write_string;
 use crate::types::{AMFVersion, Header, Lso};
 use crate::{FORMAT_VERSION_AMF0, FORMAT_VERSION_AMF3, HEADER_SIGNATURE, HEADER_VERSION, PADDING};
@@ -26,16 +29,58 @@ impl Writer {
         &'a mut self,
         lso: &'b Lso,
     ) -> impl SerializeFn<W> + 'a {
+        tuple((write_header(&lso.header), self.write_body(lso)))
+    }
+
+    /// Write a LSO to a vec of bytes with the encoders of this writer, with the length in the header set to match the
+    /// written data, see `write::to_bytes`
+    pub fn to_bytes(&mut self, lso: &Lso) -> Result<Vec<u8>, WriteError> {
+        let (mut buffer, _size) = gen(self.write_full(lso), Vec::new())?;
+
+        // The length covers every byte following the length field
+        let length_start = HEADER_VERSION.len();
+        let length =
+            u32::try_from(buffer.len() - (length_start + 4)).map_err(|_| WriteError::TooLarge)?;
+        buffer[length_start..length_start + 4].copy_from_slice(&length.to_be_bytes());
+
+        Ok(buffer)
+    }
+
+    /// Write a LSO to `w` with the encoders of this writer, without encoding it in memory first, see `write::write_to`
+    pub fn write_to<W: Write>(&mut self, w: &mut W, lso: &Lso) -> io::Result<()> {
+        let (_, size) = gen(self.write_full(lso), io::sink()).map_err(gen_error_to_io)?;
+
+        // The length covers every byte following the length field
+        let length = u32::try_from(size - (HEADER_VERSION.len() + 4) as u64)
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, WriteError::TooLarge))?;
+        let header = Header {
+            length,
+            name: lso.header.name.clone(),
+            format_version: lso.header.format_version,
+        };
+
+        gen(tuple((write_header(&header), self.write_body(lso))), w).map_err(gen_error_to_io)?;
+        Ok(())
+    }
+
+    fn write_body<'a, 'b: 'a, W: Write + 'a>(
+        &'a mut self,
+        lso: &'b Lso,
+    ) -> impl SerializeFn<W> + 'a {
+        // References can't point into another file, so the tables left by an earlier write are cleared
+        self.amf3_encoder.reset();
+        let amf3_encoder = &self.amf3_encoder;
         let amf0 = cond(
             lso.header.format_version == AMFVersion::AMF0,
-            crate::amf0::write::write_body(&lso.body),
+            // AMF0 reference indices are local to a single body, so each write needs a new encoder
+            move |out| AMF0Encoder::with_amf3_encoder(amf3_encoder).write_body(&lso.body)(out),
         );
         let amf3 = cond(
             lso.header.format_version == AMFVersion::AMF3,
-            self.amf3_encoder.write_body(&lso.body),
+            amf3_encoder.write_body(&lso.body),
         );
 
-        tuple((write_header(&lso.header), amf0, amf3))
+        tuple((amf0, amf3))
     }
 }
 
@@ -59,6 +104,46 @@ fn write_header<'a, 'b: 'a, W: Write + 'a>(header: &'b Header) -> impl Serialize
     ))
 }
 
+/// Write a LSO to a vec of bytes, with the length in the header set to match the written data
+/// Unlike `write_to_bytes` the existing `header.length` is ignored
+/// Fails with `WriteError::TooLarge` if the length doesn't fit in the header, or `WriteError::Encoding` if a value
+/// can't be encoded
+/// To write values that need registered encoders, such as flex types, use `Writer::to_bytes`
+pub fn to_bytes(lso: &Lso) -> Result<Vec<u8>, WriteError> {
+    Writer::default().to_bytes(lso)
+}
+
+/// Write a LSO to `w` without encoding it in memory first, with the length in the header set to match the written data
+/// As the length comes before the body, the lso is encoded twice, once to measure it and once to write it, so this
+/// trades time for memory compared to `to_bytes`. If the lso can't be encoded nothing is written
+/// Values are written in many small pieces, so unbuffered sinks such as a `File` should be wrapped in a `BufWriter`
+pub fn write_to<W: Write>(w: &mut W, lso: &Lso) -> io::Result<()> {
+    Writer::default().write_to(w, lso)
+}
+
+fn gen_error_to_io(error: GenError) -> io::Error {
+    match error {
+        GenError::IoError(e) => e,
+        e => io::Error::new(io::ErrorKind::InvalidData, WriteError::Encoding(e)),
+    }
+}
+
+/// Write a LSO as base64, with the length in the header set as in `to_bytes`, see `read::parse_base64`
+/// If `url_safe` is set the URL safe alphabet is used without padding, so the result can be put in a query string
+/// as is, otherwise the standard alphabet is used with padding
+#[cfg(feature = "base64")]
+pub fn to_base64(lso: &Lso, url_safe: bool) -> Result<String, WriteError> {
+    use base64::engine::general_purpose::{STANDARD, URL_SAFE_NO_PAD};
+    use base64::Engine;
+
+    let bytes = to_bytes(lso)?;
+    Ok(if url_safe {
+        URL_SAFE_NO_PAD.encode(bytes)
+    } else {
+        STANDARD.encode(bytes)
+    })
+}
+
 /// Write a LSO to a vec of bytes
 pub fn write_to_bytes(lso: &Lso) -> Vec<u8> {
     let v = vec![];
@@ -257,6 +257,7 @@ json_test_flex! {
 
 // As2 / amf0
 auto_test! {
+    [as2_amf3_switch, "AS2-AMF3Switch-Demo"],
     [as2_array, "AS2-Array-Demo"],
     [as2_boolean, "AS2-Boolean-Demo"],
     [as2_date, "AS2-Date-Demo"],
@@ -264,9 +265,11 @@ auto_test! {
     [as2_ecma_array, "AS2-ECMAArray-Demo"],
     [as2_integer, "AS2-Integer-Demo"],
     [as2_long_string, "AS2-LongString-Demo"],
+    [as2_nan, "AS2-NaN-Demo"],
     [as2_null, "AS2-Null-Demo"],
     [as2_number, "AS2-Number-Demo"],
     [as2_object, "AS2-Object-Demo"],
+    [as2_reference, "AS2-Reference-Demo"],
     [as2_string, "AS2-String-Demo"],
     [as2_typed_object, "AS2-TypedObject-Demo"],
     [as2_undefined, "AS2-Undefined-Demo"],
@@ -277,6 +280,7 @@ auto_test! {
 
 auto_test! {
     [as3_number, "AS3-Number-Demo"],
+    [as3_nan, "AS3-NaN-Demo"],
     [as3_boolean, "AS3-Boolean-Demo"],
     [as3_string, "AS3-String-Demo"],
     [as3_object, "AS3-Object-Demo"],
@@ -284,8 +288,10 @@ auto_test! {
     [as3_undefined, "AS3-Undefined-Demo"],
     [as3_strict_array, "AS3-Array-Demo"],
     [as3_date, "AS3-Date-Demo"],
+    [as3_date_reference, "AS3-DateReference-Demo"],
     [as3_xml, "AS3-XML-Demo"],
     [as3_xml_doc, "AS3-XMLDoc-Demo"],
+    [as3_xml_both, "AS3-XMLBoth-Demo"],
     [as3_typed_object, "AS3-TypedObject-Demo"],
     [as3_integer, "AS3-Integer-Demo"],
     [as3_byte_array, "AS3-ByteArray-Demo"],
@@ -294,7 +300,9 @@ auto_test! {
     [as3_vector_number, "AS3-VectorNumber-Demo"],
     [as3_vector_object, "AS3-VectorObject-Demo"],
     [as3_vector_typed_object, "AS3-VectorTypedObject-Demo"],
-    [as3_dictionary, "AS3-Dictionary-Demo"]
+    [as3_dictionary, "AS3-Dictionary-Demo"],
+    [header, "Header-Demo"],
+    [as3_repeated_names, "AS3-RepeatedNames-Demo"]
 }
 
 // Other tests, mixed
@@ -343,12 +351,3922 @@ test_parse_only! {
 }
 
 auto_test_flex! {
-    [opp_detail_prefs, "oppDetailPrefs"]
+    [opp_detail_prefs, "oppDetailPrefs"],
+    [flex_collections_demo, "Flex-Collections-Demo"]
 }
 
 should_fail! {
     // Corrupt/invalid file
     [two, "2",  nom::Err::Error(Error::Nom(vec![17, 112, 99, 95, 112, 97, 114, 116, 121, 10, 130, 51, 21, 80, 97, 114, 116, 121, 65, 108, 105, 97, 115, 0, 13, 98, 97, 116, 116, 108, 101, 2, 0].as_slice(), ErrorKind::Tag))],
     // OOB read
-    [zero_four, "00000004", nom::Err::Error(Error::Nom(vec![0, 255, 0, 0, 0, 86, 0, 84, 47, 117, 112, 108, 111, 97, 100, 115, 46, 117, 110, 103, 114, 111, 117, 110, 100, 101, 100, 46, 110, 101, 116, 47, 53, 57, 50, 48, 48, 48, 47, 53, 57, 50, 52, 55, 51, 95, 77, 97, 100, 110, 101, 115, 115, 71, 97, 109, 101, 95, 85, 76, 84, 73, 77, 65, 84, 69, 46, 115, 119, 102, 47, 97, 114, 101, 110, 97, 77, 97, 100, 110, 101, 115, 115, 71, 97, 109, 101, 50, 46, 115, 111, 108].as_slice(), ErrorKind::Eof))]
+    [zero_four, "00000004", nom::Err::Error(Error::Nom(vec![0, 255, 0, 0, 0, 86, 0, 84, 47, 117, 112, 108, 111, 97, 100, 115, 46, 117, 110, 103, 114, 111, 117, 110, 100, 101, 100, 46, 110, 101, 116, 47, 53, 57, 50, 48, 48, 48, 47, 53, 57, 50, 52, 55, 51, 95, 77, 97, 100, 110, 101, 115, 115, 71, 97, 109, 101, 95, 85, 76, 84, 73, 77, 65, 84, 69, 46, 115, 119, 102, 47, 97, 114, 101, 110, 97, 77, 97, 100, 110, 101, 115, 115, 71, 97, 109, 101, 50, 46, 115, 111, 108].as_slice(), ErrorKind::Eof))],
+    [bad_header_magic, "BadHeader-Magic", nom::Err::Error(Error::BadMagic)],
+    [bad_header_signature, "BadHeader-Signature", nom::Err::Error(Error::BadSignature)],
+    // Ends part way through the length of the name
+    [bad_header_truncated_name, "BadHeader-TruncatedName", nom::Err::Error(Error::Nom(vec![0].as_slice(), ErrorKind::Eof))]
+}
+
+#[test]
+fn shared_rc_is_written_as_reference() {
+    use flash_lso::types::{AMFVersion, Attribute, ClassDefinition, Element, Lso, Value};
+    use std::rc::Rc;
+
+    let class_def = ClassDefinition {
+        name: "".to_string(),
+        attributes: Attribute::Dynamic.into(),
+        static_properties: vec![],
+    };
+    let shared = Rc::new(Value::Object(
+        vec![Element::new("hp", Value::Integer(100))],
+        Some(class_def),
+    ));
+    let lso = Lso::new(
+        vec![
+            Element {
+                name: "a".into(),
+                value: Rc::clone(&shared),
+            },
+            Element {
+                name: "b".into(),
+                value: Rc::clone(&shared),
+            },
+        ],
+        "shared",
+        AMFVersion::AMF3,
+    );
+
+    let bytes = flash_lso::write::write_to_bytes(&lso);
+
+    // Name "b" followed by an object marker and a reference to object 0
+    let expected_reference = [0x03, b'b', 0x0A, 0x00];
+    assert!(bytes
+        .windows(expected_reference.len())
+        .any(|w| w == expected_reference));
+
+    let (_, parsed) = Reader::default().parse(&bytes).expect("Unable to parse");
+    assert_eq!(parsed.body, lso.body);
+    assert!(Rc::ptr_eq(&parsed.body[0].value, &parsed.body[1].value));
+}
+
+#[test]
+fn strict_reader_rejects_length_mismatch() {
+    let data = include_bytes!("sol/Minimal.sol");
+
+    let mut reader = Reader {
+        strict: true,
+        ..Reader::default()
+    };
+    assert!(reader.parse(data).is_ok());
+
+    let mut modified = data.to_vec();
+    modified[5] += 1;
+
+    assert_eq!(
+        reader.parse(&modified).unwrap_err(),
+        nom::Err::Error(Error::LengthMismatch {
+            declared: 57,
+            actual: 56
+        })
+    );
+
+    // Lenient by default
+    assert!(Reader::default().parse(&modified).is_ok());
+}
+
+#[test]
+fn as2_references_share_values() {
+    use flash_lso::types::Value;
+    use std::rc::Rc;
+
+    let data = include_bytes!("sol/AS2-Reference-Demo.sol");
+    let (_, sol) = Reader::default().parse(data).expect("Unable to parse");
+
+    let player = &sol.body[0].value;
+    assert!(Rc::ptr_eq(player, &sol.body[1].value));
+    if let Value::Object(elements, Some(class_def)) = player.as_ref() {
+        assert_eq!(class_def.name, "com.example.Player");
+        assert!(Rc::ptr_eq(&elements[1].value, &sol.body[2].value));
+    } else {
+        panic!("Expected typed object, got {:?}", player);
+    }
+
+    // Typed objects can have an empty class name
+    let anon = &sol.body[3].value;
+    assert!(
+        matches!(anon.as_ref(), Value::Object(_, Some(class_def)) if class_def.name.is_empty())
+    );
+
+    if let Value::StrictArray(items) = sol.body[4].value.as_ref() {
+        assert!(Rc::ptr_eq(&items[0], anon));
+        assert!(Rc::ptr_eq(&items[1], player));
+    } else {
+        panic!("Expected strict array, got {:?}", sol.body[4].value);
+    }
+}
+
+#[test]
+fn as2_self_reference_resolves_to_partial_object() {
+    use flash_lso::types::{Element, Value};
+
+    let data = include_bytes!("sol/AS2-SelfReference-Demo.sol");
+    let (_, sol) = Reader::default().parse(data).expect("Unable to parse");
+
+    // A value can't contain itself, so the reference resolves to the object as it was before its members were read
+    assert_eq!(
+        sol.body[0].value.as_ref(),
+        &Value::Object(
+            vec![
+                Element::new("name", Value::String("loop".to_string())),
+                Element::new("me", Value::Object(vec![], None)),
+            ],
+            None
+        )
+    );
+}
+
+#[test]
+fn as2_bad_reference_fails() {
+    let mut data = include_bytes!("sol/AS2-Reference-Demo.sol").to_vec();
+    // Point "current" at a reference index that doesn't exist yet
+    let position = data
+        .windows(3)
+        .position(|w| w == [0x07, 0x00, 0x00])
+        .expect("Reference not found");
+    data[position + 2] = 0x09;
+
+    assert!(Reader::default().parse(&data).is_err());
+}
+
+#[test]
+fn amf3_decoder_reset_keeps_external_decoders() {
+    use flash_lso::amf3::read::AMF3Decoder;
+    use std::rc::Rc;
+
+    let mut decoder = AMF3Decoder::default();
+    decoder.external_decoders.insert(
+        "test".to_string(),
+        Rc::new(Box::new(|i, _decoder| Ok((i, Vec::new())))),
+    );
+
+    let data = include_bytes!("sol/AS3-TypedObject-Demo.sol");
+    // Skip the magic, length, signature, name and padding / version bytes of the header
+    let body = &data[6 + 10 + 2 + "AS3-TypedObject-Demo".len() + 4..];
+
+    decoder.parse_body(body).expect("Unable to parse body");
+    assert!(!decoder.string_reference_table.is_empty());
+    assert!(!decoder.trait_reference_table.is_empty());
+    assert!(!decoder.object_reference_table.is_empty());
+
+    decoder.reset();
+    assert!(decoder.string_reference_table.is_empty());
+    assert!(decoder.trait_reference_table.is_empty());
+    assert!(decoder.object_reference_table.is_empty());
+    assert!(decoder.external_decoders.contains_key("test"));
+}
+
+#[test]
+fn reader_can_be_reused_across_files() {
+    let mut reader = Reader::default();
+    for data in [
+        &include_bytes!("sol/AS3-TypedObject-Demo.sol")[..],
+        &include_bytes!("sol/AS3-Object-Demo.sol")[..],
+    ] {
+        let (_, reused) = reader.parse(data).expect("Unable to parse");
+        let (_, fresh) = Reader::default().parse(data).expect("Unable to parse");
+        assert_eq!(reused, fresh);
+    }
+}
+
+#[test]
+fn amf3_class_stats() {
+    use flash_lso::amf3::read::ClassStats;
+
+    let data = include_bytes!("sol/AS3-VectorTypedObject-Demo.sol");
+
+    let mut reader = Reader::default();
+    reader.parse(data).expect("Unable to parse");
+    assert!(reader.amf3_decoder.class_stats().is_empty());
+
+    reader.amf3_decoder.record_class_stats = true;
+    reader.parse(data).expect("Unable to parse");
+    assert_eq!(
+        reader.amf3_decoder.class_stats().get("com.AS3SolTestClass"),
+        Some(&ClassStats {
+            definitions: 1,
+            references: 2
+        })
+    );
+}
+
+#[cfg(feature = "xml")]
+#[test]
+fn xml_values_can_be_parsed() {
+    use flash_lso::types::Value;
+
+    let data = include_bytes!("sol/AS3-XML-Demo.sol");
+    let (_, sol) = Reader::default().parse(data).expect("Unable to parse");
+    let value = &sol.body[0].value;
+    assert_eq!(value.is_xml_string(), Some(true));
+
+    let doc = value
+        .parse_xml()
+        .expect("Not an XML value")
+        .expect("Invalid XML");
+    assert_eq!(doc.root_element().tag_name().name(), "start");
+    assert_eq!(
+        doc.root_element()
+            .children()
+            .filter(|n| n.is_element())
+            .count(),
+        2
+    );
+
+    assert!(Value::XML("<a>".to_string(), false)
+        .parse_xml()
+        .expect("Not an XML value")
+        .is_err());
+    assert!(Value::Null.parse_xml().is_none());
+    assert_eq!(Value::Null.is_xml_string(), None);
+}
+
+#[test]
+fn elements_sort_by_name_stably() {
+    use flash_lso::types::{sort_elements_by_name, Element, Value};
+
+    let mut elements = vec![
+        Element::new("b", Value::Integer(1)),
+        Element::new("a", Value::Integer(2)),
+        Element::new("b", Value::Integer(3)),
+        Element::new("a", Value::Integer(4)),
+    ];
+    let object = Value::Object(elements.clone(), None);
+
+    let sorted: Vec<(&str, &Value)> = object
+        .sorted_elements()
+        .into_iter()
+        .map(|e| (e.name(), e.value.as_ref()))
+        .collect();
+    assert_eq!(
+        sorted,
+        vec![
+            ("a", &Value::Integer(2)),
+            ("a", &Value::Integer(4)),
+            ("b", &Value::Integer(1)),
+            ("b", &Value::Integer(3)),
+        ]
+    );
+    // The value itself is unchanged
+    assert_eq!(object, Value::Object(elements.clone(), None));
+
+    sort_elements_by_name(&mut elements);
+    assert_eq!(
+        elements,
+        object
+            .sorted_elements()
+            .into_iter()
+            .cloned()
+            .collect::<Vec<_>>()
+    );
+
+    assert!(Value::Integer(1).sorted_elements().is_empty());
+}
+
+#[test]
+fn parse_failure_offsets() {
+    use flash_lso::amf3::read::AMF3Decoder;
+
+    // The corrupt file fails 33 bytes before the end
+    let data = include_bytes!("sol/2.sol");
+    let (error, offset) = Reader::default().parse_with_offsets(data).unwrap_err();
+    assert_eq!(error, Error::Nom(&data[offset..], ErrorKind::Tag));
+    assert_eq!(offset, data.len() - 33);
+
+    let data = include_bytes!("sol/AS3-String-Demo.sol");
+    let body_start = 6 + 10 + 2 + "AS3-String-Demo".len() + 4;
+    assert!(AMF3Decoder::default()
+        .parse_with_offsets(&data[body_start..])
+        .is_ok());
+
+    // Truncating the body fails at the point the input runs out
+    let truncated = &data[body_start..data.len() - 3];
+    assert_eq!(
+        AMF3Decoder::default()
+            .parse_with_offsets(truncated)
+            .unwrap_err(),
+        (Error::OutOfBounds, truncated.len())
+    );
+}
+
+#[test]
+fn amf3_max_collection_len() {
+    use flash_lso::amf3::read::AMF3Decoder;
+
+    let data = include_bytes!("sol/AS3-VectorInt-Demo.sol");
+    // The value of "myVectorIntFixed", a vector of 4 ints
+    let name_end = data
+        .windows(5)
+        .position(|w| w == b"Fixed")
+        .expect("Element not found")
+        + 5;
+    let vector = &data[name_end..];
+
+    let mut decoder = AMF3Decoder::default();
+    decoder.max_collection_len = Some(3);
+    assert!(matches!(
+        decoder.parse_single_element(vector),
+        Err(nom::Err::Error(Error::Nom(_, ErrorKind::TooLarge)))
+    ));
+
+    decoder.max_collection_len = Some(4);
+    assert!(decoder.parse_single_element(vector).is_ok());
+
+    // Unlimited by default
+    assert!(AMF3Decoder::default().parse_single_element(vector).is_ok());
+}
+
+#[test]
+fn to_bytes_sets_header_length() {
+    for data in [
+        &include_bytes!("sol/AS2-Demo.sol")[..],
+        &include_bytes!("sol/AS3-TypedObject-Demo.sol")[..],
+    ] {
+        let (_, mut sol) = Reader::default().parse(data).expect("Unable to parse");
+        assert_eq!(
+            flash_lso::write::to_bytes(&sol).expect("Unable to write"),
+            data.to_vec()
+        );
+
+        sol.header.length = 0;
+        let bytes = flash_lso::write::to_bytes(&sol).expect("Unable to write");
+        assert_eq!(bytes, data.to_vec());
+
+        let mut reader = Reader {
+            strict: true,
+            ..Reader::default()
+        };
+        assert!(reader.parse(&bytes).is_ok());
+    }
+}
+
+#[cfg(feature = "flex")]
+#[test]
+fn writer_to_bytes_uses_its_encoders() {
+    use flash_lso::amf3::read::AMF3Decoder;
+    use flash_lso::amf3::write::AMF3Encoder;
+    use flash_lso::errors::WriteError;
+    use flash_lso::write::Writer;
+
+    // Flex collections can only be written by a writer with their encoders registered
+    let data = include_bytes!("sol/Flex-Collections-Demo.sol");
+    let mut reader = Reader {
+        amf3_decoder: AMF3Decoder::with_flex_types(),
+        ..Reader::default()
+    };
+    let (_, lso) = reader.parse(data).expect("Unable to parse");
+    assert!(matches!(
+        flash_lso::write::to_bytes(&lso),
+        Err(WriteError::Encoding(_))
+    ));
+
+    let mut writer = Writer {
+        amf3_encoder: AMF3Encoder::with_flex_types(),
+    };
+    assert_eq!(writer.to_bytes(&lso).unwrap(), data.to_vec());
+
+    let mut writer = Writer {
+        amf3_encoder: AMF3Encoder::with_flex_types(),
+    };
+    let mut out = Vec::new();
+    writer.write_to(&mut out, &lso).unwrap();
+    assert_eq!(out, data.to_vec());
+}
+
+#[cfg(feature = "flex")]
+#[test]
+fn flex_values_embedded_in_amf0_round_trip() {
+    use flash_lso::amf3::read::AMF3Decoder;
+    use flash_lso::amf3::write::AMF3Encoder;
+    use flash_lso::types::{AMFVersion, Element, Value};
+    use flash_lso::write::Writer;
+
+    // Embedded AMF3 values are read and written with the reader's and writer's AMF3 decoder and encoder
+    let data = include_bytes!("sol/Flex-Collections-Demo.sol");
+    let mut reader = Reader {
+        amf3_decoder: AMF3Decoder::with_flex_types(),
+        ..Reader::default()
+    };
+    let (_, mut lso) = reader.parse(data).expect("Unable to parse");
+    lso.header.format_version = AMFVersion::AMF0;
+    lso.body = lso
+        .body
+        .iter()
+        .map(|e| Element::new(e.name.as_ref(), Value::AMF3(e.value.clone())))
+        .collect();
+
+    let mut writer = Writer {
+        amf3_encoder: AMF3Encoder::with_flex_types(),
+    };
+    let bytes = writer.to_bytes(&lso).unwrap();
+    let (_, read_back) = reader.parse(&bytes).expect("Unable to parse");
+    assert_eq!(read_back.body, lso.body);
+}
+
+#[test]
+fn writer_can_be_reused() {
+    use flash_lso::write::Writer;
+
+    let data = include_bytes!("sol/AS3-TypedObject-Demo.sol");
+    let (_, sol) = Reader::default().parse(data).expect("Unable to parse");
+
+    let mut writer = Writer::default();
+    for _ in 0..2 {
+        assert_eq!(writer.to_bytes(&sol).unwrap(), data.to_vec());
+    }
+}
+
+#[test]
+fn as2_embedded_amf3_values() {
+    use flash_lso::types::{Element, Value};
+    use std::rc::Rc;
+
+    let data = include_bytes!("sol/AS2-AMF3Switch-Demo.sol");
+    let (_, sol) = Reader::default().parse(data).expect("Unable to parse");
+
+    // Both values resolve their string reference against their own table
+    for e in &sol.body[..2] {
+        if let Value::AMF3(inner) = e.value.as_ref() {
+            assert!(matches!(inner.as_ref(), Value::Object(elements, _)
+                if elements == &vec![Element::new("name", Value::String("name".to_string()))]));
+        } else {
+            panic!("Expected AMF3 value, got {:?}", e.value);
+        }
+    }
+
+    assert_eq!(
+        sol.body[2].value.as_ref(),
+        &Value::AMF3(Rc::new(Value::Integer(127)))
+    );
+    assert_eq!(
+        sol.body[3].value.as_ref(),
+        &Value::Object(
+            vec![Element::new(
+                "list",
+                Value::AMF3(Rc::new(Value::StrictArray(vec![
+                    Rc::new(Value::String("x".to_string())),
+                    Rc::new(Value::String("x".to_string())),
+                ])))
+            )],
+            None
+        )
+    );
+}
+
+#[test]
+fn value_len() {
+    use flash_lso::types::{Element, Value};
+    use std::rc::Rc;
+
+    assert_eq!(Value::Number(1.0).len(), None);
+    assert_eq!(Value::String("abc".to_string()).is_empty(), None);
+    assert_eq!(Value::ByteArray(vec![1, 2]).len(), None);
+
+    assert_eq!(Value::Object(vec![], None).is_empty(), Some(true));
+    assert_eq!(
+        Value::ECMAArray(
+            vec![Rc::new(Value::Null)],
+            vec![
+                Element::new("a", Value::Null),
+                Element::new("b", Value::Null)
+            ],
+            2
+        )
+        .len(),
+        Some(3)
+    );
+    assert_eq!(Value::VectorDouble(vec![1.0, 2.0], false).len(), Some(2));
+    assert_eq!(
+        Value::Dictionary(vec![(Rc::new(Value::Null), Rc::new(Value::Null))], false).len(),
+        Some(1)
+    );
+    assert_eq!(
+        Value::Custom(
+            vec![Element::new("a", Value::Null)],
+            vec![Element::new("b", Value::Null)],
+            None
+        )
+        .is_empty(),
+        Some(false)
+    );
+    assert_eq!(
+        Value::AMF3(Rc::new(Value::StrictArray(vec![Rc::new(Value::Null)]))).len(),
+        Some(1)
+    );
+}
+
+#[test]
+fn as3_date_by_reference() {
+    use flash_lso::types::Value;
+    use std::rc::Rc;
+
+    let data = include_bytes!("sol/AS3-DateReference-Demo.sol");
+    let (_, sol) = Reader::default().parse(data).expect("Unable to parse");
+
+    // The second date is a reference to the first, rather than another 8 byte date
+    assert_eq!(
+        sol.body[0].value.as_ref(),
+        &Value::Date(1406680830523.0, None)
+    );
+    assert!(Rc::ptr_eq(&sol.body[0].value, &sol.body[1].value));
+}
+
+#[test]
+fn element_from_native_types() {
+    use flash_lso::types::{Element, Value};
+
+    assert_eq!(*Element::new("a", 1.5).value, Value::Number(1.5));
+    assert_eq!(*Element::new("a", 100i32).value, Value::Integer(100));
+    assert_eq!(
+        *Element::new("a", 1i64 << 40).value,
+        Value::Number(1099511627776.0)
+    );
+    assert_eq!(*Element::new("a", true).value, Value::Bool(true));
+    assert_eq!(
+        *Element::new("a", "x").value,
+        Value::String("x".to_string())
+    );
+    assert_eq!(
+        *Element::new("a", "x".to_string()).value,
+        Value::String("x".to_string())
+    );
+    assert_eq!(
+        *Element::new("a", vec![1u8, 2]).value,
+        Value::ByteArray(vec![1, 2])
+    );
+}
+
+#[derive(Default)]
+struct EventLog(Vec<String>);
+
+impl flash_lso::amf3::visit::Amf3Visitor for EventLog {
+    fn on_property(&mut self, name: &str) {
+        self.0.push(format!("{}:", name));
+    }
+    fn on_object_start(&mut self, class_def: &flash_lso::types::ClassDefinition) {
+        self.0.push(format!("{{{}", class_def.name));
+    }
+    fn on_object_end(&mut self) {
+        self.0.push("}".to_string());
+    }
+    fn on_vector_object_start(&mut self, len: usize, type_name: &str, fixed_length: bool) {
+        self.0
+            .push(format!("<{} {} {}", len, type_name, fixed_length));
+    }
+    fn on_vector_object_end(&mut self) {
+        self.0.push(">".to_string());
+    }
+    fn on_reference(&mut self, index: usize) {
+        self.0.push(format!("ref {}", index));
+    }
+    fn on_integer(&mut self, value: i32) {
+        self.0.push(value.to_string());
+    }
+    fn on_number(&mut self, value: f64) {
+        self.0.push(value.to_string());
+    }
+    fn on_string(&mut self, value: &str) {
+        self.0.push(format!("{:?}", value));
+    }
+    fn on_date(&mut self, millis: f64) {
+        self.0.push(format!("date {}", millis));
+    }
+}
+
+#[test]
+fn amf3_visitor_events() {
+    use flash_lso::amf3::read::AMF3Decoder;
+
+    let data = include_bytes!("sol/AS3-Object-Demo.sol");
+    let body = &data[6 + 10 + 2 + "AS3-Object-Demo".len() + 4..];
+    let mut log = EventLog::default();
+    let (rest, _) = AMF3Decoder::default()
+        .parse_with_visitor(body, &mut log)
+        .expect("Unable to parse");
+    assert!(rest.is_empty());
+    assert_eq!(
+        log.0,
+        vec![
+            "myObject:",
+            "{",
+            "p5:",
+            "date 1409704396759",
+            "p3:",
+            "3.141592653589793",
+            "p4:",
+            "{",
+            "prop:",
+            "\"val\"",
+            "}",
+            "p1:",
+            "5",
+            "p2:",
+            "\"hallo\"",
+            "}"
+        ]
+    );
+
+    let data = include_bytes!("sol/AS3-VectorTypedObject-Demo.sol");
+    let body = &data[6 + 10 + 2 + "AS3-VectorTypedObject-Demo".len() + 4..];
+    let mut log = EventLog::default();
+    AMF3Decoder::default()
+        .parse_with_visitor(body, &mut log)
+        .expect("Unable to parse");
+    assert_eq!(
+        log.0,
+        vec![
+            "myVectorTypedObject:",
+            "<3 com.AS3SolTestClass true",
+            "{com.AS3SolTestClass",
+            "foo:",
+            "1",
+            "}",
+            "{com.AS3SolTestClass",
+            "foo:",
+            "2",
+            "}",
+            "{com.AS3SolTestClass",
+            "foo:",
+            "3",
+            "}",
+            ">"
+        ]
+    );
+
+    // The second date is a reference to the first value
+    let data = include_bytes!("sol/AS3-DateReference-Demo.sol");
+    let body = &data[6 + 10 + 2 + "AS3-DateReference-Demo".len() + 4..];
+    let mut log = EventLog::default();
+    AMF3Decoder::default()
+        .parse_with_visitor(body, &mut log)
+        .expect("Unable to parse");
+    assert_eq!(
+        log.0,
+        vec!["created:", "date 1406680830523", "modified:", "ref 0"]
+    );
+}
+
+#[test]
+fn amf3_lossy_strings() {
+    use flash_lso::amf3::read::{AMF3Decoder, StringMode};
+    use flash_lso::types::Value;
+
+    // "a" is a Latin-1 string, "b" is a reference to the same string
+    let body = [
+        0x03, b'a', 0x06, 0x05, 0xe9, b'x', 0x00, 0x03, b'b', 0x06, 0x02, 0x00,
+    ];
+
+    assert!(AMF3Decoder::default().parse_body(&body).is_err());
+
+    let mut decoder = AMF3Decoder::default();
+    decoder.string_decoding = StringMode::Lossy;
+    let (rest, elements) = decoder.parse_body(&body).unwrap();
+    assert!(rest.is_empty());
+    assert_eq!(elements.len(), 2);
+    assert_eq!(*elements[0].value, Value::String("\u{fffd}x".to_string()));
+    assert_eq!(*elements[1].value, Value::String("\u{fffd}x".to_string()));
+    assert_eq!(decoder.lossy_string_offsets(), &[3, 10]);
+
+    decoder.reset();
+    assert!(decoder.lossy_string_offsets().is_empty());
+}
+
+#[test]
+fn amf3_offsets_of_single_value() {
+    use flash_lso::amf3::read::{AMF3Decoder, StringMode};
+
+    let mut decoder = AMF3Decoder::default();
+    decoder.string_decoding = StringMode::Lossy;
+
+    // An array of a Latin-1 string and null, offsets are relative to the start of the value
+    let value = [0x09, 0x05, 0x01, 0x06, 0x05, 0xe9, b'x', 0x01];
+    decoder.parse_single_element(&value).unwrap();
+    decoder.parse_single_element(&value).unwrap();
+    assert_eq!(decoder.lossy_string_offsets(), &[4, 4]);
+}
+
+#[test]
+fn value_display() {
+    use flash_lso::types::{Element, Value};
+    use std::rc::Rc;
+
+    let value = Value::Object(
+        vec![
+            Element::new("hp", 100),
+            Element::new("name", "x"),
+            Element::new(
+                "items",
+                Value::StrictArray(vec![Rc::new(Value::Number(1.5)), Rc::new(Value::Null)]),
+            ),
+            Element::new("data", vec![1u8, 2, 3]),
+            Element::new("when", Value::Date(1409704396759.0, None)),
+            Element::new(
+                "dict",
+                Value::Dictionary(
+                    vec![(Rc::new(Value::from("k")), Rc::new(Value::Bool(true)))],
+                    false,
+                ),
+            ),
+            Element::new(
+                "assoc",
+                Value::ECMAArray(
+                    vec![Rc::new(Value::Integer(1))],
+                    vec![Element::new("a", Value::VectorInt(vec![1, -2], false))],
+                    1,
+                ),
+            ),
+        ],
+        None,
+    );
+    assert_eq!(
+        value.to_string(),
+        "{hp: 100, name: \"x\", items: [1.5, null], data: <3 bytes>, when: 1409704396759, dict: {\"k\": true}, assoc: [1, a: [1, -2]]}"
+    );
+
+    // Deeply nested values are truncated
+    let mut nested = Value::StrictArray(vec![]);
+    for _ in 0..20 {
+        nested = Value::StrictArray(vec![Rc::new(nested)]);
+    }
+    assert_eq!(nested.to_string(), "[[[[[[[[[...]]]]]]]]]");
+}
+
+#[test]
+fn walk_mut_renames_nested_property() {
+    use flash_lso::types::{AMFVersion, Element, Lso, Value};
+    use std::rc::Rc;
+
+    let data = include_bytes!("sol/AS3-Object-Demo.sol");
+    let (_, mut sol) = Reader::default()
+        .parse(data)
+        .expect("Failed to parse lso file");
+
+    let mut paths = Vec::new();
+    sol.walk_mut(|path, value| {
+        paths.push(path.to_string());
+        if path == "myObject.p4" {
+            if let Value::Object(elements, _) = value {
+                elements[0].name = "renamed".into();
+            }
+        }
+    });
+
+    assert_eq!(
+        paths,
+        [
+            "myObject",
+            "myObject.p5",
+            "myObject.p3",
+            "myObject.p4",
+            "myObject.p4.renamed",
+            "myObject.p1",
+            "myObject.p2"
+        ]
+    );
+
+    let p4 = match &*sol.body[0].value {
+        Value::Object(elements, _) => Rc::clone(&elements[2].value),
+        v => panic!("Expected an object, got {:?}", v),
+    };
+    match &*p4 {
+        Value::Object(elements, _) => assert_eq!(elements, &[Element::new("renamed", "val")]),
+        v => panic!("Expected an object, got {:?}", v),
+    }
+
+    // Shared values are cloned before being visited
+    let shared = Rc::new(Value::Integer(1));
+    let mut lso = Lso::new(
+        vec![
+            Element {
+                name: "a".into(),
+                value: Rc::clone(&shared),
+            },
+            Element {
+                name: "b".into(),
+                value: Rc::clone(&shared),
+            },
+        ],
+        "test",
+        AMFVersion::AMF3,
+    );
+    lso.walk_mut(|path, value| {
+        if path == "a" {
+            *value = Value::Integer(2);
+        }
+    });
+    assert_eq!(*lso.body[0].value, Value::Integer(2));
+    assert_eq!(*lso.body[1].value, Value::Integer(1));
+    assert_eq!(*shared, Value::Integer(1));
+}
+
+#[cfg(feature = "compression")]
+#[test]
+fn parse_compressed_files() {
+    use flash_lso::read::{parse_maybe_compressed, Compression};
+
+    let (_, expected) = Reader::default()
+        .parse(include_bytes!("sol/AS2-Demo.sol"))
+        .expect("Failed to parse lso file");
+
+    for (data, compression) in [
+        (
+            &include_bytes!("sol/AS2-Demo-Zlib.sol")[..],
+            Some(Compression::Zlib),
+        ),
+        (
+            &include_bytes!("sol/AS2-Demo-Gzip.sol")[..],
+            Some(Compression::Gzip),
+        ),
+        (&include_bytes!("sol/AS2-Demo.sol")[..], None),
+    ] {
+        let (lso, found) = parse_maybe_compressed(data).expect("Failed to parse lso file");
+        assert_eq!(found, compression);
+        assert_eq!(lso, expected);
+    }
+
+    // A valid zlib header that doesn't decompress is parsed as is, and fails
+    assert!(parse_maybe_compressed(&[0x78, 0x9c, 0xff, 0xff]).is_err());
+}
+
+#[test]
+fn class_definition_compatibility() {
+    use flash_lso::types::{Attribute, ClassDefinition};
+
+    let def = ClassDefinition {
+        name: "Player".to_string(),
+        attributes: Attribute::Dynamic.into(),
+        static_properties: vec!["hp".to_string(), "name".to_string()],
+    };
+    assert!(def.is_compatible_with(&def.clone()));
+
+    let mut renamed = def.clone();
+    renamed.name = "Enemy".to_string();
+    assert!(!def.is_compatible_with(&renamed));
+
+    let mut reordered = def.clone();
+    reordered.static_properties.reverse();
+    assert!(!def.is_compatible_with(&reordered));
+
+    let mut sealed = def.clone();
+    sealed.attributes.clear();
+    assert!(!def.is_compatible_with(&sealed));
+}
+
+#[test]
+fn amf0_ecma_array_count_mismatch() {
+    use flash_lso::amf0::read::AMF0Decoder;
+    use flash_lso::types::{AMFVersion, Element, Lso, Value};
+
+    // "arr" is an ECMA array that declares 0 elements but contains "a" = 1.0 and "b" = true
+    let body = [
+        0x00, 0x03, b'a', b'r', b'r', 0x08, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01, b'a', 0x00, 0x3f,
+        0xf0, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01, b'b', 0x01, 0x01, 0x00, 0x00, 0x09,
+        0x00,
+    ];
+
+    let (rest, elements) = AMF0Decoder::default().parse_body(&body).unwrap();
+    assert!(rest.is_empty());
+    assert_eq!(
+        elements,
+        vec![Element::new(
+            "arr",
+            Value::ECMAArray(
+                vec![],
+                vec![Element::new("a", 1.0), Element::new("b", true)],
+                0
+            )
+        )]
+    );
+
+    // The declared count is kept when writing
+    let lso = Lso::new(elements, "test", AMFVersion::AMF0);
+    let bytes = flash_lso::write::to_bytes(&lso).unwrap();
+    assert!(bytes.ends_with(&body));
+}
+
+#[test]
+fn amf3_collected_class_defs() {
+    let data = include_bytes!("sol/AS3-VectorTypedObject-Demo.sol");
+    let mut reader = Reader::default();
+    reader.parse(data).expect("Failed to parse lso file");
+
+    let defs = reader.amf3_decoder.collected_class_defs();
+    let names: Vec<&str> = defs.iter().map(|def| def.name.as_str()).collect();
+    assert_eq!(names, ["com.AS3SolTestClass"]);
+    assert_eq!(defs[0].static_properties, ["foo"]);
+}
+
+#[test]
+fn amf3_xml_markers_round_trip() {
+    use flash_lso::types::Value;
+
+    let data = include_bytes!("sol/AS3-XMLBoth-Demo.sol");
+    let (_, sol) = Reader::default()
+        .parse(data)
+        .expect("Failed to parse lso file");
+
+    assert_eq!(sol.body[0].value.is_xml_string(), Some(true));
+    assert_eq!(sol.body[1].value.is_xml_string(), Some(false));
+
+    let written = flash_lso::write::to_bytes(&sol).unwrap();
+    let marker_after = |name: &[u8]| {
+        let start = written
+            .windows(name.len())
+            .position(|w| w == name)
+            .expect("Element not found");
+        written[start + name.len()]
+    };
+    // XMLString is 0x0B and XMLDocument is 0x07
+    assert_eq!(marker_after(b"myXML"), 0x0b);
+    assert_eq!(marker_after(b"myXMLDoc"), 0x07);
+
+    let (_, reread) = Reader::default().parse(&written).unwrap();
+    assert!(matches!(*reread.body[1].value, Value::XML(_, false)));
+}
+
+#[test]
+fn iterate_lso_and_object_elements() {
+    use flash_lso::types::{Element, Value};
+
+    let data = include_bytes!("sol/AS2-Object-Demo.sol");
+    let (_, sol) = Reader::default()
+        .parse(data)
+        .expect("Failed to parse lso file");
+
+    let names: Vec<&str> = (&sol).into_iter().map(|e| e.name()).collect();
+    assert_eq!(names, sol.body.iter().map(|e| e.name()).collect::<Vec<_>>());
+
+    let mut count = 0;
+    for element in &sol {
+        if let Some(elements) = element.value().elements() {
+            count += elements.len();
+        }
+    }
+    assert!(count > 0);
+
+    let elements = vec![Element::new("a", Value::Integer(1))];
+    let object = Value::Object(elements.clone(), None);
+    assert_eq!(object.elements(), Some(elements.as_slice()));
+    assert_eq!(
+        Value::AMF3(std::rc::Rc::new(object)).elements(),
+        Some(elements.as_slice())
+    );
+    let custom = Value::Custom(Vec::new(), elements.clone(), None);
+    assert_eq!(custom.elements(), Some(elements.as_slice()));
+    assert_eq!(Value::StrictArray(Vec::new()).elements(), None);
+}
+
+#[test]
+fn content_hash_is_canonical() {
+    use flash_lso::types::{AMFVersion, Element, Lso, Value};
+    use std::rc::Rc;
+
+    let data = include_bytes!("sol/AS2-Demo.sol");
+    let (_, sol) = Reader::default()
+        .parse(data)
+        .expect("Failed to parse lso file");
+    let written = flash_lso::write::to_bytes(&sol).unwrap();
+    let (_, reread) = Reader::default().parse(&written).unwrap();
+    assert_eq!(sol.content_hash(false), reread.content_hash(false));
+
+    let a = Lso::new(
+        vec![
+            Element::new("x", Value::Integer(1)),
+            Element::new("y", Value::ByteArray(vec![1, 2, 3])),
+        ],
+        "a",
+        AMFVersion::AMF3,
+    );
+    let b = Lso::new(
+        vec![
+            Element::new("y", Value::ByteArray(vec![1, 2, 3])),
+            Element::new("x", Value::Number(1.0)),
+        ],
+        "b",
+        AMFVersion::AMF0,
+    );
+    assert_ne!(a.content_hash(false), b.content_hash(false));
+    assert_eq!(a.content_hash(true), b.content_hash(true));
+
+    let c = Lso::new(
+        vec![
+            Element::new("x", Value::Integer(1)),
+            Element::new("y", Value::ByteArray(vec![1, 2, 4])),
+        ],
+        "a",
+        AMFVersion::AMF3,
+    );
+    assert_ne!(a.content_hash(true), c.content_hash(true));
+
+    let date = |tz| {
+        Lso::new(
+            vec![Element::new("d", Value::Date(0.0, tz))],
+            "d",
+            AMFVersion::AMF0,
+        )
+    };
+    assert_eq!(
+        date(None).content_hash(false),
+        date(None).content_hash(false)
+    );
+    assert_ne!(
+        date(None).content_hash(false),
+        date(Some(0)).content_hash(false)
+    );
+
+    // A shared value hashes the same as equal copies, and is only hashed once, so this doesn't hash 2^64 values
+    let doubled = |depth, share| {
+        let mut value = Value::Null;
+        for _ in 0..depth {
+            let previous = Rc::new(value);
+            let other = if share {
+                Rc::clone(&previous)
+            } else {
+                Rc::new(previous.as_ref().clone())
+            };
+            value = Value::StrictArray(vec![previous, other]);
+        }
+        Lso::new(vec![Element::new("v", value)], "v", AMFVersion::AMF3)
+    };
+    assert_eq!(
+        doubled(8, true).content_hash(false),
+        doubled(8, false).content_hash(false)
+    );
+    assert_ne!(
+        doubled(64, true).content_hash(false),
+        doubled(63, true).content_hash(false)
+    );
+}
+
+#[test]
+fn as3_self_reference_resolves_to_partial_object() {
+    use flash_lso::types::{Attribute, ClassDefinition, Element, Value};
+    use std::rc::Rc;
+
+    let data = include_bytes!("sol/AS3-SelfReference-Demo.sol");
+    let (_, sol) = Reader::default().parse(data).expect("Unable to parse");
+    let class_def = ClassDefinition {
+        attributes: Attribute::Dynamic.into(),
+        ..ClassDefinition::default_with_name(String::new())
+    };
+
+    // References made while a value is being read resolve to it as it was before its contents were read
+    assert_eq!(
+        sol.body[0].value.as_ref(),
+        &Value::Object(
+            vec![
+                Element::new("name", Value::String("loop".to_string())),
+                Element::new("me", Value::Object(vec![], Some(class_def.clone()))),
+                Element::new("list", Value::StrictArray(vec![Rc::new(Value::Null)])),
+            ],
+            Some(class_def)
+        )
+    );
+}
+
+#[test]
+fn as3_self_references_round_trip() {
+    use flash_lso::write::to_bytes;
+
+    // The partial objects that references made while reading resolve to must still be written as valid objects
+    for data in [
+        &include_bytes!("sol/AS3-LinkedList-Demo.sol")[..],
+        &include_bytes!("sol/AS3-SelfReference-Demo.sol")[..],
+    ] {
+        let (_, sol) = Reader::default().parse(data).expect("Unable to parse");
+        let bytes = to_bytes(&sol).expect("Unable to write");
+        let (_, reread) = Reader::default()
+            .parse(&bytes)
+            .expect("Unable to parse written file");
+        assert_eq!(
+            to_bytes(&reread).expect("Unable to write again"),
+            bytes,
+            "Writing the re-read file should not change it"
+        );
+    }
+}
+
+#[test]
+fn amf3_object_static_and_dynamic_members() {
+    use enumset::EnumSet;
+    use flash_lso::amf3::read::AMF3Decoder;
+    use flash_lso::types::{Attribute, ClassDefinition, Element, Value};
+
+    let parse = |data: &[u8]| {
+        let (rest, value) = AMF3Decoder::default()
+            .parse_single_element(data)
+            .expect("Unable to parse object");
+        assert!(rest.is_empty());
+        value.as_ref().clone()
+    };
+    let class_def = |name: &str, attributes: EnumSet<Attribute>, static_properties: &[&str]| {
+        Some(ClassDefinition {
+            name: name.to_string(),
+            attributes,
+            static_properties: static_properties.iter().map(|s| s.to_string()).collect(),
+        })
+    };
+
+    // Sealed class `A` with a static property `x`
+    assert_eq!(
+        parse(&[0x0a, 0x13, 0x03, b'A', 0x03, b'x', 0x04, 0x01]),
+        Value::Object(
+            vec![Element::new("x", Value::Integer(1))],
+            class_def("A", EnumSet::empty(), &["x"])
+        )
+    );
+    // Anonymous dynamic object with a member `y`
+    assert_eq!(
+        parse(&[0x0a, 0x0b, 0x01, 0x03, b'y', 0x04, 0x02, 0x01]),
+        Value::Object(
+            vec![Element::new("y", Value::Integer(2))],
+            class_def("", Attribute::Dynamic.into(), &[])
+        )
+    );
+    // Dynamic class `B` with a static property `x` and a member `y`
+    assert_eq!(
+        parse(&[0x0a, 0x1b, 0x03, b'B', 0x03, b'x', 0x04, 0x01, 0x03, b'y', 0x04, 0x02, 0x01]),
+        Value::Object(
+            vec![
+                Element::new("x", Value::Integer(1)),
+                Element::new("y", Value::Integer(2))
+            ],
+            class_def("B", Attribute::Dynamic.into(), &["x"])
+        )
+    );
+    // Empty sealed and dynamic objects, only the dynamic one has an end marker
+    assert_eq!(
+        parse(&[0x0a, 0x03, 0x01]),
+        Value::Object(vec![], class_def("", EnumSet::empty(), &[]))
+    );
+    assert_eq!(
+        parse(&[0x0a, 0x0b, 0x01, 0x01]),
+        Value::Object(vec![], class_def("", Attribute::Dynamic.into(), &[]))
+    );
+}
+
+#[cfg(feature = "indexmap")]
+#[test]
+fn value_as_map() {
+    use flash_lso::types::{Element, Value};
+    use std::rc::Rc;
+
+    let array = Value::ECMAArray(
+        vec![Rc::new(Value::Integer(0))],
+        vec![
+            Element::new("b", Value::Integer(1)),
+            Element::new("a", Value::Integer(2)),
+            Element::new("b", Value::Integer(3)),
+        ],
+        1,
+    );
+    let map = array.as_map().expect("Not a map");
+    // Duplicate names keep the position of the first and the value of the last, the dense part is ignored
+    assert_eq!(
+        map.iter()
+            .map(|(k, v)| (k.as_str(), v.as_ref()))
+            .collect::<Vec<_>>(),
+        vec![("b", &Value::Integer(3)), ("a", &Value::Integer(2))]
+    );
+
+    let object = Value::Object(vec![Element::new("x", "y")], None);
+    assert_eq!(
+        Value::AMF3(Rc::new(object)).as_map().unwrap()["x"].as_ref(),
+        &Value::from("y")
+    );
+    assert!(Value::StrictArray(vec![]).as_map().is_none());
+}
+
+#[test]
+fn amf3_lenient_markers() {
+    use flash_lso::amf3::read::AMF3Decoder;
+    use flash_lso::types::{Element, Value};
+
+    // `a` has the unknown marker 0x20, `b` is the integer 5
+    let body = [0x03, b'a', 0x20, 0x00, 0x03, b'b', 0x04, 0x05, 0x00];
+
+    assert!(AMF3Decoder::default().parse_body(&body).is_err());
+
+    let mut decoder = AMF3Decoder::default();
+    decoder.lenient_markers = true;
+    let (rest, elements) = decoder.parse_body(&body).expect("Unable to parse");
+    assert!(rest.is_empty());
+    assert_eq!(
+        elements,
+        vec![
+            Element::new("a", Value::Unsupported),
+            Element::new("b", Value::Integer(5))
+        ]
+    );
+    assert_eq!(decoder.unknown_markers(), &[(2, 0x20)]);
+
+    #[derive(Default)]
+    struct Markers(Vec<u8>);
+    impl flash_lso::amf3::visit::Amf3Visitor for Markers {
+        fn on_unknown_marker(&mut self, marker: u8) {
+            self.0.push(marker);
+        }
+    }
+    let mut markers = Markers::default();
+    decoder.parse_with_visitor(&body, &mut markers).unwrap();
+    assert_eq!(markers.0, vec![0x20]);
+}
+
+#[test]
+fn amf3_u29_and_i29_round_trip() {
+    use cookie_factory::gen;
+    use flash_lso::amf3::{read_i29, read_u29, write_i29, write_u29};
+
+    for &n in &[
+        0,
+        0x7f,
+        0x80,
+        0x3fff,
+        0x4000,
+        0x1f_ffff,
+        0x20_0000,
+        0x1fff_ffff,
+    ] {
+        let (bytes, _) = gen(write_u29(n), Vec::new()).unwrap();
+        assert_eq!(read_u29(&bytes).unwrap(), (&[][..], n));
+    }
+    for &n in &[0, 1, -1, 0x3fff, -0x4000, 0x0fff_ffff, -0x1000_0000] {
+        let (bytes, _) = gen(write_i29(n), Vec::new()).unwrap();
+        assert_eq!(read_i29(&bytes).unwrap(), (&[][..], n));
+    }
+
+    // Each of the first 3 bytes gives 7 bits, the 4th gives 8
+    assert_eq!(read_u29(&[0x81, 0x00]).unwrap().1, 0x80);
+    assert_eq!(read_u29(&[0xff, 0xff, 0xff, 0xff]).unwrap().1, 0x1fff_ffff);
+    assert!(read_u29(&[0x81]).is_err());
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn serde_round_trip_unshares_values() {
+    use flash_lso::types::{AMFVersion, Element, Lso, Value};
+    use std::rc::Rc;
+
+    let data = include_bytes!("sol/AS3-Object-Demo.sol");
+    let (_, sol) = Reader::default().parse(data).unwrap();
+    let json = serde_json::to_string(&sol).unwrap();
+    let reread: Lso = serde_json::from_str(&json).unwrap();
+    assert_eq!(reread, sol);
+
+    let shared = Rc::new(Value::Object(
+        vec![Element::new("hp", Value::Number(100.0))],
+        None,
+    ));
+    let lso = Lso::new(
+        vec![Element {
+            name: "list".into(),
+            value: Rc::new(Value::StrictArray(vec![
+                Rc::clone(&shared),
+                Rc::clone(&shared),
+            ])),
+        }],
+        "shared",
+        AMFVersion::AMF0,
+    );
+
+    let json = serde_json::to_string(&lso).unwrap();
+    let reread: Lso = serde_json::from_str(&json).unwrap();
+    assert_eq!(reread, lso);
+    match reread.body[0].value.as_ref() {
+        // The values are equal but no longer shared, so will be written inline twice
+        Value::StrictArray(items) => {
+            assert_eq!(items[0], items[1]);
+            assert!(!Rc::ptr_eq(&items[0], &items[1]));
+        }
+        v => panic!("Expected an array, got {:?}", v),
+    }
+    assert!(
+        flash_lso::write::to_bytes(&reread).unwrap().len()
+            > flash_lso::write::to_bytes(&lso).unwrap().len()
+    );
+}
+
+#[test]
+fn amf3_xml_length_past_end_of_input() {
+    use flash_lso::amf3::read::AMF3Decoder;
+    use flash_lso::amf3::visit::Amf3Visitor;
+
+    // An XMLString declaring 5 bytes, with only 2 present
+    let value = [0x0b, 0x0b, b'a', b'b'];
+    assert_eq!(
+        AMF3Decoder::default().parse_single_element(&value),
+        Err(nom::Err::Error(Error::Nom(
+            &value[2..],
+            ErrorKind::TooLarge
+        )))
+    );
+
+    struct Ignore;
+    impl Amf3Visitor for Ignore {}
+    // As a body element named "x"
+    let body = [0x03, b'x', 0x0b, 0x0b, b'a', b'b'];
+    assert!(matches!(
+        AMF3Decoder::default().parse_with_visitor(&body, &mut Ignore),
+        Err(nom::Err::Error(Error::Nom(_, ErrorKind::TooLarge)))
+    ));
+
+    // Exactly enough input is fine
+    let value = [0x0b, 0x05, b'a', b'b'];
+    assert!(AMF3Decoder::default().parse_single_element(&value).is_ok());
+}
+
+#[cfg(feature = "flex")]
+#[test]
+fn flex_collection_types() {
+    use flash_lso::amf3::read::AMF3Decoder;
+    use flash_lso::amf3::write::AMF3Encoder;
+    use flash_lso::extra::flex;
+    use flash_lso::types::Value;
+    use flash_lso::write::Writer;
+    use std::rc::Rc;
+
+    let data = include_bytes!("sol/Flex-Collections-Demo.sol");
+    let mut reader = Reader {
+        amf3_decoder: AMF3Decoder::with_flex_types(),
+        ..Reader::default()
+    };
+    let (_, lso) = reader.parse(data).expect("Unable to parse");
+
+    let items = &lso.body[0];
+    match items.value() {
+        Value::Custom(_, _, Some(def)) => assert_eq!(def.name, "flex.messaging.io.ArrayCollection"),
+        v => panic!("Expected an ArrayCollection, got {:?}", v),
+    }
+    assert_eq!(
+        flex::wrapped_value(items.value()).map(|v| v.as_ref()),
+        Some(&Value::StrictArray(vec![
+            Rc::new(Value::Integer(1)),
+            Rc::new(Value::Integer(2)),
+            Rc::new(Value::String("three".to_string())),
+        ]))
+    );
+    assert!(Rc::ptr_eq(&items.value, &lso.body[2].value));
+
+    let proxy = &lso.body[1];
+    let object = flex::wrapped_value(proxy.value()).unwrap();
+    let name = &object.elements().unwrap()[0];
+    assert_eq!(name.name(), "name");
+    assert_eq!(name.value(), &Value::String("flex".to_string()));
+    assert_eq!(
+        proxy.value(),
+        &flex::wrap_value("flex.messaging.io.ObjectProxy", Rc::clone(object))
+    );
+
+    let mut writer = Writer {
+        amf3_encoder: AMF3Encoder::with_flex_types(),
+    };
+    let (bytes, _) = cookie_factory::gen(writer.write_full(&lso), vec![]).unwrap();
+    assert_eq!(bytes, data.to_vec());
+
+    // Without the decoders the wrapped values can't be read
+    assert!(Reader::default().parse(data).is_err());
+}
+
+#[test]
+fn value_approx_eq() {
+    use flash_lso::types::{Element, Value};
+    use std::rc::Rc;
+
+    let a = Value::Object(
+        vec![
+            Element::new("x", Value::Number(0.1 + 0.2)),
+            Element::new("nan", Value::Number(f64::NAN)),
+            Element::new("v", Value::VectorDouble(vec![1.0, 2.0 / 3.0], false)),
+        ],
+        None,
+    );
+    let b = Value::Object(
+        vec![
+            Element::new("x", Value::Number(0.3)),
+            Element::new("nan", Value::Number(f64::NAN)),
+            Element::new("v", Value::VectorDouble(vec![1.0, 0.666_666_7], false)),
+        ],
+        None,
+    );
+    assert_ne!(a, b);
+    assert!(a.approx_eq(&b, 1e-6));
+    assert!(!a.approx_eq(&b, 1e-12));
+
+    // Integers and numbers compare by value, AMF3 wrappers are ignored
+    assert!(Value::Integer(3).approx_eq(&Value::AMF3(Rc::new(Value::Number(3.0))), 0.0));
+    assert!(!Value::Integer(3).approx_eq(&Value::Number(3.5), 0.1));
+
+    // Infinities only match themselves
+    assert!(Value::Number(f64::INFINITY).approx_eq(&Value::Number(f64::INFINITY), 0.0));
+    assert!(!Value::Number(f64::INFINITY).approx_eq(&Value::Number(f64::MAX), 1.0));
+
+    // Structure and names are compared exactly
+    assert!(!Value::StrictArray(vec![Rc::new(Value::Number(1.0))])
+        .approx_eq(&Value::StrictArray(vec![]), 1.0));
+    let renamed = Value::Object(vec![Element::new("y", Value::Number(0.3))], None);
+    let original = Value::Object(vec![Element::new("x", Value::Number(0.3))], None);
+    assert!(!renamed.approx_eq(&original, 1.0));
+    assert!(!Value::String("1".to_string()).approx_eq(&Value::Number(1.0), 1.0));
+}
+
+#[test]
+fn amf3_bad_references() {
+    use flash_lso::amf3::read::AMF3Decoder;
+    use flash_lso::amf3::visit::Amf3Visitor;
+    use flash_lso::errors::ReferenceTable;
+
+    let bad_reference = |input, index, table_len, table_kind| {
+        nom::Err::Error(Error::BadReference {
+            input,
+            index,
+            table_len,
+            table_kind,
+        })
+    };
+
+    // A string referring to the first string, before any have been read
+    let value = [0x06, 0x00];
+    assert_eq!(
+        AMF3Decoder::default()
+            .parse_single_element(&value)
+            .unwrap_err(),
+        bad_reference(&value[2..], 0, 0, ReferenceTable::String)
+    );
+
+    // An array referring to the second object, while only the array containing it has been read
+    let value = [0x09, 0x03, 0x01, 0x09, 0x02];
+    assert_eq!(
+        AMF3Decoder::default()
+            .parse_single_element(&value)
+            .unwrap_err(),
+        bad_reference(&value[5..], 1, 1, ReferenceTable::Object)
+    );
+
+    // An object referring to the second object
+    let value = [0x0a, 0x02];
+    assert_eq!(
+        AMF3Decoder::default()
+            .parse_single_element(&value)
+            .unwrap_err(),
+        bad_reference(&value[2..], 1, 0, ReferenceTable::Object)
+    );
+
+    // An object using the first trait, before any have been read
+    let value = [0x0a, 0x01];
+    assert_eq!(
+        AMF3Decoder::default()
+            .parse_single_element(&value)
+            .unwrap_err(),
+        bad_reference(&value[2..], 0, 0, ReferenceTable::Trait)
+    );
+
+    // The visitor reports the same error
+    struct Ignore;
+    impl Amf3Visitor for Ignore {}
+    let body = [0x03, b'x', 0x09, 0x02, 0x00];
+    assert_eq!(
+        AMF3Decoder::default()
+            .parse_with_visitor(&body, &mut Ignore)
+            .unwrap_err(),
+        bad_reference(&body[4..], 1, 0, ReferenceTable::Object)
+    );
+
+    let error = Error::BadReference {
+        input: &[],
+        index: 1,
+        table_len: 1,
+        table_kind: ReferenceTable::String,
+    };
+    assert_eq!(
+        error.to_string(),
+        "Bad string reference 1, the table has 1 entries"
+    );
+}
+
+#[test]
+fn amf3_decode_one() {
+    use flash_lso::amf3::decode_one;
+    use flash_lso::amf3::read::AMF3Decoder;
+    use flash_lso::types::Value;
+    use std::rc::Rc;
+
+    // An integer followed by unrelated bytes
+    let data = [0x04, 0x05, 0xff, 0xfe];
+    assert_eq!(
+        decode_one(&data),
+        Ok((Rc::new(Value::Integer(5)), &data[2..]))
+    );
+
+    // Two strings, where the second refers to the first
+    let data = [0x06, 0x05, b'h', b'i', 0x06, 0x00];
+    let mut decoder = AMF3Decoder::default();
+    let (first, rest) = decoder.decode_one(&data).unwrap();
+    let (second, rest) = decoder.decode_one(rest).unwrap();
+    assert_eq!(first, second);
+    assert_eq!(second.as_ref(), &Value::String("hi".to_string()));
+    assert!(rest.is_empty());
+
+    // A fresh decoder can't resolve the reference
+    assert!(matches!(
+        decode_one(&data[4..]),
+        Err(Error::BadReference { .. })
+    ));
+    assert!(decode_one(&[]).is_err());
+}
+
+#[test]
+fn value_to_amf3() {
+    use flash_lso::types::{Attribute, ClassDefinition, Element, Value};
+    use std::rc::Rc;
+
+    // Whole numbers in the AMF3 integer range become integers
+    assert_eq!(Value::Number(5.0).to_amf3(), Value::Integer(5));
+    assert_eq!(
+        Value::Number(268_435_455.0).to_amf3(),
+        Value::Integer(268_435_455)
+    );
+    assert_eq!(
+        Value::Number(-268_435_456.0).to_amf3(),
+        Value::Integer(-268_435_456)
+    );
+    assert_eq!(
+        Value::Number(268_435_456.0).to_amf3(),
+        Value::Number(268_435_456.0)
+    );
+    assert_eq!(
+        Value::Number(-268_435_457.0).to_amf3(),
+        Value::Number(-268_435_457.0)
+    );
+    assert_eq!(Value::Number(1.5).to_amf3(), Value::Number(1.5));
+    assert!(matches!(Value::Number(-0.0).to_amf3(), Value::Number(n) if n.is_sign_negative()));
+    assert!(matches!(Value::Number(f64::NAN).to_amf3(), Value::Number(n) if n.is_nan()));
+
+    // Dates lose their timezone
+    assert_eq!(
+        Value::Date(1_000.0, Some(60)).to_amf3(),
+        Value::Date(1_000.0, None)
+    );
+
+    // Shared values stay shared, embedded AMF3 is unwrapped
+    let shared = Rc::new(Value::Number(2.0));
+    let array = Value::StrictArray(vec![
+        Rc::clone(&shared),
+        shared,
+        Rc::new(Value::AMF3(Rc::new(Value::ByteArray(vec![1])))),
+    ]);
+    match array.to_amf3() {
+        Value::StrictArray(items) => {
+            assert!(Rc::ptr_eq(&items[0], &items[1]));
+            assert_eq!(items[0].as_ref(), &Value::Integer(2));
+            assert_eq!(items[2].as_ref(), &Value::ByteArray(vec![1]));
+        }
+        v => panic!("Expected an array, got {:?}", v),
+    }
+
+    // Objects get a class definition that writes every element
+    let object = Value::Object(vec![Element::new("a", Value::Number(0.5))], None);
+    assert_eq!(
+        object.to_amf3(),
+        Value::Object(
+            vec![Element::new("a", Value::Number(0.5))],
+            Some(ClassDefinition {
+                attributes: Attribute::Dynamic.into(),
+                ..ClassDefinition::default_with_name(String::new())
+            })
+        )
+    );
+}
+
+#[test]
+fn header_padding_only_checked_when_strict() {
+    let data = include_bytes!("sol/Header-Padding-Demo.sol");
+    let (_, lso) = Reader::default()
+        .parse(data)
+        .expect("Padding should be tolerated");
+    assert_eq!(lso.header.name, "Header-Padding-Demo");
+    assert_eq!(lso.body.len(), 1);
+
+    let mut reader = Reader {
+        strict: true,
+        ..Reader::default()
+    };
+    assert_eq!(
+        reader.parse(data),
+        Err(nom::Err::Error(Error::Nom(&data[10..], ErrorKind::Tag)))
+    );
+
+    let (error, offset) = Reader::default()
+        .parse_with_offsets(include_bytes!("sol/BadHeader-Signature.sol"))
+        .unwrap_err();
+    assert_eq!(error, Error::BadSignature);
+    assert_eq!(error.to_string(), "Bad header signature, expected TCSO");
+    assert_eq!(offset, 0);
+}
+
+#[test]
+fn value_count_nodes_and_heap_size() {
+    use flash_lso::types::{Element, Value};
+    use std::mem::size_of;
+    use std::rc::Rc;
+
+    assert_eq!(Value::Null.count_nodes(), 1);
+    assert_eq!(Value::Null.heap_size(), 0);
+    assert_eq!(Value::String("abc".to_string()).heap_size(), 3);
+    assert_eq!(Value::VectorInt(vec![1, 2, 3], false).count_nodes(), 1);
+    assert_eq!(Value::VectorDouble(vec![1.0, 2.0], false).heap_size(), 16);
+
+    // Shared values are counted once
+    let shared = Rc::new(Value::String("shared".to_string()));
+    let array = Value::StrictArray(vec![Rc::clone(&shared), Rc::clone(&shared)]);
+    assert_eq!(array.count_nodes(), 2);
+    let object = Value::Object(
+        vec![
+            Element::new("a", Value::AMF3(Rc::new(array))),
+            Element {
+                name: "b".into(),
+                value: shared,
+            },
+        ],
+        None,
+    );
+    assert_eq!(object.count_nodes(), 4);
+
+    // The nested strings and names are all included, along with the space for the values that hold them
+    let strings = 6 + 2;
+    assert!(object.heap_size() > strings + 3 * size_of::<Value>());
+
+    // Arrays each holding the previous one twice would be a tree of 2^64 values, this is only 65 distinct values
+    let mut doubled = Value::Null;
+    for _ in 0..64 {
+        let previous = Rc::new(doubled);
+        doubled = Value::StrictArray(vec![Rc::clone(&previous), previous]);
+    }
+    assert_eq!(doubled.count_nodes(), 65);
+    assert!(doubled.heap_size() < 65 * 2 * size_of::<Value>());
+
+    let data = include_bytes!("sol/AS3-Demo.sol");
+    let (_, lso) = Reader::default().parse(data).unwrap();
+    let total: usize = lso.body.iter().map(|e| e.value.count_nodes()).sum();
+    assert!(total > lso.body.len());
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn amf3_from_value() {
+    use flash_lso::amf3::from_value;
+    use flash_lso::types::{Element, Value};
+    use serde::Deserialize;
+    use std::rc::Rc;
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    enum Kind {
+        Player,
+        Npc { level: u8 },
+    }
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Save {
+        name: String,
+        score: u32,
+        ratio: f64,
+        items: Vec<i64>,
+        data: Vec<u8>,
+        nickname: Option<String>,
+        kind: Kind,
+        other: Kind,
+    }
+
+    let value = Value::Object(
+        vec![
+            Element::new("name", Value::String("alice".to_string())),
+            // A whole Number into an integer field and an Integer into a float field
+            Element::new("score", Value::Number(1200.0)),
+            Element::new("ratio", Value::Integer(2)),
+            Element::new(
+                "items",
+                Value::StrictArray(vec![
+                    Rc::new(Value::Integer(-1)),
+                    Rc::new(Value::Number(5.0)),
+                ]),
+            ),
+            Element::new("data", Value::ByteArray(vec![1, 2, 3])),
+            Element::new("nickname", Value::Null),
+            Element::new("kind", Value::String("Player".to_string())),
+            Element::new(
+                "other",
+                Value::Object(
+                    vec![Element::new(
+                        "Npc",
+                        Value::Object(vec![Element::new("level", Value::Integer(3))], None),
+                    )],
+                    None,
+                ),
+            ),
+            // Ignored, as Save has no such field
+            Element::new("unknown", Value::Bool(true)),
+        ],
+        None,
+    );
+
+    assert_eq!(
+        from_value::<Save>(&value),
+        Ok(Save {
+            name: "alice".to_string(),
+            score: 1200,
+            ratio: 2.0,
+            items: vec![-1, 5],
+            data: vec![1, 2, 3],
+            nickname: None,
+            kind: Kind::Player,
+            other: Kind::Npc { level: 3 },
+        })
+    );
+
+    // Fractional and out of range numbers can't be read as integers
+    assert!(from_value::<u32>(&Value::Number(1.5)).is_err());
+    assert!(from_value::<u8>(&Value::Integer(300)).is_err());
+    assert_eq!(
+        from_value::<Vec<u32>>(&Value::VectorUInt(vec![7], false)),
+        Ok(vec![7])
+    );
+
+    // Values read from a file
+    #[derive(Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct Demo {
+        my_bool: bool,
+        my_int_array: Vec<u16>,
+        my_null: Option<u8>,
+        my_date: f64,
+        my_byte_array: Vec<u8>,
+    }
+    let data = include_bytes!("sol/AS3-Demo.sol");
+    let (_, lso) = Reader::default().parse(data).unwrap();
+    let demo: Demo = from_value(&Value::Object(lso.body, None)).unwrap();
+    assert!(demo.my_bool);
+    assert_eq!(demo.my_int_array, vec![1, 2, 3]);
+    assert_eq!(demo.my_null, None);
+    assert_eq!(demo.my_date, 1406680830523.0);
+    assert_eq!(demo.my_byte_array.len(), 14);
+}
+
+#[test]
+fn amf3_huge_dictionary_len() {
+    use flash_lso::amf3::read::AMF3Decoder;
+    use flash_lso::amf3::visit::Amf3Visitor;
+
+    // A dictionary declaring the largest possible number of entries (2^28 - 1), with none present
+    let value = [0x11, 0xff, 0xff, 0xff, 0xff, 0x00];
+    assert_eq!(
+        AMF3Decoder::default().parse_single_element(&value),
+        Err(nom::Err::Error(Error::Nom(
+            &value[6..],
+            ErrorKind::TooLarge
+        )))
+    );
+
+    struct Ignore;
+    impl Amf3Visitor for Ignore {}
+    let body = [0x03, b'd', 0x11, 0xff, 0xff, 0xff, 0xff, 0x00, 0x00];
+    assert!(matches!(
+        AMF3Decoder::default().parse_with_visitor(&body, &mut Ignore),
+        Err(nom::Err::Error(Error::Nom(_, ErrorKind::TooLarge)))
+    ));
+
+    // Two entries need at least four bytes, here two null keys with null values
+    let value = [0x11, 0x05, 0x00, 0x01, 0x01, 0x01, 0x01];
+    let (rest, dict) = AMF3Decoder::default().parse_single_element(&value).unwrap();
+    assert!(rest.is_empty());
+    assert_eq!(dict.len(), Some(2));
+    assert!(AMF3Decoder::default()
+        .parse_single_element(&value[..6])
+        .is_err());
+}
+
+#[test]
+fn lso_merge() {
+    use flash_lso::types::{AMFVersion, Element, Lso, MergeStrategy, Value};
+    use std::rc::Rc;
+
+    let object = |elements: Vec<Element>| Value::Object(elements, None);
+    let base = || {
+        Lso::new(
+            vec![
+                Element::new("score", Value::Number(1.0)),
+                Element::new(
+                    "player",
+                    object(vec![
+                        Element::new("name", Value::String("alice".to_string())),
+                        Element::new("level", Value::Number(1.0)),
+                    ]),
+                ),
+                Element::new(
+                    "items",
+                    Value::StrictArray(vec![Rc::new(Value::Number(1.0))]),
+                ),
+            ],
+            "base",
+            AMFVersion::AMF0,
+        )
+    };
+    let update = Lso::new(
+        vec![
+            Element::new("score", Value::Number(2.0)),
+            Element::new(
+                "player",
+                object(vec![
+                    Element::new("level", Value::Number(5.0)),
+                    Element::new("class", Value::String("mage".to_string())),
+                ]),
+            ),
+            Element::new(
+                "items",
+                Value::StrictArray(vec![Rc::new(Value::Number(2.0))]),
+            ),
+            Element::new("new", Value::Bool(true)),
+        ],
+        "update",
+        AMFVersion::AMF3,
+    );
+
+    let mut lso = base();
+    lso.merge(&update, MergeStrategy::KeepSelf);
+    let mut expected = base();
+    expected.body.push(Element::new("new", Value::Bool(true)));
+    assert_eq!(lso, expected);
+
+    let mut lso = base();
+    lso.merge(&update, MergeStrategy::KeepOther);
+    assert_eq!(lso.header, base().header);
+    assert_eq!(lso.body, update.body);
+
+    let mut lso = base();
+    lso.merge(&update, MergeStrategy::DeepMergeObjects);
+    assert_eq!(
+        lso.body,
+        vec![
+            Element::new("score", Value::Number(2.0)),
+            Element::new(
+                "player",
+                object(vec![
+                    Element::new("name", Value::String("alice".to_string())),
+                    Element::new("level", Value::Number(5.0)),
+                    Element::new("class", Value::String("mage".to_string())),
+                ]),
+            ),
+            // Arrays are replaced
+            Element::new(
+                "items",
+                Value::StrictArray(vec![Rc::new(Value::Number(2.0))])
+            ),
+            Element::new("new", Value::Bool(true)),
+        ]
+    );
+}
+
+#[test]
+fn amf3_max_string_table_bytes() {
+    use flash_lso::amf3::read::AMF3Decoder;
+
+    let abc = [0x06, 0x07, b'a', b'b', b'c'];
+    let defg = [0x06, 0x09, b'd', b'e', b'f', b'g'];
+    // A reference to the first string in the table
+    let reference = [0x06, 0x00];
+
+    let mut decoder = AMF3Decoder::default();
+    decoder.max_string_table_bytes = Some(6);
+    assert!(decoder.parse_single_element(&abc).is_ok());
+    assert!(matches!(
+        decoder.parse_single_element(&defg),
+        Err(nom::Err::Error(Error::Nom(_, ErrorKind::TooLarge)))
+    ));
+    assert_eq!(decoder.string_reference_table.len(), 1);
+    // References don't add to the total
+    assert!(decoder.parse_single_element(&reference).is_ok());
+
+    decoder.reset();
+    assert!(decoder.parse_single_element(&defg).is_ok());
+
+    // Unlimited by default
+    let mut decoder = AMF3Decoder::default();
+    for _ in 0..100 {
+        assert!(decoder.parse_single_element(&defg).is_ok());
+    }
+}
+
+#[test]
+fn as3_doubly_linked_objects() {
+    use flash_lso::types::{ClassDefinition, Element, Value};
+    use std::rc::Rc;
+
+    // A sealed `Node` whose next node refers back to it as both its previous and next node
+    let data = include_bytes!("sol/AS3-LinkedList-Demo.sol");
+    let (_, sol) = Reader::default().parse(data).expect("Unable to parse");
+    let class_def = ClassDefinition {
+        static_properties: vec!["value".to_string(), "prev".to_string(), "next".to_string()],
+        ..ClassDefinition::default_with_name("Node".to_string())
+    };
+    let partial_head = || Value::Object(vec![], Some(class_def.clone()));
+
+    assert_eq!(
+        sol.body[0].value.as_ref(),
+        &Value::Object(
+            vec![
+                Element::new("value", Value::Integer(1)),
+                Element::new("prev", Value::Null),
+                Element::new(
+                    "next",
+                    Value::Object(
+                        vec![
+                            Element::new("value", Value::Integer(2)),
+                            Element::new("prev", partial_head()),
+                            Element::new("next", partial_head()),
+                        ],
+                        Some(class_def.clone())
+                    )
+                ),
+            ],
+            Some(class_def.clone())
+        )
+    );
+
+    // The partial head is written once with its properties undefined, and the second reference to it as a reference
+    let bytes = flash_lso::write::to_bytes(&sol).expect("Unable to write");
+    let (_, reread) = Reader::default()
+        .parse(&bytes)
+        .expect("Unable to parse written file");
+    let head = match reread.body[0].value.as_ref() {
+        Value::Object(elements, _) => elements,
+        v => panic!("Expected an object, got {:?}", v),
+    };
+    let next = match head[2].value.as_ref() {
+        Value::Object(elements, _) => elements,
+        v => panic!("Expected an object, got {:?}", v),
+    };
+    assert_eq!(next[0].value.as_ref(), &Value::Integer(2));
+    assert!(Rc::ptr_eq(&next[1].value, &next[2].value));
+    assert_eq!(
+        next[1].value.as_ref(),
+        &Value::Object(
+            vec![
+                Element::new("value", Value::Undefined),
+                Element::new("prev", Value::Undefined),
+                Element::new("next", Value::Undefined),
+            ],
+            Some(class_def)
+        )
+    );
+}
+
+#[test]
+fn amf3_parse_body_checked_trailing_bytes() {
+    use flash_lso::amf3::read::AMF3Decoder;
+    use flash_lso::types::{Element, Value};
+
+    // "a" = 5, followed by its padding
+    let body = [0x03, b'a', 0x04, 0x05, 0x00];
+    let expected = vec![Element::new("a", Value::Integer(5))];
+
+    let parsed = AMF3Decoder::default()
+        .parse_body_checked(&body)
+        .expect("Unable to parse body");
+    assert_eq!(parsed.elements, expected);
+    assert_eq!(parsed.trailing_bytes, 0);
+
+    let mut hidden = body.to_vec();
+    hidden.extend_from_slice(b"\xffhidden");
+    let parsed = AMF3Decoder::default()
+        .parse_body_checked(&hidden)
+        .expect("Unable to parse body");
+    assert_eq!(parsed.elements, expected);
+    assert_eq!(parsed.trailing_bytes, 7);
+    assert_eq!(
+        &hidden[hidden.len() - parsed.trailing_bytes..],
+        b"\xffhidden"
+    );
+
+    // The trailing bytes are still an error when the whole input must be read
+    assert!(AMF3Decoder::default().parse_with_offsets(&hidden).is_err());
+}
+
+#[test]
+fn amf3_name_transform() {
+    use flash_lso::amf3::read::AMF3Decoder;
+    use flash_lso::types::{Element, Value};
+    use std::rc::Rc;
+
+    fn upper_names(elements: &[Element]) -> Vec<Element> {
+        elements
+            .iter()
+            .map(|e| Element {
+                name: e.name.to_uppercase().into(),
+                value: Rc::new(upper_value(&e.value)),
+            })
+            .collect()
+    }
+    fn upper_values(values: &[Rc<Value>]) -> Vec<Rc<Value>> {
+        values.iter().map(|v| Rc::new(upper_value(v))).collect()
+    }
+    fn upper_value(value: &Value) -> Value {
+        match value {
+            Value::Object(elements, def) => Value::Object(upper_names(elements), def.clone()),
+            Value::ECMAArray(dense, assoc, len) => {
+                Value::ECMAArray(upper_values(dense), upper_names(assoc), *len)
+            }
+            Value::StrictArray(items) => Value::StrictArray(upper_values(items)),
+            Value::VectorObject(items, name, fixed) => {
+                Value::VectorObject(upper_values(items), name.clone(), *fixed)
+            }
+            v => v.clone(),
+        }
+    }
+
+    let decoder = || {
+        let mut decoder = AMF3Decoder::default();
+        decoder.name_transform = Some(Rc::new(|name: &str| name.to_uppercase()));
+        decoder
+    };
+
+    let data = include_bytes!("sol/AS3-Demo.sol");
+    let (_, sol) = Reader::default().parse(data).expect("Unable to parse");
+    let mut reader = Reader {
+        amf3_decoder: decoder(),
+        ..Reader::default()
+    };
+    let (_, transformed) = reader.parse(data).expect("Unable to parse");
+    // Compared with `approx_eq` as the body contains NaN
+    assert!(Value::Object(transformed.body, None)
+        .approx_eq(&Value::Object(upper_names(&sol.body), None), 0.0));
+
+    // Static properties are renamed, but the class definition keeps the names as read
+    let sealed = [0x0a, 0x13, 0x03, b'A', 0x03, b'x', 0x04, 0x01];
+    let (_, value) = decoder()
+        .parse_single_element(&sealed)
+        .expect("Unable to parse object");
+    match value.as_ref() {
+        Value::Object(elements, Some(def)) => {
+            assert_eq!(elements, &vec![Element::new("X", Value::Integer(1))]);
+            assert_eq!(def.static_properties, vec!["x".to_string()]);
+        }
+        v => panic!("Expected an object, got {:?}", v),
+    }
+}
+
+#[test]
+fn value_eq_unordered() {
+    use flash_lso::types::{Attribute, ClassDefinition, Element, Value};
+    use std::rc::Rc;
+
+    let object = |elements: Vec<Element>| Value::Object(elements, None);
+    let a = object(vec![
+        Element::new("x", Value::Integer(1)),
+        Element::new(
+            "inner",
+            object(vec![
+                Element::new("p", Value::Bool(true)),
+                Element::new("q", Value::Null),
+            ]),
+        ),
+        Element::new("x", Value::Integer(2)),
+    ]);
+    let reordered = object(vec![
+        Element::new("x", Value::Integer(2)),
+        Element::new(
+            "inner",
+            object(vec![
+                Element::new("q", Value::Null),
+                Element::new("p", Value::Bool(true)),
+            ]),
+        ),
+        Element::new("x", Value::Integer(1)),
+    ]);
+    assert!(a.eq_unordered(&reordered));
+    assert!(reordered.eq_unordered(&a));
+    assert_ne!(a, reordered);
+
+    // Duplicate names are compared as multisets
+    let duplicated = object(vec![
+        Element::new("x", Value::Integer(1)),
+        Element::new("x", Value::Integer(1)),
+    ]);
+    let mixed = object(vec![
+        Element::new("x", Value::Integer(1)),
+        Element::new("x", Value::Integer(2)),
+    ]);
+    assert!(!duplicated.eq_unordered(&mixed));
+    assert!(!mixed.eq_unordered(&duplicated));
+
+    // Dense parts are compared in order
+    let array = |dense: Vec<i32>, assoc: Vec<Element>| Value::ecma_array(dense, assoc);
+    assert!(
+        array(vec![1, 2], vec![Element::new("a", 1), Element::new("b", 2)]).eq_unordered(&array(
+            vec![1, 2],
+            vec![Element::new("b", 2), Element::new("a", 1)]
+        ))
+    );
+    assert!(!array(vec![1, 2], vec![]).eq_unordered(&array(vec![2, 1], vec![])));
+    assert!(
+        !Value::StrictArray(vec![Rc::new(Value::Integer(1)), Rc::new(Value::Integer(2))])
+            .eq_unordered(&Value::StrictArray(vec![
+                Rc::new(Value::Integer(2)),
+                Rc::new(Value::Integer(1))
+            ]))
+    );
+
+    // Static properties of sealed classes may be in any order
+    let sealed = |props: &[&str]| {
+        Value::Object(
+            props
+                .iter()
+                .map(|p| Element::new(*p, Value::Null))
+                .collect(),
+            Some(ClassDefinition {
+                static_properties: props.iter().map(|p| p.to_string()).collect(),
+                ..ClassDefinition::default_with_name("Point".to_string())
+            }),
+        )
+    };
+    assert!(sealed(&["x", "y"]).eq_unordered(&sealed(&["y", "x"])));
+    let dynamic = Value::Object(
+        vec![],
+        Some(ClassDefinition {
+            attributes: Attribute::Dynamic.into(),
+            ..ClassDefinition::default_with_name("Point".to_string())
+        }),
+    );
+    assert!(!sealed(&[]).eq_unordered(&dynamic));
+
+    // Values are otherwise compared exactly
+    assert!(!Value::Integer(1).eq_unordered(&Value::Number(1.0)));
+}
+
+#[test]
+fn lso_rename_classes() {
+    use flash_lso::types::Value;
+    use std::collections::BTreeMap;
+
+    let data = include_bytes!("sol/AS3-Demo.sol");
+    let (_, mut sol) = Reader::default().parse(data).expect("Unable to parse");
+    assert_eq!(sol.class_names(), vec!["com.AS3SolTestClass".to_string()]);
+
+    let mut names = BTreeMap::new();
+    names.insert("com.AS3SolTestClass".to_string(), "Point".to_string());
+    names.insert("Unused".to_string(), "Other".to_string());
+    sol.rename_classes(&names);
+    assert_eq!(sol.class_names(), vec!["Point".to_string()]);
+
+    let get = |name: &str| {
+        sol.body
+            .iter()
+            .find(|e| e.name() == name)
+            .map(|e| e.value.as_ref())
+            .expect("Element not found")
+    };
+    let class_name = |value: &Value| match value {
+        Value::Object(_, Some(def)) => def.name.clone(),
+        v => panic!("Expected a typed object, got {:?}", v),
+    };
+    // The class is used by an object and by each object in a typed vector
+    assert_eq!(class_name(get("myTypedObject")), "Point");
+    match get("myVectorTypedObject") {
+        Value::VectorObject(items, type_name, _) => {
+            assert_eq!(type_name, "Point");
+            assert_eq!(items.len(), 3);
+            for item in items {
+                assert_eq!(class_name(item), "Point");
+            }
+        }
+        v => panic!("Expected a vector, got {:?}", v),
+    }
+}
+
+#[test]
+fn amf3_write_element() {
+    use flash_lso::amf3::read::AMF3Decoder;
+    use flash_lso::amf3::write::AMF3Encoder;
+    use flash_lso::amf3::write_element;
+    use flash_lso::types::{Attribute, ClassDefinition, Element, Value};
+    use std::rc::Rc;
+
+    let mut encoder = AMF3Encoder::default();
+    assert_eq!(
+        write_element(&Value::Integer(5), &mut encoder).unwrap(),
+        vec![0x04, 0x05]
+    );
+    assert_eq!(
+        write_element(&Value::String("hi".to_string()), &mut encoder).unwrap(),
+        vec![0x06, 0x05, b'h', b'i']
+    );
+    // The string table is kept between calls
+    assert_eq!(
+        write_element(&Value::String("hi".to_string()), &mut encoder).unwrap(),
+        vec![0x06, 0x00]
+    );
+
+    // A value shared between messages is written by reference the second time
+    let object = |elements: Vec<Element>| {
+        Value::Object(
+            elements,
+            Some(ClassDefinition {
+                attributes: Attribute::Dynamic.into(),
+                ..ClassDefinition::default_with_name(String::new())
+            }),
+        )
+    };
+    let shared = Rc::new(object(vec![Element::new("x", Value::Integer(1))]));
+    let first = Value::StrictArray(vec![Rc::clone(&shared)]);
+    let second = object(vec![Element {
+        name: "p".into(),
+        value: Rc::clone(&shared),
+    }]);
+    let mut encoder = AMF3Encoder::default();
+    let first_bytes = encoder.encode_one(&first).unwrap();
+    let second_bytes = encoder.encode_one(&second).unwrap();
+    assert!(second_bytes.len() < AMF3Encoder::default().encode_one(&second).unwrap().len());
+
+    let mut decoder = AMF3Decoder::default();
+    let (decoded, rest) = decoder.decode_one(&first_bytes).unwrap();
+    assert!(rest.is_empty());
+    assert_eq!(decoded.as_ref(), &first);
+    let (decoded, rest) = decoder.decode_one(&second_bytes).unwrap();
+    assert!(rest.is_empty());
+    assert_eq!(decoded.as_ref(), &second);
+
+    // After a reset nothing is written by reference
+    encoder.reset();
+    assert_eq!(
+        encoder
+            .encode_one(&Value::String("hi".to_string()))
+            .unwrap(),
+        vec![0x06, 0x05, b'h', b'i']
+    );
+}
+
+#[test]
+fn nan_and_infinity_keep_their_bits() {
+    use flash_lso::types::Value;
+
+    let expected: [u64; 7] = [
+        0x7ff8_0000_0000_0000,
+        0xfff8_0000_0000_0000,
+        0x7ff8_0000_0000_1234,
+        0x7ff0_0000_0000_0001,
+        0x7ff0_0000_0000_0000,
+        0xfff0_0000_0000_0000,
+        0x8000_0000_0000_0000,
+    ];
+
+    let data = include_bytes!("sol/AS2-NaN-Demo.sol");
+    let (_, sol) = Reader::default().parse(data).unwrap();
+    let bits: Vec<u64> = sol
+        .body
+        .iter()
+        .map(|e| match e.value() {
+            Value::Number(n) => n.to_bits(),
+            v => panic!("Expected a number, got {:?}", v),
+        })
+        .collect();
+    assert_eq!(bits, expected);
+    assert_eq!(flash_lso::write::write_to_bytes(&sol), data.to_vec());
+
+    let data = include_bytes!("sol/AS3-NaN-Demo.sol");
+    let (_, sol) = Reader::default().parse(data).unwrap();
+    let vector = sol.body.iter().find(|e| e.name() == "vector").unwrap();
+    match vector.value() {
+        Value::VectorDouble(items, _) => {
+            assert_eq!(
+                items.iter().map(|n| n.to_bits()).collect::<Vec<_>>(),
+                expected
+            )
+        }
+        v => panic!("Expected a vector, got {:?}", v),
+    }
+    assert_eq!(flash_lso::write::write_to_bytes(&sol), data.to_vec());
+}
+
+#[test]
+fn value_pretty() {
+    use flash_lso::types::{ClassDefinition, Element, Value};
+
+    let point = Value::Object(
+        vec![
+            Element::new("x", Value::Integer(1)),
+            Element::new("y", Value::Number(2.5)),
+        ],
+        Some(ClassDefinition::default_with_name("com.Point".to_string())),
+    );
+    let value = Value::object(
+        vec![
+            Element::new("name", Value::String("x".to_string())),
+            Element::new("point", point),
+            Element::new("ints", Value::VectorInt(vec![3, 4], false)),
+            Element::new(
+                "lookup",
+                Value::dictionary(vec![(7, Value::strict_array(Vec::<Value>::new()))], false),
+            ),
+            Element::new(
+                "mixed",
+                Value::ecma_array(vec![true], vec![Element::new("k", Value::Null)]),
+            ),
+        ],
+        None,
+    );
+
+    assert_eq!(
+        value.pretty(2, 8),
+        "Object (5 properties)
+  name: \"x\"
+  point: com.Point (2 properties)
+    x: 1
+    y: 2.5
+  ints: Vector.<int> (2 items)
+    0: 3
+    1: 4
+  lookup: Dictionary (1 entry)
+    7: Array (0 items)
+  mixed: ECMAArray (1 item, 1 property)
+    0: true
+    k: null"
+    );
+
+    // Empty containers have nothing to cut off
+    assert_eq!(
+        value.pretty(4, 1).lines().collect::<Vec<_>>()[2..=3],
+        ["    point: com.Point (2 properties)", "        ..."]
+    );
+    assert_eq!(
+        Value::StrictArray(Vec::new()).pretty(2, 0),
+        "Array (0 items)"
+    );
+    assert_eq!(value.pretty(2, 0), "Object (5 properties)\n  ...");
+    assert_eq!(Value::Integer(3).pretty(2, 0), "3");
+}
+
+#[test]
+fn amf3_repeated_traits_written_by_reference() {
+    use flash_lso::types::{AMFVersion, ClassDefinition, Element, Lso, Value};
+
+    let class_def = |name: &str| ClassDefinition {
+        name: name.to_string(),
+        attributes: Default::default(),
+        static_properties: vec!["id".to_string()],
+    };
+    let point = |id: i32| {
+        Value::Object(
+            vec![Element::new("id", Value::Integer(id))],
+            Some(class_def("com.Point")),
+        )
+    };
+    let points: Vec<Value> = (0..100).map(point).collect();
+    // A second class whose objects follow repeated points, so its index depends on the points adding one trait
+    let mixed: Vec<Value> = (0..10)
+        .flat_map(|id| {
+            vec![
+                point(id),
+                Value::Object(
+                    vec![Element::new("id", Value::Integer(id))],
+                    Some(class_def("com.Line")),
+                ),
+            ]
+        })
+        .collect();
+    let lso = Lso::new(
+        vec![
+            Element::new("points", Value::strict_array(points)),
+            Element::new("mixed", Value::strict_array(mixed)),
+        ],
+        "Traits",
+        AMFVersion::AMF3,
+    );
+    let bytes = flash_lso::write::write_to_bytes(&lso);
+
+    let mut reader = Reader::default();
+    let (_, read) = reader.parse(&bytes).unwrap();
+    assert_eq!(read.body, lso.body);
+
+    // Only the first object of each class has its trait written in full, the reader only adds those to its table
+    assert_eq!(
+        reader.amf3_decoder.trait_reference_table,
+        vec![class_def("com.Point"), class_def("com.Line")]
+    );
+}
+
+#[test]
+fn objects_without_a_class_are_written_as_dynamic_amf3() {
+    use flash_lso::types::{AMFVersion, Element, Lso, Value};
+
+    let lso = Lso::new(
+        vec![Element::new(
+            "points",
+            Value::strict_array(vec![Value::object(vec![Element::new("x", 1)], None)]),
+        )],
+        "Anonymous",
+        AMFVersion::AMF3,
+    );
+    let bytes = flash_lso::write::write_to_bytes(&lso);
+
+    let (_, read) = Reader::default().parse(&bytes).unwrap();
+    match read.body[0].value() {
+        Value::StrictArray(items) => match items[0].as_ref() {
+            Value::Object(elements, Some(class_def)) => {
+                assert_eq!(elements, &vec![Element::new("x", 1)]);
+                assert!(class_def.name.is_empty());
+                assert_eq!(class_def.encoding(), 2);
+            }
+            v => panic!("Expected an object, got {:?}", v),
+        },
+        v => panic!("Expected an array, got {:?}", v),
+    }
+}
+
+#[test]
+fn value_retain_removes_passwords() {
+    use flash_lso::types::{ClassDefinition, Element, Value};
+    use std::rc::Rc;
+
+    let account = |name: &str| {
+        Value::Object(
+            vec![
+                Element::new("name", name),
+                Element::new("password", "secret"),
+            ],
+            Some(ClassDefinition {
+                name: "com.Account".to_string(),
+                attributes: Default::default(),
+                static_properties: vec!["name".to_string(), "password".to_string()],
+            }),
+        )
+    };
+    let shared = Rc::new(account("shared"));
+    let mut value = Value::object(
+        vec![
+            Element::new("password", "top"),
+            Element::new("main", account("main")),
+            Element {
+                name: "alias".into(),
+                value: Rc::clone(&shared),
+            },
+            Element::new(
+                "history",
+                Value::ecma_array(
+                    vec![account("old")],
+                    vec![Element::new("password", "older")],
+                ),
+            ),
+            Element::new(
+                "lookup",
+                Value::dictionary(vec![("key", account("dict"))], false),
+            ),
+        ],
+        None,
+    );
+
+    value.retain(|name, _| name != "password");
+
+    let pruned = |name: &str| {
+        Value::Object(
+            vec![Element::new("name", name)],
+            Some(ClassDefinition {
+                name: "com.Account".to_string(),
+                attributes: Default::default(),
+                static_properties: vec!["name".to_string()],
+            }),
+        )
+    };
+    assert_eq!(
+        value,
+        Value::object(
+            vec![
+                Element::new("main", pruned("main")),
+                Element::new("alias", pruned("shared")),
+                Element::new(
+                    "history",
+                    Value::ECMAArray(vec![Rc::new(pruned("old"))], Vec::new(), 1),
+                ),
+                Element::new(
+                    "lookup",
+                    Value::dictionary(vec![("key", pruned("dict"))], false)
+                ),
+            ],
+            None,
+        )
+    );
+    // The shared value is copied rather than changed
+    assert_eq!(*shared, account("shared"));
+}
+
+#[test]
+fn amf3_parse_with_spans() {
+    use flash_lso::amf3::read::{AMF3Decoder, Span};
+
+    let body = [
+        // a: {x: 1, list: [2, "s"]}
+        0x03, b'a', 0x0a, 0x0b, 0x01, 0x03, b'x', 0x04, 0x01, 0x09, b'l', b'i', b's', b't', 0x09,
+        0x05, 0x01, 0x04, 0x02, 0x06, 0x03, b's', 0x01, 0x00, // b: a reference to a
+        0x03, b'b', 0x0a, 0x00, 0x00,
+    ];
+    let span = |path: &str, start, end| Span {
+        path: path.to_string(),
+        start,
+        end,
+    };
+
+    let mut decoder = AMF3Decoder::default();
+    let (rest, (elements, spans)) = decoder.parse_with_spans(&body).unwrap();
+    assert!(rest.is_empty());
+    assert_eq!(
+        elements,
+        AMF3Decoder::default().parse_body(&body).unwrap().1
+    );
+    assert_eq!(
+        spans,
+        vec![
+            span("a", 2, 23),
+            span("a.x", 7, 9),
+            span("a.list", 14, 22),
+            span("a.list.0", 17, 19),
+            span("a.list.1", 19, 22),
+            span("b", 26, 28),
+        ]
+    );
+
+    // Each call only gives the spans of its own values, though references can refer to earlier ones
+    let (_, _) = decoder.parse_body(&body).unwrap();
+    let (_, (_, spans)) = decoder.parse_with_spans(&body[24..]).unwrap();
+    assert_eq!(spans, vec![span("b", 2, 4)]);
+}
+
+#[test]
+fn amf3_huge_static_property_count() {
+    use flash_lso::amf3::read::AMF3Decoder;
+
+    // An inline sealed trait with an empty class name declaring the largest possible number of static properties
+    let value = [0x0a, 0xff, 0xff, 0xff, 0xff, 0x01];
+    assert_eq!(
+        AMF3Decoder::default().parse_single_element(&value),
+        Err(nom::Err::Error(Error::Nom(
+            &value[6..],
+            ErrorKind::TooLarge
+        )))
+    );
+
+    // A few bytes follow, but too few to hold the names
+    let value = [0x0a, 0xff, 0xff, 0xff, 0xff, 0x01, 0x03, b'a', 0x00];
+    assert!(matches!(
+        AMF3Decoder::default().parse_single_element(&value),
+        Err(nom::Err::Error(Error::Nom(_, ErrorKind::TooLarge)))
+    ));
+}
+
+#[test]
+fn hashable_value_dedups_subtrees() {
+    use flash_lso::types::{Element, HashableValue, Value};
+    use std::collections::HashMap;
+    use std::rc::Rc;
+
+    // Count how often each subtree appears
+    fn count(value: &Rc<Value>, counts: &mut HashMap<HashableValue, usize>) {
+        *counts
+            .entry(HashableValue::from(Rc::clone(value)))
+            .or_default() += 1;
+        match value.as_ref() {
+            Value::Object(elements, _) => elements.iter().for_each(|e| count(&e.value, counts)),
+            Value::StrictArray(items) => items.iter().for_each(|v| count(v, counts)),
+            _ => {}
+        }
+    }
+
+    let point = |x: f64| Value::object(vec![Element::new("x", x)], None);
+    let tree = Rc::new(Value::strict_array(vec![
+        point(1.0),
+        point(1.0),
+        point(f64::NAN),
+        point(f64::NAN),
+        point(0.0),
+        point(-0.0),
+    ]));
+    let mut counts = HashMap::new();
+    count(&tree, &mut counts);
+
+    assert_eq!(counts[&HashableValue::from(point(1.0))], 2);
+    assert_eq!(counts[&HashableValue::from(point(f64::NAN))], 2);
+    assert_eq!(counts[&HashableValue::from(point(0.0))], 1);
+    assert_eq!(counts[&HashableValue::from(point(-0.0))], 1);
+    assert_eq!(counts[&HashableValue::from(Value::Number(1.0))], 2);
+    assert!(!counts.contains_key(&HashableValue::from(Value::Integer(1))));
+    // The array, 4 distinct points and 4 distinct numbers
+    assert_eq!(counts.len(), 9);
+
+    // A copy of a value is equal to it, even if it holds NaN
+    let (_, sol) = Reader::default()
+        .parse(include_bytes!("sol/AS3-Demo.sol"))
+        .unwrap();
+    for e in &sol.body {
+        assert_eq!(
+            HashableValue::from(Rc::clone(&e.value)),
+            HashableValue::from(e.value().clone())
+        );
+    }
+}
+
+#[test]
+fn write_to_sink() {
+    use flash_lso::write::{to_bytes, write_to};
+    use std::io::{self, Write};
+
+    for data in [
+        &include_bytes!("sol/AS2-Demo.sol")[..],
+        &include_bytes!("sol/AS3-Demo.sol")[..],
+    ] {
+        let (_, mut sol) = Reader::default().parse(data).unwrap();
+        // The length in the header is recalculated
+        sol.header.length = 0;
+
+        let mut out = Vec::new();
+        write_to(&mut out, &sol).unwrap();
+        assert_eq!(out, to_bytes(&sol).unwrap());
+        assert!(Reader::default().parse(&out).is_ok());
+    }
+
+    // Errors from the sink are passed on
+    struct Full;
+    impl Write for Full {
+        fn write(&mut self, _buf: &[u8]) -> io::Result<usize> {
+            Err(io::Error::new(io::ErrorKind::WriteZero, "full"))
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+    let (_, sol) = Reader::default()
+        .parse(include_bytes!("sol/AS2-Demo.sol"))
+        .unwrap();
+    assert_eq!(
+        write_to(&mut Full, &sol).unwrap_err().kind(),
+        io::ErrorKind::WriteZero
+    );
+}
+
+#[test]
+fn read_header_only() {
+    use flash_lso::read::parse_header;
+    use flash_lso::types::AMFVersion;
+
+    let data = include_bytes!("sol/AS3-Demo.sol");
+    // Cut off part way through the body, which can no longer be parsed
+    let truncated = &data[..40];
+    assert!(Reader::default().parse(truncated).is_err());
+
+    let (header, body_start) = parse_header(truncated).unwrap();
+    assert_eq!(header.name, "AS3-Demo");
+    assert_eq!(header.format_version, AMFVersion::AMF3);
+    assert_eq!(header.length as usize, data.len() - 6);
+    // 2 byte magic, 4 byte length, 10 byte signature, 2 + 8 byte name, 4 byte padding and version
+    assert_eq!(body_start, 30);
+
+    let (header, _) = parse_header(include_bytes!("sol/AS2-Demo.sol")).unwrap();
+    assert_eq!(header.format_version, AMFVersion::AMF0);
+
+    let mut bad_signature = data[..30].to_vec();
+    bad_signature[6] = b'X';
+    assert_eq!(
+        parse_header(&bad_signature).unwrap_err(),
+        Error::BadSignature
+    );
+    assert!(parse_header(&data[..20]).is_err());
+    assert_eq!(parse_header(&[0x00, 0x00]).unwrap_err(), Error::BadMagic);
+}
+
+#[test]
+fn amf3_validate_vector_types() {
+    use flash_lso::amf3::read::AMF3Decoder;
+
+    let validating = || {
+        let mut decoder = AMF3Decoder::default();
+        decoder.validate_vector_types = true;
+        decoder
+    };
+
+    // Typed objects matching the vector type
+    let mut reader = Reader {
+        amf3_decoder: validating(),
+        ..Reader::default()
+    };
+    assert!(reader
+        .parse(include_bytes!("sol/AS3-VectorTypedObject-Demo.sol"))
+        .is_ok());
+    // Untyped vectors hold anything
+    assert!(reader
+        .parse(include_bytes!("sol/AS3-VectorObject-Demo.sol"))
+        .is_ok());
+
+    // A `Vector.<C>` holding null and an anonymous object
+    let mismatched = [0x10, 0x05, 0x00, 0x03, b'C', 0x01, 0x0a, 0x0b, 0x01, 0x01];
+    assert_eq!(
+        validating().parse_single_element(&mismatched),
+        Err(nom::Err::Error(Error::Nom(
+            &mismatched[6..],
+            ErrorKind::Verify
+        )))
+    );
+    assert!(AMF3Decoder::default()
+        .parse_single_element(&mismatched)
+        .is_ok());
+
+    // The same as a `Vector.<*>`
+    let wildcard = [0x10, 0x05, 0x00, 0x03, b'*', 0x01, 0x0a, 0x0b, 0x01, 0x01];
+    assert!(validating().parse_single_element(&wildcard).is_ok());
+}
+
+#[test]
+fn value_dict_string_keys() {
+    use flash_lso::types::Value;
+    use std::collections::HashMap;
+    use std::rc::Rc;
+
+    let dict = Value::dictionary(vec![("a", 1), ("b", 2)], false);
+    assert_eq!(
+        dict.dict_string_keys(false),
+        Some(vec![
+            ("a".to_string(), Rc::new(Value::Integer(1))),
+            ("b".to_string(), Rc::new(Value::Integer(2)))
+        ])
+    );
+
+    // Integer keys are only accepted when asked for
+    let mixed = Value::dictionary(vec![(Value::from("1"), 1), (Value::Integer(1), 2)], false);
+    assert_eq!(mixed.dict_string_keys(false), None);
+    let entries = mixed.dict_string_keys(true).unwrap();
+    assert_eq!(entries.len(), 2);
+    let map: HashMap<_, _> = entries.into_iter().collect();
+    assert_eq!(map["1"].as_ref(), &Value::Integer(2));
+
+    // Other keys are never accepted
+    let object_key = Value::dictionary(vec![(Value::object(Vec::new(), None), 1)], false);
+    assert_eq!(object_key.dict_string_keys(true), None);
+    assert_eq!(Value::Integer(1).dict_string_keys(true), None);
+    assert_eq!(
+        Value::AMF3(Rc::new(dict.clone())).dict_string_keys(false),
+        dict.dict_string_keys(false)
+    );
+}
+
+#[test]
+fn amf3_clear_string_table() {
+    use flash_lso::amf3::read::AMF3Decoder;
+    use flash_lso::errors::ReferenceTable;
+    use flash_lso::types::Value;
+
+    let abc = [0x06, 0x07, b'a', b'b', b'c'];
+    // A reference to the first string in the table
+    let reference = [0x06, 0x00];
+
+    let mut decoder = AMF3Decoder::default();
+    decoder.max_string_table_bytes = Some(3);
+    let (_, value) = decoder.parse_single_element(&abc).unwrap();
+    assert_eq!(decoder.string_reference_table.len(), 1);
+
+    decoder.clear_string_table();
+    assert!(decoder.string_reference_table.is_empty());
+    assert_eq!(*value, Value::String("abc".to_string()));
+    assert!(matches!(
+        decoder.parse_single_element(&reference),
+        Err(nom::Err::Error(Error::BadReference {
+            index: 0,
+            table_len: 0,
+            table_kind: ReferenceTable::String,
+            ..
+        }))
+    ));
+    // The space used towards the limit is freed too
+    assert!(decoder.parse_single_element(&abc).is_ok());
+
+    decoder.reset();
+    decoder.shrink_tables();
+    assert_eq!(decoder.string_reference_table.capacity(), 0);
+}
+
+#[test]
+fn amf0_date_timezone_round_trip() {
+    use flash_lso::types::{AMFVersion, Element, Lso, Value};
+
+    let date = Value::Date(1409704396759.0, Some(0xFE20));
+    let amf0 = Lso::new(
+        vec![Element::new("when", date.clone())],
+        "test",
+        AMFVersion::AMF0,
+    );
+    let bytes = flash_lso::write::to_bytes(&amf0).unwrap();
+    // Marker, time and then the timezone
+    assert!(
+        bytes.ends_with(&[0x0B, 0x42, 0x74, 0x83, 0x8E, 0xE0, 0x7D, 0x70, 0x00, 0xFE, 0x20, 0x00])
+    );
+    let (_, read) = Reader::default().parse(&bytes).unwrap();
+    assert_eq!(*read.body[0].value, date);
+
+    // Amf3 dates are always utc, so the timezone is dropped
+    let amf3 = Lso::new(vec![Element::new("when", date)], "test", AMFVersion::AMF3);
+    let bytes = flash_lso::write::to_bytes(&amf3).unwrap();
+    let (_, read) = Reader::default().parse(&bytes).unwrap();
+    assert_eq!(*read.body[0].value, Value::Date(1409704396759.0, None));
+}
+
+#[test]
+fn read_parse_many() {
+    use flash_lso::read::parse_many;
+
+    let valid = include_bytes!("sol/AS2-Demo.sol").to_vec();
+    let mut bad_reference = include_bytes!("sol/AS3-DateReference-Demo.sol").to_vec();
+    // Point the date reference at an object that hasn't been read
+    let last = bad_reference.len() - 2;
+    bad_reference[last] = 0x7E;
+
+    let results = parse_many(vec![
+        valid.clone(),
+        b"not an lso".to_vec(),
+        bad_reference,
+        vec![],
+        valid,
+    ]);
+    assert_eq!(results.len(), 5);
+    assert_eq!(results[0].as_ref().unwrap().header.name, "AS2-Demo");
+    assert_eq!(results[1].as_ref().unwrap_err(), &(Error::BadMagic, 0));
+    assert!(matches!(results[2], Err((Error::Nom(&[], _), 63))));
+    assert!(results[3].is_err());
+    assert_eq!(results[4].as_ref().unwrap().header.name, "AS2-Demo");
+}
+
+#[test]
+fn value_is_externalized() {
+    use flash_lso::types::{Attribute, ClassDefinition, Element, Value};
+
+    let external = ClassDefinition {
+        attributes: Attribute::External.into(),
+        ..ClassDefinition::default_with_name("flex.messaging.io.ArrayCollection".to_string())
+    };
+    let sealed = ClassDefinition::default_with_name("com.Point".to_string());
+
+    assert!(
+        Value::Custom(vec![Element::new("a", 1.0)], vec![], Some(external.clone()))
+            .is_externalized()
+    );
+    assert!(Value::Object(vec![], Some(external)).is_externalized());
+    assert!(!Value::Custom(vec![], vec![], Some(sealed.clone())).is_externalized());
+    assert!(!Value::Object(vec![], Some(sealed)).is_externalized());
+    assert!(!Value::Custom(vec![], vec![], None).is_externalized());
+    assert!(!Value::Number(1.0).is_externalized());
+}
+
+#[test]
+fn deeply_nested_values_dont_overflow_the_stack() {
+    use flash_lso::amf0::read::AMF0Decoder;
+    use flash_lso::amf3::read::AMF3Decoder;
+    use flash_lso::amf3::visit::Amf3Visitor;
+    use flash_lso::types::Value;
+    use std::rc::Rc;
+
+    const DEPTH: usize = 100_000;
+
+    /// Get how many containers are nested in a value, following the first value of each
+    fn depth(value: &Value) -> usize {
+        let mut depth = 0;
+        let mut inner = value;
+        loop {
+            inner = match inner {
+                Value::StrictArray(items) => &items[0],
+                Value::Object(elements, _) => &elements[0].value,
+                Value::Null => return depth,
+                v => panic!("Unexpected value {:?}", v),
+            };
+            depth += 1;
+        }
+    }
+
+    // A recursive reader would need many times this much stack
+    std::thread::Builder::new()
+        .stack_size(256 * 1024)
+        .spawn(|| {
+            // Arrays holding the next array, with a null in the innermost
+            let mut amf3 = [0x09, 0x03, 0x01].repeat(DEPTH);
+            amf3.push(0x01);
+
+            let unlimited = || {
+                let mut decoder = AMF3Decoder::default();
+                decoder.max_depth = None;
+                decoder
+            };
+            let (_, value) = unlimited().parse_single_element(&amf3).unwrap();
+            assert_eq!(depth(&value), DEPTH);
+            assert!(unlimited()
+                .parse_single_element(&amf3[..amf3.len() - 1])
+                .is_err());
+
+            // The visitor is given each array as it is read, in the same way
+            struct Arrays(usize);
+            impl Amf3Visitor for Arrays {
+                fn on_array_start(&mut self, _dense_len: usize) {
+                    self.0 += 1;
+                }
+            }
+            let mut body = vec![0x03, b'a'];
+            body.extend_from_slice(&amf3);
+            body.push(0x00);
+            let mut arrays = Arrays(0);
+            unlimited().parse_with_visitor(&body, &mut arrays).unwrap();
+            assert_eq!(arrays.0, DEPTH);
+
+            // Objects each with a property holding the next, with a null in the innermost
+            let mut amf0 = vec![0x00, 0x01, b'a'];
+            amf0.extend([0x03, 0x00, 0x01, b'x'].repeat(DEPTH));
+            amf0.push(0x05);
+            amf0.extend([0x00, 0x00, 0x09].repeat(DEPTH));
+            amf0.push(0x00);
+
+            let mut amf0_decoder = AMF0Decoder::default();
+            amf0_decoder.max_depth = None;
+            let (_, mut body) = amf0_decoder.parse_body(&amf0).unwrap();
+            assert_eq!(depth(&body[0].value), DEPTH);
+            drop(amf0_decoder);
+
+            // Dropping these normally would be recursive
+            Rc::try_unwrap(value).unwrap().drop_iteratively();
+            Rc::try_unwrap(body.remove(0).value)
+                .unwrap()
+                .drop_iteratively();
+
+            // As are values built by hand
+            let mut nested = Value::Null;
+            for _ in 0..DEPTH {
+                nested = Value::StrictArray(vec![Rc::new(nested)]);
+            }
+            assert_eq!(depth(&nested), DEPTH);
+            assert_eq!(nested.count_nodes(), DEPTH + 1);
+            assert!(nested.heap_size() > DEPTH * std::mem::size_of::<Value>());
+            nested.drop_iteratively();
+        })
+        .unwrap()
+        .join()
+        .unwrap();
+}
+
+#[test]
+fn nesting_is_limited_by_max_depth() {
+    use flash_lso::amf0::read::AMF0Decoder;
+    use flash_lso::amf3::read::{decode_one, DEFAULT_MAX_DEPTH};
+    use flash_lso::errors::Error;
+    use flash_lso::types::{AMFVersion, Element, Lso};
+
+    // Dynamic objects each with a property holding the next, with a null in the innermost
+    let amf3 = |depth: usize| {
+        let mut amf3 = vec![0x0a, 0x0b, 0x01, 0x03, b'x'];
+        amf3.extend([0x0a, 0x01, 0x00].repeat(depth - 1));
+        amf3.push(0x01);
+        amf3.extend(vec![0x01; depth]);
+        amf3
+    };
+    assert!(matches!(
+        decode_one(&amf3(DEFAULT_MAX_DEPTH + 1)),
+        Err(Error::Nom(_, ErrorKind::TooLarge))
+    ));
+    assert!(decode_one(&[0x09, 0x03, 0x01].repeat(200_000)).is_err());
+
+    let mut amf0 = vec![0x00, 0x01, b'a'];
+    amf0.extend([0x03, 0x00, 0x01, b'x'].repeat(DEFAULT_MAX_DEPTH + 1));
+    amf0.push(0x05);
+    amf0.extend([0x00, 0x00, 0x09].repeat(DEFAULT_MAX_DEPTH + 1));
+    amf0.push(0x00);
+    assert!(AMF0Decoder::default().parse_with_offsets(&amf0).is_err());
+    let mut amf0_decoder = AMF0Decoder::default();
+    amf0_decoder.max_depth = None;
+    assert!(amf0_decoder.parse_with_offsets(&amf0).is_ok());
+
+    // Values as deep as the limit can be used normally, the writer needs the most stack per level
+    std::thread::Builder::new()
+        .stack_size(4 * 1024 * 1024)
+        .spawn(move || {
+            let input = amf3(DEFAULT_MAX_DEPTH);
+            let (value, rest) = decode_one(&input).unwrap();
+            assert!(rest.is_empty());
+            assert_eq!(value.count_nodes(), DEFAULT_MAX_DEPTH + 1);
+            assert_eq!(value.as_ref().clone(), *value);
+
+            let lso = Lso::new(
+                vec![Element {
+                    name: "a".into(),
+                    value,
+                }],
+                "Deep",
+                AMFVersion::AMF3,
+            );
+            let bytes = flash_lso::write::to_bytes(&lso).unwrap();
+            let (_, read) = Reader::default().parse(&bytes).unwrap();
+            assert_eq!(read.body, lso.body);
+            assert_eq!(read.content_hash(true), lso.content_hash(true));
+        })
+        .unwrap()
+        .join()
+        .unwrap();
+}
+
+#[test]
+fn amf3_intern_names() {
+    use flash_lso::amf3::read::AMF3Decoder;
+    use flash_lso::types::Value;
+
+    // Two dynamic objects each with an `x` member, the second refers to the name in the string table
+    let body = [
+        0x03, b'a', 0x0A, 0x0B, 0x01, 0x03, b'x', 0x04, 0x01, 0x01, 0x00, //
+        0x03, b'b', 0x0A, 0x01, 0x02, 0x04, 0x02, 0x01, 0x00,
+    ];
+
+    let member =
+        |elements: &[flash_lso::types::Element], index: usize| match &*elements[index].value {
+            Value::Object(members, _) => std::rc::Rc::clone(&members[0].name),
+            v => panic!("Expected an object, got {:?}", v),
+        };
+
+    let mut decoder = AMF3Decoder::default();
+    let (_, elements) = decoder.parse_body(&body).unwrap();
+    assert_eq!(&*member(&elements, 0), "x");
+    assert!(!std::rc::Rc::ptr_eq(
+        &member(&elements, 0),
+        &member(&elements, 1)
+    ));
+
+    let mut decoder = AMF3Decoder::default();
+    decoder.intern_names = true;
+    let (_, interned) = decoder.parse_body(&body).unwrap();
+    assert_eq!(interned, elements);
+    assert!(std::rc::Rc::ptr_eq(
+        &member(&interned, 0),
+        &member(&interned, 1)
+    ));
+}
+
+#[test]
+fn lso_flatten_amf3() {
+    use flash_lso::types::{Element, Value};
+    use std::rc::Rc;
+
+    let data = include_bytes!("sol/AS2-AMF3Switch-Demo.sol");
+    let (_, mut sol) = Reader::default().parse(data).expect("Unable to parse");
+
+    let wrapped = Value::AMF3(Rc::new(Value::Integer(127)));
+    assert_eq!(wrapped.unwrap_amf3(), &Value::Integer(127));
+    assert_eq!(Value::Integer(127).unwrap_amf3(), &Value::Integer(127));
+    let unwrapped: Vec<Value> = sol
+        .body
+        .iter()
+        .map(|e| e.value.unwrap_amf3().clone())
+        .collect();
+
+    sol.flatten_amf3();
+    assert_eq!(sol.body[2].value.as_ref(), &Value::Integer(127));
+    assert_eq!(
+        sol.body[3].value.as_ref(),
+        &Value::Object(
+            vec![Element::new(
+                "list",
+                Value::StrictArray(vec![
+                    Rc::new(Value::String("x".to_string())),
+                    Rc::new(Value::String("x".to_string())),
+                ])
+            )],
+            None
+        )
+    );
+    // The last element only has a nested wrapper
+    for (e, expected) in sol.body.iter().zip(unwrapped).take(3) {
+        assert_eq!(e.value.as_ref(), &expected);
+    }
+}
+
+#[cfg(feature = "base64")]
+#[test]
+fn base64_round_trip() {
+    use base64::engine::general_purpose::{STANDARD, URL_SAFE_NO_PAD};
+    use base64::Engine;
+    use flash_lso::read::parse_base64;
+    use flash_lso::write::to_base64;
+
+    let data = include_bytes!("sol/AS2-Demo.sol");
+    let (_, expected) = Reader::default().parse(data).unwrap();
+
+    // The length of the file means the standard encoding is padded, and it uses `+`
+    let standard = STANDARD.encode(data);
+    assert!(standard.ends_with("==") && standard.contains('+'));
+    assert_eq!(parse_base64(&standard).unwrap(), expected);
+    assert_eq!(
+        parse_base64(standard.trim_end_matches('=')).unwrap(),
+        expected
+    );
+
+    let url_safe = URL_SAFE_NO_PAD.encode(data);
+    assert_eq!(parse_base64(&format!(" {}\n", url_safe)).unwrap(), expected);
+
+    let written = to_base64(&expected, true).unwrap();
+    assert!(!written.contains(&['+', '/', '='][..]));
+    assert_eq!(parse_base64(&written).unwrap().body, expected.body);
+    let written = to_base64(&expected, false).unwrap();
+    assert!(written.ends_with('='));
+    assert_eq!(parse_base64(&written).unwrap().body, expected.body);
+
+    assert_eq!(parse_base64("not base64!"), Err(Error::InvalidBase64));
+    // Valid base64, but not an lso
+    assert_eq!(parse_base64("AAAA"), Err(Error::BadMagic));
+}
+
+#[test]
+fn amf3_bool_markers_round_trip() {
+    use flash_lso::types::Value;
+    use flash_lso::write::to_bytes;
+
+    // AS3-Boolean-Demo with a false element added after the true one
+    let mut data = include_bytes!("sol/AS3-Boolean-Demo.sol").to_vec();
+    data.extend_from_slice(&[0x0F, b'm', b'y', b'F', b'a', b'l', b's', b'e', 0x02, 0x00]);
+    data[5] += 10;
+
+    let (_, lso) = Reader::default().parse(&data).unwrap();
+    assert_eq!(*lso.body[0].value, Value::Bool(true));
+    assert_eq!(*lso.body[1].value, Value::Bool(false));
+
+    let written = to_bytes(&lso).unwrap();
+    assert_eq!(written, data);
+    // Each name is followed by its marker and then padding
+    assert_eq!(
+        &written[written.len() - 12..written.len() - 10],
+        &[0x03, 0x00]
+    );
+    assert_eq!(&written[written.len() - 2..], &[0x02, 0x00]);
+}
+
+#[test]
+fn diff_lsos() {
+    use flash_lso::diff::{diff, Change};
+    use flash_lso::types::{AMFVersion, ClassDefinition, Element, Lso, Value};
+    use std::rc::Rc;
+
+    let player = |x: f64, items: Vec<&str>| {
+        Value::object(
+            vec![
+                Element::new("x", x),
+                Element::new("items", Value::strict_array(items)),
+            ],
+            None,
+        )
+    };
+    let old = Lso::new(
+        vec![
+            Element::new("player", player(1.0, vec!["a"])),
+            Element::new("score", f64::NAN),
+            Element::new("removed", true),
+            Element::new(
+                "typed",
+                Value::object(vec![], Some(ClassDefinition::default_with_name("A".into()))),
+            ),
+        ],
+        "save",
+        AMFVersion::AMF3,
+    );
+    assert!(diff(&old, &old).is_empty());
+
+    let new = Lso::new(
+        vec![
+            Element::new("added", 1),
+            Element::new(
+                "typed",
+                Value::object(vec![], Some(ClassDefinition::default_with_name("B".into()))),
+            ),
+            Element::new("score", f64::NAN),
+            // Wrapping a value isn't a change, but its contents are still compared
+            Element::new("player", Value::AMF3(Rc::new(player(2.0, vec!["a", "b"])))),
+        ],
+        "renamed",
+        AMFVersion::AMF0,
+    );
+
+    let changes = diff(&old, &new);
+    assert_eq!(
+        changes,
+        vec![
+            Change::Modified {
+                path: "player.x".to_string(),
+                old: Rc::new(Value::Number(1.0)),
+                new: Rc::new(Value::Number(2.0)),
+            },
+            Change::Added {
+                path: "player.items.1".to_string(),
+                value: Rc::new("b".into()),
+            },
+            Change::Removed {
+                path: "removed".to_string()
+            },
+            Change::Modified {
+                path: "typed".to_string(),
+                old: old.body[3].value.clone(),
+                new: new.body[1].value.clone(),
+            },
+            Change::Added {
+                path: "added".to_string(),
+                value: Rc::new(Value::Integer(1)),
+            },
+        ]
+    );
+    assert_eq!(changes[1].path(), "player.items.1");
+
+    let reversed = diff(&new, &old);
+    assert!(reversed.contains(&Change::Removed {
+        path: "player.items.1".to_string()
+    }));
+    assert!(reversed.contains(&Change::Added {
+        path: "removed".to_string(),
+        value: Rc::new(Value::Bool(true)),
+    }));
+}
+
+#[test]
+fn amf3_vectors_with_the_largest_length_are_too_large() {
+    use flash_lso::amf3::read::AMF3Decoder;
+
+    // VectorInt, VectorUInt and VectorDouble declaring 0x0fff_ffff items, followed by only a few bytes
+    for marker in [0x0D, 0x0E, 0x0F] {
+        let data = [marker, 0xff, 0xff, 0xff, 0xff, 0x00, 0x00, 0x00, 0x00, 0x01];
+        assert!(matches!(
+            AMF3Decoder::default().parse_single_element(&data),
+            Err(nom::Err::Error(Error::Nom(_, ErrorKind::TooLarge)))
+        ));
+    }
+}
+
+#[test]
+fn value_index() {
+    use flash_lso::types::{Element, Value};
+    use std::rc::Rc;
+
+    let save = Value::object(
+        vec![
+            Element::new(
+                "player",
+                Value::object(vec![Element::new("hp", 10), Element::new("hp", 20)], None),
+            ),
+            Element::new(
+                "items",
+                Value::AMF3(Rc::new(Value::strict_array(vec!["sword", "shield"]))),
+            ),
+            Element::new(
+                "mixed",
+                Value::ECMAArray(
+                    vec![Rc::new(Value::Bool(true))],
+                    vec![Element::new("key", "value")],
+                    1,
+                ),
+            ),
+        ],
+        None,
+    );
+
+    // The last of repeated names is used
+    assert_eq!(save["player"]["hp"], Value::Integer(20));
+    assert_eq!(save["items"][1], Value::from("shield"));
+    assert_eq!(save["mixed"][0], Value::Bool(true));
+    assert_eq!(save["mixed"]["key"], Value::from("value"));
+    assert_eq!(
+        save.get("items").and_then(|v| v.get_index(0)),
+        Some(&"sword".into())
+    );
+
+    // Misses give null rather than panicking
+    assert_eq!(save["missing"]["hp"], Value::Null);
+    assert_eq!(save["items"][2], Value::Null);
+    assert_eq!(save["player"][0], Value::Null);
+    assert_eq!(save.get("missing"), None);
+    assert_eq!(Value::VectorInt(vec![1], false).get_index(0), None);
+}
+
+#[test]
+fn amf3_invalid_strings() {
+    use flash_lso::amf3::read::AMF3Decoder;
+
+    let body = [
+        // An object with a Latin-1 string value, and a Latin-1 property name
+        0x03, b'p', 0x0A, 0x0B, 0x01, 0x09, b'n', b'a', b'm', b'e', 0x06, 0x05, 0xe9, b'x', 0x03,
+        0xe9, 0x06, 0x05, b'o', b'k', 0x01, 0x00, //
+        // An array with an invalid string as its second item
+        0x09, b'l', b'i', b's', b't', 0x09, 0x05, 0x01, 0x06, 0x03, b'a', 0x06, 0x05, 0xff, b'y',
+        0x00,
+    ];
+
+    let mut decoder = AMF3Decoder::default();
+    assert!(decoder.parse_body(&body).is_err());
+
+    decoder.record_invalid_strings = true;
+    let (_, elements) = decoder.parse_body(&body).unwrap();
+    assert_eq!(elements[0].value["name"], "\u{fffd}x".into());
+    assert_eq!(elements[0].value["\u{fffd}"], "ok".into());
+    assert_eq!(
+        decoder.invalid_strings(),
+        &[
+            ("p.name".to_string(), vec![0xe9, b'x']),
+            ("p".to_string(), vec![0xe9]),
+            ("list.1".to_string(), vec![0xff, b'y']),
+        ]
+    );
+    assert_eq!(decoder.lossy_string_offsets().len(), 3);
+
+    // Spans are still complete when recorded at the same time
+    let (_, (_, spans)) = decoder.parse_with_spans(&body).unwrap();
+    let paths: Vec<&str> = spans.iter().map(|s| s.path.as_str()).collect();
+    assert_eq!(
+        paths,
+        vec!["p", "p.name", "p.\u{fffd}", "list", "list.0", "list.1"]
+    );
+    assert_eq!(decoder.invalid_strings().len(), 3);
+
+    decoder.reset();
+    assert!(decoder.invalid_strings().is_empty());
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn value_to_vec() {
+    use flash_lso::types::{ClassDefinition, Element, Value};
+    use serde::Deserialize;
+    use std::rc::Rc;
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Item {
+        id: u32,
+        name: String,
+    }
+
+    let item = |id: i32, name: &str| {
+        Rc::new(Value::object(
+            vec![Element::new("id", id), Element::new("name", name)],
+            Some(ClassDefinition::default_with_name("Item".to_string())),
+        ))
+    };
+    let vector = Value::VectorObject(
+        vec![item(1, "sword"), item(2, "shield")],
+        "Item".into(),
+        false,
+    );
+    let expected = vec![
+        Item {
+            id: 1,
+            name: "sword".to_string(),
+        },
+        Item {
+            id: 2,
+            name: "shield".to_string(),
+        },
+    ];
+    assert_eq!(vector.to_vec::<Item>(), Ok(expected));
+
+    let array = Value::AMF3(Rc::new(Value::strict_array(vec![1.0, 2.0])));
+    assert_eq!(array.to_vec::<u8>(), Ok(vec![1, 2]));
+    // An item that doesn't match fails the whole conversion
+    assert!(Value::strict_array(vec!["a"]).to_vec::<Item>().is_err());
+    // Other containers aren't converted, even if they could be deserialized as a sequence
+    assert!(Value::VectorInt(vec![1], false).to_vec::<i32>().is_err());
+
+    assert_eq!(
+        Value::VectorInt(vec![-1, 2], true).to_vec_i32(),
+        Some(vec![-1, 2])
+    );
+    assert_eq!(
+        Value::VectorUInt(vec![3], false).to_vec_u32(),
+        Some(vec![3])
+    );
+    assert_eq!(
+        Value::AMF3(Rc::new(Value::VectorDouble(vec![0.5], false))).to_vec_f64(),
+        Some(vec![0.5])
+    );
+    assert_eq!(Value::VectorDouble(vec![0.5], false).to_vec_i32(), None);
+    assert_eq!(Value::strict_array(vec![1]).to_vec_i32(), None);
+}
+
+#[test]
+fn amf3_max_string_len() {
+    use flash_lso::amf3::read::AMF3Decoder;
+
+    let abc = [0x06, 0x07, b'a', b'b', b'c'];
+    // A string declaring 0x0fff_ffff bytes, followed by only a few
+    let huge = [0x06, 0xff, 0xff, 0xff, 0xff, b'a', b'b', b'c'];
+    // An object with a property name declared as 4 bytes long
+    let long_name = [0x0A, 0x0B, 0x01, 0x09, b'n', b'a', b'm', b'e', 0x01, 0x01];
+
+    let mut decoder = AMF3Decoder::default();
+    decoder.max_string_len = Some(3);
+    assert!(decoder.parse_single_element(&abc).is_ok());
+    for data in [&huge[..], &long_name[..]] {
+        assert!(matches!(
+            decoder.parse_single_element(data),
+            Err(nom::Err::Error(Error::Nom(_, ErrorKind::TooLarge)))
+        ));
+    }
+
+    // Unlimited by default, so the huge string only fails once there isn't enough input
+    let mut decoder = AMF3Decoder::default();
+    assert!(decoder.parse_single_element(&long_name).is_ok());
+    assert!(!matches!(
+        decoder.parse_single_element(&huge),
+        Err(nom::Err::Error(Error::Nom(_, ErrorKind::TooLarge)))
+    ));
+}
+
+#[test]
+#[cfg(feature = "flex")]
+fn value_externalizable() {
+    use flash_lso::amf3::read::AMF3Decoder;
+    use flash_lso::amf3::write::AMF3Encoder;
+    use flash_lso::types::{Attribute, ClassDefinition, Element, Value};
+
+    let items = Value::strict_array(vec![1, 2]);
+    let collection = Value::externalizable(
+        "flex.messaging.io.ArrayCollection",
+        vec![Element::new("data", items.clone())],
+        Vec::new(),
+    );
+    assert!(collection.is_externalized());
+    assert!(collection.unwritten_elements().is_empty());
+
+    let bytes = AMF3Encoder::with_flex_types()
+        .encode_one(&collection)
+        .unwrap();
+    let (decoded, rest) = AMF3Decoder::with_flex_types().decode_one(&bytes).unwrap();
+    assert!(rest.is_empty());
+    assert_eq!(decoded.as_ref(), &collection);
+
+    // Regular elements of an externalizable class are not written
+    let extra = vec![Element::new("extra", Value::Integer(1))];
+    let with_extra = Value::externalizable(
+        "flex.messaging.io.ArrayCollection",
+        vec![Element::new("data", items.clone())],
+        extra.clone(),
+    );
+    assert_eq!(with_extra.unwritten_elements(), &extra[..]);
+
+    // Without `Attribute::External` the custom elements are dropped instead
+    let custom = vec![Element::new("data", items)];
+    let not_external = Value::custom(
+        custom.clone(),
+        extra,
+        Some(ClassDefinition {
+            attributes: Attribute::Dynamic.into(),
+            ..ClassDefinition::default_with_name("flex.messaging.io.ArrayCollection".to_string())
+        }),
+    );
+    assert!(!not_external.is_externalized());
+    assert_eq!(not_external.unwritten_elements(), &custom[..]);
+    assert!(Value::Integer(1).unwritten_elements().is_empty());
+}
+
+#[test]
+fn amf3_marker_counts() {
+    use flash_lso::amf3::read::AMF3Decoder;
+    use flash_lso::amf3::type_marker::TypeMarker;
+
+    // An array of 1, "a" and a reference to "a"
+    let data = [0x09, 0x07, 0x01, 0x04, 0x01, 0x06, 0x03, b'a', 0x06, 0x00];
+
+    let mut decoder = AMF3Decoder::default();
+    decoder.parse_single_element(&data).unwrap();
+    assert!(decoder.marker_counts().is_empty());
+
+    decoder.record_marker_counts = true;
+    decoder.parse_single_element(&data).unwrap();
+    let counts = decoder.marker_counts();
+    assert_eq!(counts.len(), 3);
+    assert_eq!(counts[&TypeMarker::Array], 1);
+    assert_eq!(counts[&TypeMarker::Integer], 1);
+    assert_eq!(counts[&TypeMarker::String], 2);
+
+    // Counts are kept across a reset
+    decoder.reset();
+    decoder.parse_single_element(&data).unwrap();
+    assert_eq!(decoder.marker_counts()[&TypeMarker::String], 4);
+}
+
+#[test]
+fn amf3_array_dense_length_checked_after_assoc() {
+    use flash_lso::amf3::read::AMF3Decoder;
+    use flash_lso::amf3::visit::Amf3Visitor;
+
+    struct NoopVisitor;
+    impl Amf3Visitor for NoopVisitor {}
+
+    // An array declaring 100 dense values, which fits in the input until the associative part has been read
+    let data = include_bytes!("regressions/amf3-array-huge-dense-after-assoc.bin");
+    // Skip the element name, the body ends with the empty key and the padding
+    let array = &data[2..];
+
+    match AMF3Decoder::default().parse_single_element(array) {
+        Err(nom::Err::Error(Error::Nom(rest, ErrorKind::TooLarge))) => assert_eq!(rest, &[0x00]),
+        r => panic!("Expected TooLarge, got {:?}", r),
+    }
+    assert!(matches!(
+        AMF3Decoder::default().parse_with_visitor(data, &mut NoopVisitor),
+        Err(nom::Err::Error(Error::Nom(_, ErrorKind::TooLarge)))
+    ));
+}
+
+#[test]
+fn detect_version() {
+    use flash_lso::read::{detect_version, parse_header};
+    use flash_lso::types::AMFVersion;
+
+    let amf0 = include_bytes!("sol/AS2-Demo.sol");
+    let amf3 = include_bytes!("sol/AS3-Demo.sol");
+    assert_eq!(detect_version(amf0), Some(AMFVersion::AMF0));
+    assert_eq!(detect_version(amf3), Some(AMFVersion::AMF3));
+
+    // Bodies without a header are recognised by their first element
+    let (_, offset) = parse_header(amf0).unwrap();
+    assert_eq!(detect_version(&amf0[offset..]), Some(AMFVersion::AMF0));
+    let (_, offset) = parse_header(amf3).unwrap();
+    assert_eq!(detect_version(&amf3[offset..]), Some(AMFVersion::AMF3));
+    assert_eq!(
+        detect_version(&[0x00, 0x01, b'a', 0x00, 0, 0, 0, 0, 0, 0, 0, 0]),
+        Some(AMFVersion::AMF0)
+    );
+    assert_eq!(
+        detect_version(&[0x03, b'a', 0x04, 0x01]),
+        Some(AMFVersion::AMF3)
+    );
+
+    assert_eq!(detect_version(&[]), None);
+    assert_eq!(detect_version(b"not an lso"), None);
+    // An empty name isn't the start of a body in either version
+    assert_eq!(detect_version(&[0x01, 0x04, 0x01]), None);
+}
+
+#[test]
+fn amf3_integer_out_of_range_written_as_number() {
+    use flash_lso::amf3::read::AMF3Decoder;
+    use flash_lso::amf3::write::AMF3Encoder;
+    use flash_lso::types::Value;
+
+    let max = (1 << 28) - 1;
+    let min = -(1 << 28);
+
+    for i in [0, max, min] {
+        let bytes = AMF3Encoder::default()
+            .encode_one(&Value::Integer(i))
+            .unwrap();
+        assert_eq!(bytes[0], 0x04);
+        let (value, _) = AMF3Decoder::default().decode_one(&bytes).unwrap();
+        assert_eq!(*value, Value::Integer(i));
+    }
+
+    for i in [max + 1, min - 1, i32::MAX, i32::MIN] {
+        let bytes = AMF3Encoder::default()
+            .encode_one(&Value::Integer(i))
+            .unwrap();
+        let mut expected = vec![0x05];
+        expected.extend_from_slice(&f64::from(i).to_be_bytes());
+        assert_eq!(bytes, expected);
+        let (value, _) = AMF3Decoder::default().decode_one(&bytes).unwrap();
+        assert_eq!(*value, Value::Number(f64::from(i)));
+    }
+}
+
+#[test]
+fn value_path() {
+    use flash_lso::types::{Element, Value};
+
+    let data = include_bytes!("sol/AS3-Demo.sol");
+    let (_, lso) = Reader::default().parse(data).unwrap();
+    let root = Value::object(lso.body.clone(), None);
+
+    // Every path given by `walk_mut` can be looked up, other than the contents of dictionaries and names with a `[`
+    let mut lso = lso;
+    let mut paths = Vec::new();
+    let mut dictionaries = Vec::new();
+    lso.walk_mut(|path, value| {
+        if matches!(value, Value::Dictionary(..)) {
+            dictionaries.push(format!("{}.", path));
+        }
+        if !path.contains('[') && !dictionaries.iter().any(|d| path.starts_with(d.as_str())) {
+            paths.push(path.to_string());
+        }
+    });
+    assert!(!paths.is_empty());
+    for path in &paths {
+        assert!(root.path(path).is_some(), "{} not found", path);
+    }
+
+    let items = Value::strict_array(vec![Value::object(vec![Element::new("c", 5)], None)]);
+    let value = Value::object(
+        vec![
+            Element::new("a", Value::object(vec![Element::new("b", items)], None)),
+            Element::new(
+                "grid",
+                Value::strict_array(vec![Value::strict_array(vec![1, 2])]),
+            ),
+        ],
+        None,
+    );
+    assert_eq!(
+        value.path("a.b[0].c").map(|v| v.as_ref()),
+        Some(&Value::Integer(5))
+    );
+    assert_eq!(value.path("a.b.0.c"), value.path("a.b[0].c"));
+    assert_eq!(
+        value.path("grid[0][1]").map(|v| v.as_ref()),
+        Some(&Value::Integer(2))
+    );
+
+    // Missing segments
+    assert_eq!(value.path("a.x.c"), None);
+    assert_eq!(value.path("a.b[0].c.d"), None);
+    // Out of bounds
+    assert_eq!(value.path("a.b[1]"), None);
+    assert_eq!(value.path("grid[0][2]"), None);
+    // Indexing something that isn't an array
+    assert_eq!(value.path("a[0]"), None);
+    // Invalid paths
+    for path in ["", ".", "a.", "a..b", "a.b[", "a.b[x]", "a.b[0", "a.b]0["] {
+        assert_eq!(value.path(path), None, "{}", path);
+    }
+}
+
+#[test]
+fn normalize_dates() {
+    use flash_lso::types::{Element, Lso, Value};
+    use std::rc::Rc;
+
+    let amf0 = Value::Date(1_600_000_000_000.0, Some(60));
+    let amf3 = Value::AMF3(Rc::new(Value::Date(1_600_000_000_000.0, None)));
+    assert_ne!(amf0, *amf3.unwrap_amf3());
+    assert!(amf0.same_instant(&amf3));
+    assert!(amf3.same_instant(&amf0));
+    assert!(!amf0.same_instant(&Value::Date(0.0, Some(60))));
+    assert!(!amf0.same_instant(&Value::Number(1_600_000_000_000.0)));
+
+    let mut lso = Lso::new(
+        vec![
+            Element::new("date", amf0.clone()),
+            Element::new(
+                "dates",
+                Value::strict_array(vec![amf0, Value::Date(0.0, None)]),
+            ),
+            Element::new("wrapped", amf3),
+        ],
+        "dates",
+        flash_lso::types::AMFVersion::AMF0,
+    );
+    lso.normalize_dates();
+    assert_eq!(*lso.body[0].value, Value::Date(1_600_000_000_000.0, None));
+    assert_eq!(
+        *lso.body[1].value,
+        Value::strict_array(vec![
+            Value::Date(1_600_000_000_000.0, None),
+            Value::Date(0.0, None)
+        ])
+    );
+    assert_eq!(lso.body[2].value.unwrap_amf3(), &*lso.body[0].value);
+}
+
+#[test]
+fn parse_all_concatenated() {
+    use flash_lso::read::parse_all;
+    use flash_lso::types::{AMFVersion, Value};
+
+    let data = include_bytes!("sol/Concatenated-Demo.sol");
+    let lsos = parse_all(data).unwrap();
+    assert_eq!(lsos.len(), 2);
+    assert_eq!(lsos[0].header.name, "AS2-Null-Demo");
+    assert_eq!(lsos[0].header.format_version, AMFVersion::AMF0);
+    assert_eq!(lsos[1].header.name, "AS3-Null-Demo");
+    assert_eq!(lsos[1].header.format_version, AMFVersion::AMF3);
+    for lso in &lsos {
+        assert_eq!(lso.body[0].name(), "myNull");
+        assert_eq!(lso.body[0].value(), &Value::Null);
+    }
+
+    // A single file is also accepted
+    assert_eq!(
+        parse_all(include_bytes!("sol/AS2-Null-Demo.sol")).unwrap(),
+        lsos[..1]
+    );
+    assert!(parse_all(&[]).unwrap().is_empty());
+
+    // The second file is cut short
+    assert!(matches!(
+        parse_all(&data[..data.len() - 1]),
+        Err(Error::LengthMismatch {
+            declared: 0x26,
+            actual: 0x25
+        })
+    ));
+    // Trailing bytes that aren't a header
+    let mut trailing = data.to_vec();
+    trailing.push(0);
+    assert!(parse_all(&trailing).is_err());
+}
+
+#[test]
+fn lso_canonicalize() {
+    use flash_lso::read::Reader;
+    use flash_lso::types::{AMFVersion, Attribute, ClassDefinition, Element, Lso, Value};
+    use flash_lso::write::to_bytes;
+
+    let point = |elements: Vec<Element>, static_properties: &[&str]| {
+        Value::Object(
+            elements,
+            Some(ClassDefinition {
+                attributes: Attribute::Dynamic.into(),
+                static_properties: static_properties.iter().map(|p| p.to_string()).collect(),
+                ..ClassDefinition::default_with_name("Point".to_string())
+            }),
+        )
+    };
+
+    let mut a = Lso::new(
+        vec![
+            Element::new(
+                "point",
+                point(
+                    vec![
+                        Element::new("y", Value::Number(2.0)),
+                        Element::new("x", Value::Integer(1)),
+                        Element::new("label", "a"),
+                    ],
+                    &["y", "x"],
+                ),
+            ),
+            Element::new("date", Value::Date(0.0, None)),
+            Element::new(
+                "array",
+                Value::ECMAArray(
+                    Vec::new(),
+                    vec![Element::new("b", 2), Element::new("a", 1)],
+                    5,
+                ),
+            ),
+        ],
+        "canonical",
+        AMFVersion::AMF3,
+    );
+    let mut b = Lso::new(
+        vec![
+            Element::new(
+                "array",
+                Value::ecma_array(
+                    Vec::<Value>::new(),
+                    vec![Element::new("a", 1), Element::new("b", 2)],
+                ),
+            ),
+            Element::new("date", Value::Date(0.0, Some(60))),
+            Element::new(
+                "point",
+                point(
+                    vec![
+                        Element::new("label", "a"),
+                        Element::new("x", Value::Number(1.0)),
+                        Element::new("y", Value::Integer(2)),
+                    ],
+                    &["x", "y"],
+                ),
+            ),
+        ],
+        "canonical",
+        AMFVersion::AMF3,
+    );
+    assert_ne!(to_bytes(&a).unwrap(), to_bytes(&b).unwrap());
+
+    a.canonicalize();
+    b.canonicalize();
+    assert_eq!(a, b);
+    let bytes = to_bytes(&a).unwrap();
+    assert_eq!(bytes, to_bytes(&b).unwrap());
+    assert_eq!(a.content_hash(false), b.content_hash(false));
+
+    // Static properties are still written with their own values
+    let (_, read) = Reader::default().parse(&bytes).unwrap();
+    let point = read.body.iter().find(|e| e.name() == "point").unwrap();
+    assert_eq!(point.value().get("x"), Some(&Value::Integer(1)));
+    assert_eq!(point.value().get("y"), Some(&Value::Integer(2)));
+    assert_eq!(
+        point.value().get("label"),
+        Some(&Value::String("a".to_string()))
+    );
+
+    // AMF0 has no integers, so numbers are kept
+    let mut amf0 = Lso::new(
+        vec![Element::new("n", Value::Number(1.0))],
+        "canonical",
+        AMFVersion::AMF0,
+    );
+    amf0.canonicalize();
+    assert_eq!(amf0.body[0].value(), &Value::Number(1.0));
+}
+
+#[test]
+fn empty_input_and_body() {
+    use flash_lso::read::parse_header;
+    use flash_lso::types::{AMFVersion, Lso};
+    use flash_lso::write::to_bytes;
+
+    assert_eq!(
+        Reader::default().parse(&[]),
+        Err(nom::Err::Error(Error::EmptyInput))
+    );
+    assert_eq!(
+        Reader::default().parse_with_offsets(&[]),
+        Err((Error::EmptyInput, 0))
+    );
+    assert_eq!(parse_header(&[]), Err(Error::EmptyInput));
+    assert_eq!(
+        Error::EmptyInput.to_string(),
+        "Empty input, not an lso file"
+    );
+
+    for version in [AMFVersion::AMF0, AMFVersion::AMF3] {
+        let lso = Lso::new(Vec::new(), "empty", version);
+        let bytes = to_bytes(&lso).unwrap();
+        let mut strict = Reader {
+            strict: true,
+            ..Reader::default()
+        };
+        let (_, read) = strict.parse(&bytes).unwrap();
+        assert!(read.body.is_empty());
+        assert_eq!(read.header.name, "empty");
+        assert_eq!(read.header.format_version, version);
+
+        // Only a strict reader checks the length, so a header declaring no body is also accepted
+        let mut zero_length = bytes.clone();
+        zero_length[2..6].copy_from_slice(&[0; 4]);
+        let (_, read) = Reader::default().parse(&zero_length).unwrap();
+        assert!(read.body.is_empty());
+        assert_eq!(read.header.length, 0);
+    }
+}
+
+#[test]
+fn lso_byte_arrays() {
+    use flash_lso::amf3::read::{decode_one, is_amf3_value};
+    use flash_lso::amf3::write::AMF3Encoder;
+    use flash_lso::types::{AMFVersion, Attribute, ClassDefinition, Element, Lso, Value};
+    use std::rc::Rc;
+
+    let data = include_bytes!("sol/AS3-ByteArray-Demo.sol");
+    let (_, lso) = Reader::default().parse(data).unwrap();
+    let byte_arrays = lso.byte_arrays();
+    assert_eq!(byte_arrays.len(), 1);
+    assert_eq!(byte_arrays[0].0, lso.body[0].name());
+    assert_eq!(Some(byte_arrays[0].1), lso.body[0].value().as_bytes());
+
+    let object = Value::object(
+        vec![Element::new("name", "embedded")],
+        Some(ClassDefinition {
+            attributes: Attribute::Dynamic.into(),
+            ..ClassDefinition::default_with_name(String::new())
+        }),
+    );
+    let serialized = AMF3Encoder::default().encode_one(&object).unwrap();
+    let image = vec![0x89, b'P', b'N', b'G'];
+    let lso = Lso::new(
+        vec![
+            Element::new("image", Value::ByteArray(image.clone())),
+            Element::new(
+                "saves",
+                Value::strict_array(vec![
+                    Value::Integer(1),
+                    Value::AMF3(Rc::new(Value::ByteArray(serialized.clone()))),
+                ]),
+            ),
+        ],
+        "bytes",
+        AMFVersion::AMF3,
+    );
+    let byte_arrays = lso.byte_arrays();
+    assert_eq!(
+        byte_arrays,
+        vec![
+            ("image".to_string(), image.as_slice()),
+            ("saves.1".to_string(), serialized.as_slice()),
+        ]
+    );
+    assert!(!is_amf3_value(byte_arrays[0].1));
+    assert!(is_amf3_value(byte_arrays[1].1));
+    let (decoded, _) = decode_one(byte_arrays[1].1).unwrap();
+    assert_eq!(
+        decoded.get("name"),
+        Some(&Value::String("embedded".to_string()))
+    );
+
+    // Only a value followed by nothing else is accepted
+    assert!(!is_amf3_value(&[]));
+    assert!(!is_amf3_value(&[0x01, 0x01]));
+    assert_eq!(Value::Integer(1).as_bytes(), None);
 }
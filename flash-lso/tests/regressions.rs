@@ -0,0 +1,53 @@
+//! Checks that every input in `tests/regressions/` can be given to each parser without panicking
+
+use flash_lso::amf0::read::AMF0Decoder;
+use flash_lso::amf3::read::AMF3Decoder;
+use flash_lso::amf3::visit::Amf3Visitor;
+use flash_lso::read::Reader;
+use std::fs;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::Path;
+
+/// Ignores every value
+struct NoopVisitor;
+
+impl Amf3Visitor for NoopVisitor {}
+
+/// Parse `data` with every entry point, the results are ignored as only panics are of interest
+fn parse_all(data: &[u8]) {
+    let _ = Reader::default().parse(data);
+    let _ = Reader {
+        strict: true,
+        ..Reader::default()
+    }
+    .parse_with_offsets(data);
+    let _ = AMF0Decoder::default().parse_body(data);
+    let _ = AMF3Decoder::default().parse_body(data);
+    let _ = AMF3Decoder::default().parse_with_visitor(data, &mut NoopVisitor);
+    let _ = AMF3Decoder::default().decode_one(data);
+    #[cfg(feature = "flex")]
+    let _ = AMF3Decoder::with_flex_types().parse_body(data);
+    #[cfg(feature = "compression")]
+    let _ = flash_lso::read::parse_maybe_compressed(data);
+}
+
+#[test]
+fn regression_inputs_do_not_panic() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/regressions");
+    let mut paths: Vec<_> = fs::read_dir(&dir)
+        .expect("Unable to read regressions directory")
+        .map(|entry| entry.expect("Unable to read directory entry").path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "bin"))
+        .collect();
+    paths.sort();
+    assert!(!paths.is_empty(), "No inputs found in {}", dir.display());
+
+    let panicked: Vec<_> = paths
+        .iter()
+        .filter(|path| {
+            let data = fs::read(path).expect("Unable to read input");
+            panic::catch_unwind(AssertUnwindSafe(|| parse_all(&data))).is_err()
+        })
+        .collect();
+    assert!(panicked.is_empty(), "Parsing panicked for {:?}", panicked);
+}
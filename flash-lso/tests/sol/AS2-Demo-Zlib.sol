@@ -0,0 +1,81 @@
+xUU[0^Ɨ%b9CLmڦ}NҾ(0TQUDDJ,/KD,i!t1bL!ƘJ!bHxMg~}边7}ǇsZ{lYjvQڞHwךZf5/k-ײE;ֵ?}Z_{-M7kt׾WM23}-mjoJy?9^5z/>V릦twtvwvyKӔjw_՞	jik_ַ36yMwwsZnw~nw~rOnw5Ss z >A8x } ~  p |/` |_A0 <(]
+{!A0;A0hGI0~OI0{$}`(d~0XO?'$<I0X`'Q$<%I0XO?<?y <O?'`~dKk`D`L0ΟA0g~gL0x\	O3	}&<'ge3`Ŷ`L	?gO`}&D	O3'g~̟a0g3_`\	`\	g¿6ÿĿſsA).x<`?T͟.ϑ+9r?\Vse?\ݟ.˿sI>GT>ϑOs?\p.?\ZZDd[VQpiwcҪ#Ҧ㑜m>^piKGxxgG|xgGHЕ:R#CWxe\VxJ]:Iڮ:Yڮ:iv/xjGvxDjGvxjGvxjGvx.:ک㑭:ک㑮:ک㑯:J#aWxe\Vx*]t<rvGЮH:QZ#kWxe\Vxj]kt<wG5:F#uxe\OVDZj(T[E: 
+.1QpNK.x}F#wgt<r}F#wݟxO}S/T#w㑻?ݟxOu<rK#wt<rK#w|..]㑻ku<rwGݵ:.k}V#wu<r[#wu<r[#wu<r[#wu<r.=>^p^.
+.>zp靗ۻT\zeFK<a.&w{ϊpQދ^&
+.Ϫ}jG>Ϫ}jG>Ϫ}jG>Ϫ}jG>}jۧ}jۧ}jۧ}jۧ}jۧ}jۗ=roBE."\K(O(Qp*
+.? 
+."\D}nG}nG}nM#wkj[SݚG>r5ܭ}nM#wkj[SݚG>r5ܭ}n]#wj[WݺG>ruܭ}n]#wj[WݺG>ruܭ}:]>rwGSujN#wש}:]>rwGSujN#wש}sjG>ϩ}sjG>ϩ}sjG>ϩ}sjG>ϩ}z]>rwGWj^#w׫}z]>rwGWj^#w'wo秋Qpy~(<"
+./[E^Qpy~@\O>?&	.ELpQjA#w7}ݠ>rwGnPjA#w7}ݠ>rwGnP>r}A#w_P>r}A#w_P>r}A#w_P>r}A#w_PݍjQ#w7}Fݨ>rwGnTݍjQ#w7}Fݨ>rwGnT>rE}Q#w_T>rE}Q#w_T>rE}Q#w_T>rE}Q#w_TMjI#w7}&ݤ>rwGnRMjI#w7}&ݤ>rwGnRݟ}>rgjܾ?k-zEga.c"\Ίp}rg"\@(L20S\*20G\t&݁rw`%rw`3rw`Arw`Orw`(;Kpѹ&wE̓E݁"\+L+"\cQp$
+.fYGnfYGnV[Deݬ#w7ͭ"\fYGn#w7?rwYGnV'D?rw<)EGnV&Qp٢#w-?rwKE\-?rw"
+.[EGni#w-?rwˀEGnQ[D-?rwK-"\.p(I.WD\rC\^jr=@4]\^)
+./UD9<Qpyi("
+./-TKݗZE+e.C"\Epr"\K"\K?r%Ҥ}IG6ˠT?rw"
+.?rwPGQpT?rwPGpQ?rwPGpQ?rwPGpQ?rwPGNpQ?rwPGNpQ?rwPGnm?rwݪ#wVDeݪ#wݭ-UGnU[V.?rwݪ#wpQ[VuLݪ#wݭ"\V5ݭ"\p&(lkmSEetQp6S\UDe[扂˶ಭE\-Tr."\ֈp.C"\Ep2."\&DmRݦ#wM]GnW(lWvE\?rw*EGnW.?rwݮ#wpQv}Bݮ#w"\v]GAeHG!;T!;#wP(!;#wZE!;#wD!;#wD!;#w&D!;#w&E!;or(<U\^.
+./+Qpyy(@\^ns- w_^*eV.kDpY/e@ːa./prVy."\.p(I./_#ww4CGP;*CGP;Qp١#ww?rwGCGP;D?rw1&EGP;	.?rwݡ#wwLpQ;I\vݝ?rwY?rwݩ#wwNSGT;[Eݝ?rw9 EGT;1.?rwݩ#wwNpQ;NsRݩ#ww]MKGRrU]sDe<Qpٵ@\vˮ"\pY.¥UKzE2 eH˰Q.c"\E	.D\rYˤ+"\p!
+.Dewn"
+.n[Gn?rwݭ#wwpQn{@ݭ#wwc"\n[G#ww?rw[GV{Deݣ#w=Qp٣#w=?rwO(Q{Uݣ#w="\GG#w=?rwτGGQ{&E=?rw}I\^QW+QpyEG#w_I7WZFtYnDՈ#JF0b5$$_6b5jj̈ոF0buE#V+Ff1XEVSjx1XO3`5<CH,4Y`5\1Fj6b5ψ|#VBzň"#V%F|ՑˌX"$Wj5b!$}]F|Œ5#VFXMfþI}F|GX:aʥAv6bJ!	a=	#VrYßr×Xυ/X$F\u#V.X|[cz^u鯺~W]_RU/\KZ1W]D_Uo<#VX-0bЈUEFZbj+or#V+X0bƈU^#V5#VX~FWX2bˈհ}FFX1b5fJ̽:nF:o#VF>2bu7n{KF.܈դ/X]1buՈ5#V߸F5&cbV#Sjd1XL7`52FTqCHFfcK~/>%G\dH+/>%G\dlq#_}Kr#_}K~/>%G\dȀ+/>%G\dlq#_}Kr#_}K~/>%GTqȄX]0bF.ls#VFTq#VWX]3bu݈#V&c;N5߸otc;4jo6b5ǈUyFTq[Fj1bȈb#VKX-5b̈r#V߸Aoo#VkXu5bU3bވF#VFTq!#VXgjԈ#VcFN7b5buΈy#V_}+/پKu{]d^/پKu{'X~_}l%~_}lO}1Xs\d>/پKs\d>/پbV\d>/پKs\d>/پKk1b%~}߸oR#VˌX-7bU#VkXu5b7bшՀA#VCFv6bψը+oö	#VFN:kF>0b5aJ`#VX]2buوF&X}i+oFn7)`1XfV~o1XeV+`9FX3b5߈#V߸1pEFr]d~/پKw[X~_}l%~_}l?`Kw]d~/پKw]d1#V_}l%~_}l%Or]d~/پKw]d~o@҈՗FjFn4oJx`1XfVՁ`u`1XeV*`u`9FTqSyFZ`j#VX-6bĈR#V߸r#V+X0bƈU^#V5#VX~FFXej؈>#VF3b[:msFՄX]0b\4buɈe#V_+/~Kp\d/~Kpl?%~_l?%~c:%~_l?%~_`+/~Kt]dA/~Kt[X~_7nR<kĪfjFX2bˈհ+oܮxpԈ#VcFN7buڈY#VX7bNȈ#VhF>7b5iJ0bkFa[cJ1XN1ѩ`5GciVjbVX1blj+o8B#V-FZljFZnJ780bƈlu_}K~#V_}K~Q/>%G]dl3b%G]dlu_}K~	#V_}K~Q/>%G]dl4b%G]dlu_}K~Fך)`Tczm1X6^aV4fkczMD6ǈUyFZ`j#VX-6b#_[jjFVj5baj.#VFTqkXm4b5`jЈՐ]FX3b5jJ7L6fq#VX5buΈy#V0b['_`#VX]2buوF&X}i+oD5#V׍X0b%5!/~Kr\d!/~Kr*`uKr\d!/~Kr\d#V_l?%~C_l?%r\d!/~Kr\d!/~hl?%~fCF5buĈ՘FƍX6buֈ7n<tވF&X}dOX]4buɈe#V߸Ф/X]1buՈ5#V׍X0b1Xn2ê߸Tc:<O73Lc:<Wl#VsX~X7bB#V-FZljFTq{FVj5baj.#VFjFRƍX2b%~î_l?%r]da/~Kv]da/~xl?%~î_l?%~ÓF\da/~Kv]da/~K1XqG\d/~KqG\doybVGfcĪو<#VX-0bЈU+oyd%FZfjFZXuZcJ7y5bU3bވF#VFXej؈7n<2j1#V'X:msFRƍG&X}dOX]4buɈe#V4b[@\1buՈ5#V׍X0b1Xm2STc:AN73Lc:%ۏVl?%ۏ~_l?%ۏ~-F\dQ/~KuG]dQ/~Km5b%ۏ~_l?%ۏ~_+/~KuG]dQ/~KuGǌX~_7n=zֈ9#VX}`jGF.ĈE#V߸}e#V4b+FfFTq#&c:6M5cӌtc:64cX~csX5gjFj1bȈb#V߸R#VˌX-7bU#VkXu5bL7bшՀA#VCFv6bψը+opzl̈	#VF\d1/~Ks\d	#V_l?%ۏ~c_l?%ۏMr\d1/~Ks\d1/z1X%_w~]d_u/lbV~]d_u/lK/#VX-6bĈR#VˌX-7bU!#V]FzXՌX7bшՀA#VCFv	ׇX3b5j1#V'X:msBF>0b5a##VX}bKF.\H>iK#VWX]5bu͈u#V7X}kV4GVc)`5v1XM5یjl1XnVcӍj#V3X)$fˈ,#Vwǈl#Vc>!>ljyF7b5߈FzЈl{HHz؈l{Ĉӌl{ԈÍl{̈l{\H9'X9	X9XZiʱI1b.#VFzXZgjB}lMFXm1b5hj!#V;X2bGH1bψ#VF:b1#Vǅd	#V'X:e3FsFcX҈F>4b5aWF>2bk#VX},hS#VXֈe#V܈F~gK#V_bFkFlnFnƈշFdVo4Sq_Sیi``tcz#V3Xij+q_XmĪb#VXkjX51ψFz#VZh!#V-F4}o,2bF5bĈcFz܈2#V7zʈ
+#VOj5b҈UUFeĪۈU>#V5#VX7bF#V7Xm1b5hj!#V;X2bǈհ+q_X0b5j#F3bu܈	#V7ƍX2buڈ#VgX9#V:oJoܗF>4b5aWF>2bk#VX}l#V7.Ԉ%#V5buوgF>7bI#V74b+F~o?fk#V׍XiR߸a#V1X7b:~1XbVo5S߸/4c:~1XnV0b5È՝Ffˈ,#V#Vm^#VsXgĪو\#VXiR7bF4bЈCFZX=lj+q_F5bĈcFz܈2#VOZnJoܗz|XZiĪÈ*#VkX=cĪˈ߸/x>#V5#VX7bF#VX4b͈ՐFvcj؈Ո}F4}GX2buĈ1#VcF:aq#V㧍X1buֈ/X3bF~i#VF~e##V6buF>1b#VXiR_2b[#VX}fs#V_4b;#V_bFkF6bu݈X0b7K=?՛M`-`czVczs1Xy1X99޼È#Vwi.#VXmĪbJoܗl#Vc>#VFg~#VXiR\`A#VX=dĪňFzĈb#V7z̈R#VZfI#VˍX=ej+q_ꛭFV0bʈ#V2bmĪ׈߸/͚uF`jMFXm1b5hJoܗFvcj؈Ո}F5b7K}cFƌX7buI#VFN:mJoܗY#V0buΈFX}hj߸/͏Xڈ#VĈoX]4bKF4}o^6bύX}aj҈X}i+#VWXiR߼jFڈu#V4buÈ7F5b7KMl`uc:1NjV'Չی4c:q1XnV'4}'fӈL#Vwen#V#VmJoܗzbX5kjX7bF4}'zȈUX-2bF5bĈ߸/R#VZfI#VˍX=ejX0bʈ#V2bmĪ׈U+q_F6hj#V[XfjȈ߸/.#V{X1bψ#VF:bJoܗzb̈q#V'X4b5nF:kJoܗzX7bK#VЈՄ_Ȉ߸/#VĈoX]4bKF~k+q_ύX}aj҈X}i+#VWXވU#V׌X}m?a#V1XdVoiRߚbVojVoM5n3[[Ӎ;X0b7K}kX2bu{X6bu9F4}o5kjX7bF4bЈ߸/#VZd#VX=jjǌX-5b7K}k'X-7bF6bjj#VԷzƈUn#VFXՌX3bވ߸/F60bňՠmFX0bˈ߸/a#V#F:`jԈ!#VGX3b5fJoܗ	#V'X:e3FsF4}o7bK#VЈՄ_ȈկX]0b7K}#V1buшէF.F>3b+q_[F~gK#V_bFkF4}o]7bG#V7X}c[#V2tFV'o1S߸/Tc:y1XfV'o7ӍFfӈL#VԓXmĪb#VXkjX5g~#VX=`jX-4b#VԓX=bjGX-1bF7b̈߸/r#VOZai#VFV0bʈ#Vԓ]FX3bU3bΈz#VXm4b7K=9`jA#VیXaj=FXiRO3buըCF:fj̈q#V'XiRO:e3FsF7buވ߸/F>4b5aWF>2bk#VX}l#VԓX}jl3#Vդ+q_/X}ejFڈu#Vԓ7X}c[#V2[)``Tcz[oܗ4czvcz{1X}F4b5ӈ]Ff]1buF5b5ǈ}FX5b5ψ߸/F0bՃFzȈUX-2b7K}{GX-1bF7b̈ՓFFZX4baj5F1be*ߋR5bgĪfjF6hj#V[dۃF2bÈ.#V{X1bψ!CF:fj̈q#V'X4b5nꔐl3FsF7buވ/X}`C!_ȈկX]0bOXƈE#V
+c_2b[#VX}fs#V_4b;#V_JjFڈu#V4buÈ71XK2bƧ`5>o3i`5~lnVwaN#V3XejXUX#$g׈#Vj6b5׈<#Vo*ߋRzЈB#Vj1bEF1b؈գB}|ǌX-5beF4b܈SFVzZHZiĪÈ*#VkX=cĪˈU^#VuF`jMFXm1b5hJoܗ:>dj]F6b5bjFFXiRǏ:fj̈q#V'X4b5nF4}gX9#V:oF>0b	#VXڈ#VĈoX]4bKF4}㗍X}fs#V_4b;#V_ʈ#VF`X]7bG#V7X}c[#Vw[)`έ`Tcz6czg1Xs1X33È՝Ffˈ,#Vwǈl#VwψUF߈|#VZ`JoܗB#Vj1bEF1b؈գFԈFz҈r#VOZai#VF4}tZejgXu6bkĪψU͈߸/F6hj#V[XfjȈ߸/]F6b5bjFFX2buĈ߸/1#VǍX0bu҈ոSFN:c+q_;Xo_ՇF&XʈGF4}\0bOXƈE#VdF./XM/X}ejJoܗ5#V_nFnƈշFdVNiROM1Sթ`u6c:5NnVթ;X0b7K=5ӈ]FfۈUň=Ff׈#VSFg~#VX=`jX-4b7K=ba#VX=bjGX-1bF4}z҈r#VOZai#VFV0b7K=ƈ3FXu5bgĪfjF4}6dj#VF2bÈ.#VSFFX3buըCF:fj̈߸/	#V'X:e3FsF4}X}hjկX}dF.ĈoX]4bKF~kόX}nJoܗzj҈X}i+#VWXވU#V0bu͈߸/u#V4buÈ7F5b'cz޽ޝbVjRߝjVfVN3wo7wջwaN#V3XiRߝen#V#Vm^#VsXgĪو߸/yF7b5߈FzЈB#Vj1b7K}wGX-6b%F3bԈF܈SFVzڈUF:X2bƈ߸/.#VFzXZgjF6;`jA#VیXaj=FXiRgQ#VX1bü՘FN;nF:kFz߈y#Vw?0b	#V2b_`c#V{шէF.F>3b/XMX]1b{#VWX5#V_nJoܗ#Vֈ՟*]t[c:}1XjV5}ۍtc:}F4b5ӈ]Ffǈl#Vc>#VFgJoܗzzX-0bF2bba#VXiRO/6b%F3bԈFz҈r#V+X=mĪՈJ#VFVZc#V]F4}{XZgjF6dj߸/mFX0bˈ#VFFX3b7K==j#F3bu܈	#V'X>mF~aX7bK#V0b+#VF>6bhJoܗzl3#Vդ҈߸/#V7buՈX]3bFh+q_oXk2n1{{Sیi`߸/`FfӈL#Vwen#V#Vf׈#Vj6b5׈<#VoJoܗ#VZh!#V-F6bȈ#FĈcFz܈2#VOZn)#V+XiRk5b҈UUFzƈUn#VF4}ՌX3bވ#VXm2b5`jA#VX0bˈ#VFFX3buը+q_{GX3b5fFN7buʈi#VsF7buވ/X}`C#VF4}}dF.؈'F~cOX]2b7K}όX}n#VF~gK#V_bJoܗU#V0bu͈FF1b+q_j"3ՙ)`uVc:3fVgՙۍtc:7K=3È՝Ffˈ,#Vwǈl#V3sXgĪو\#VXojX-0b7K=ЈCFZX=ljGX-6b%F4}gz܈2#VOZn)#V+X=mĪՈ߸/LUFzƈUn#VFXՌXiRϬ7bF#VXbjЈ6#VCF4}gvcj؈Ո}F5buȈ#V3cF:aq#VX6buƈY#V3Xo_ՇF&XʈGF4}g.؈'F~cOX]2b[#VXiR|n#VF~gK#V_bFfk#V׍Xш#Vֈ՟M`sq_ϧo5O5fV?fV?~>~~F4}?i.#VXmĪb#VXkj+q_ϛX5b5ψFz#VZh*Mt]Q__t_5}g^ߥ;A)w0޺Empm?}6-|ht~W^=;SvIO_JW*mk;k͕ZgWiV*]?Ԫmk+k{:kJGZ?[okm޵Wu5WUkmήZgJwuj?$߶/%޾j]YVo?W+]ZZ_wjW^Qgwg[wt׻NWʞZ«7~qgW;VoeR\~?|[-4߬]S	'zjj[{ZHӟ笣6ZˁB륏cnG]xv;kYMi=YhiU9-m*H@wg{2|Spy/_ݕpzVۡC;u;He][_NG5Wڲf=}:u*tGuuw?z&([>zOG'P[uEcsgvNL@骮woqwڙ9~4z'׌NfP0_G$ȧ9̢kEGF܊aO>"Q;;+d]hcw[8[]tz|;jjGpneijW/-J/um#*~ֶq}ТȒڶա:5;78"&Z}Q5FOR%SGBN+񼻃	WohɢYY)ysL.PZ͎p"V7'&6b]^1/uE</)~kNIKBQ>yA lmmc$\h0 tYveҵm]5va+FER
+.'¹>wFqz>g9&ݝXr9E02x_ٗNFJJ+WjAQtIYå?$]BṭWwgURIIoz] ǒxuvuVWx*c=QP.zjSRsM稹IqZbv4Jgh5QV;;^BSFaS-Ff}MZquaQaP(:tb	_W%K8]ZUtO)k5"5(""t_)z>x]*I	n)xKq_boCP()Hɧ55E&k.*ȖpVWHEjEԚڋ\N9}-/pBScm4*15͕V 5:L<OZ\`Qhn՜|iʗb<{$_-:YWIM)r$5PmJWE3'zb_ʷƚȹ>AC<E/S51xdu]Z*'S<OF`'5"*}ZZ(U_|k~*Xϔ{.OBok5!]Ixw&KN-/<O\{L1燞4ˆa)F&&E+J	/ґEeOKMLZ'̍?[\pu	K]_x4+D9N;SŰ&ypW {<|8w1ͱV"WuWW]Zjdh@[~wiKM&JpUB*ܙ`7c|AQȗ}WWu50}n/fRp,IkI~Rqf6(f515JJI[mםE+&NN`V,.tڡZ-~ߖ>_׈	mv7n..8ެHMh~<e-V#׸FRlX*{.]nғ{0K/7P(Ƒ4L#DFRHjN	tD4(VATRDOU@|uZ(ڜ&s$7K1lOE`(,q0_ Eh-/Qsh>E<#OZ~'MH.MwXѵ
+vs˩+TRjOqp9Y[L+\o!ݚ+ť[L:{~fK#Bx@6qbM楂6'Q0XHs2C&IgL08avQ(r'ķ4Ц{8CR) JlxObQtJ^ZKoK[2]	|Qu]lkVSY-CHS-f5J/7qn.⺈UxŶt{igsP#]YqJ;Ww
+=WRZel>ߚsqę>N!˕9.BTeR8^Ey͔]' X`qw5tnX2.R1GF˭X.|JUqR_xQSmM\<j,-
+Eue<W, bRi4WO"+Xܱ2.WҺU>_ԯUԛ/n^O骪iroJkC.8E^˙R\6CSl<H<c3[ͲP|88Ж\Jk("ۧ41ֲ)4Jk0Ssfʹ\iabO6ޙ֟+q^ՒBұTy&ܛwt+Cs1O,]*%#^tHtpMΎ7ŚA\~\,e֮0fGb_
+ҼYMJWuE"ǫZOGEiP晑S~h'9BIhqiDp^^-Թ}3e>$wsiby--RW3I;|N'oA13yAg!/Dc`&[ŸDyXLwg^px^fZK
+s1ea鎵Ns֛8WcoeQ,壑5Ǔ`5z;bjrOn	/K h덃bW3zznxٻXg2hz6ˏ_;@C7Ń8$@Ici$_1kq2EB6>a:IVbuGzZ~#9@,Q2(DUttu<K	7?'%H7uzs"@qҙn<$wymhPqs*K㳢|u"_*[ݣiAED}?&}xeã?_$+^Ik '8i4.絰AXUbgh)̗4.fdu';LhqXoKm0(]SOCj^Xa_آ=	Y-d'V7=mOIOY?-/'fI-]
+;U>iQ܉#jͷZhX(.粯l$i\=Jwi*$^i~PSi+D;IMͷHq?V֊3ڼiLIKqy{)¨X$miq+gUV.FҍlH׋áXN)a/-k4%t-&Bi@/tm4A3z?*ƆO'kpBd`.=8ӟ'[|Axm16/O0]ZU;G'/qhd#bsԂa:'Hl@htRuxzǔ55K-
+SY{4gYLcŽHq%tӜ4_WE"^i80LwgEa43Motiea4K"WFU
+ކ+BH4U\H8X˃˸z^]s1LIP,dڨ6t \_mHIin8}q+JknӘKukTi\[t_z8F@ŋ.wASV-jV̆;)mi7-SxI ׊+J6TA NsTcUV}5T'4?s_Q-z;.VVskn2:[N͍a::nOajS~_^oQxb}ύ:M/főtO/WʳRzٯ45wi^伴痌sh0KOϔl"^rir<sr6-_:+-\[(֦KXjxSK~N*֫gQBsb%oTrG5nrw]:_g|vOŋAuxԪkT4ƇYv
+떔ޢkxya_zItQߴȗܺMF?o_}Pi	;~>4i:=sۖFJś/<bKG=<u<M3>\J>rJӂo>-}JKbZijQy1Fg	9OIk/uuنe5vr:4yרJRD1Sxv^)glzC@79V_+)-M«U?z+ZbdXz (=TR[i@=_#$ŋcx'd\EiG\<;RkJQV1>^BY~>j=Ʒ:kUt}xEM"ͿP~V1Oʷ<00MkiT+ŖtZiM>r1ݦ?KY9R'W+xW0zθ_hM1"
+hw~́o&~)?KOV1kAVZOޣLt[Q8ғEh.{..9Gs2*ݴ\l/+z$s?+tI=_BEza>)KEu0N=v5\EK#Y-8?!LxQiJ!q5]BޮHCm~Q5NKr[k}q]?.}QiNOkCZ+]+Iޤ.t/_̽oZ:Wz/ӘMHS7N)|g7?/א{/V\=WD^h(^~oxm]&bxBKҋz#{(nZ0LeJl
+4Sr:Э#-]:1CT,4=M=\z{*:߳j^j=Ųvqхo	oWzV-W`=Df-Yqmv̸:Rj,ՆJw.-NGjޤzA6ViqW\dZi+(bwvz;+Y\mڭ^~zVqzX/EkV7k<JO]f#C"gNiLso
+*OXDFyXeLky?c]e?PcdQjxcKA?RS2?yŤuߟ5E.:o	.2ٗWjK)K_*vú`(}I~r֭<\1H4'N?)*ksmsݚb,.n|K^W.k/8(!#︈i:SZ?pӳrzH'Y38j܇M>↮|cS~&Ug3t ][bUYPyѾw:ʋrJ!rRL.Қpz_2_4fqpzM5YWM޺0;M1+or#5-+uK--[OeĽy;Ȇij>y>:yowqSbeQHP:ҋiSO`N/'4-]	y&a	w8'A@ߛ&Yhyω䩘FbŨxVG":?~v>=Л<鬤/,Şfsq%/퀪<'gE5Cylx*,s{>a1"<M镤cˁ+}^EKKA=+:"mb𽡞aVo@;9aS"Mݯx]8Cyk-$ݢ5,Mb.m..r/1zn7G@]JMo_w6k;ŲO%meP/BÖPumi;p*:vٸc!*"c^{xۑԹ)KӍ/gj+"klK=EĝyhMJH1Kۏ58Mfh_|n#qNA=Uz==.ņjF>|M~~ԞӦWSAooFӟOnک6\DFtI-&.#"m欘R~iSOßbmJq=n4UW*y|Ec=_0L$tt84.=iK~鼔@$Mq̉n_ _mNtwSJZ|)c
+J+Z9XH"up
+MXJYϋDt1'rVieS".b/I3mC$o:ҳ$U|ʝPː_I,:<r&ƽXi~|i '5-]138_.n\HpxKnZ'4nag7Y5nCԋVjZ.Sy?|^OM7ɢ`.4mJ	LЋo7#A'8y[mcy#C[8^z7=Uߏ^yhJg*+4РUKŔQתUk@]WϗT1>NQ-2B1/	4ߚ7Ysv\uP$^p_7}F޵ḫojA~*MK M^~Ni/4M5?c?qː3*= Ҹ\Kp=QzZ15Zb濹W/-/e5NxC~޿s?԰#p*yʐذ3),.o:+t7z'$mX~r[=٪h~ز,4ؔB<] [MO:^)nA{>7N2|O>uV~.u|?{RX$~nCVĊwu@1[@TjdD_W͸[S!j&¤$"DĞl/ejtdCsmU($UBy[ם%_N{*LlbEL=WL}u8ȧ{_"S
+S_Ĺڎ(Lmq,1CZԋ`gCʙH;V3t<f$ܵUPY+SD%o ׷P=e.L]˽Ɠȩ ⴄ66!f>^WfIHͫ XrL wʃ"JO3$;
+aun]qF0JdDYBK
+٭z0[!5q.la-&zo3	.VB0o٣EzgՌU%i+*MO5H*x̭cN1OX2-mJ@9w?U!x	,6b6gd:>@r,k4Yܳ>u	*ԆJ㨢\Lζ0?C;<R_JY4P*	Píܛ?l
+[LS!T|i9r,JnaB\jhQ7~kQ+ {$v)FonK:8bk	Nh@*Md.>[л [ќν.M[Ω
+=3TC1ʷlJe9w}<q#^Yo~^} YP@\°B^c	[a&Wu5Gu޴^+f<fI8^|ڱ9ܚ5 ).>U·AX+g"NhRmHz{:GU)\9"%Dh=?MȨb%FjK] Aف(h};D)"Xc6{cK]ߗ1R'j(1,<E3.ٹO~>^wx9u5٣hah"Cv>bB[ KxJJ"YDv`r
+IppLE/> x'55p3z&-r
+盧z>7v`dCZPA>5v{`Mmjg̘dw?O϶]`cg eTBCU=8N $AВŁ`N".7*&SK9}މ)z
+=(^&uR}S]*ma
+<Tb&BƓa=-u.髃lHy4SQ9χ燐bbca;}._ETh6nCzпH(E~t	뇞Oy&.{kH>|U%-71ɑ$`Sz!4_od1Oq4[+׾B0jA%<nLb[A$Vv3ǟϱ`}Ԉ&ЌNޠ!+> t$ĩ`yl*)qCH!h!3=1C?fjpf}ɝϹ4ĳ~fiYЅ+vŇ.nGeۼkd[TLFȠWg6(KU\Zw[!Pg<%zR,ZbFlӺzإlި#ZQ}WB\v(
+Ŷ{*7I6UP]X9xыQh|.bb\EpXv_dp%Qh*ߐg?*seş9'XL
+}O#4&f}<N	ɼǫ,0{)6me>՚5'kQm[0-|Ff[ SLĺ54q%V
+c~X֤#ԯ;.D*.w8Ω|)'SsyV?bkvDVܫf|'_3D/97Vbh۷6Rp>=/A@[O,Y( 	o}-%O9Ԅ7Z_S5	ԫdL_A)@7ڀiE_pqEoQ !/wlP֝Ue8	*@Qn#qO+K.,x*@'I#u_6FDcXDENNSqO|{%6v:dd=>׵V(<w6Q.ڏkobhI_^uH0:<AW@"c[w.ubXԾug
+>$KP%1h@:5P1}!mX"8C܈5cWU?q7as|Y3	4⨒v%8ӁuJ%5N|gâktA=i8?+*2xhn
+񧅐gzusTK S$9Z5C4K%ĴGƦPo񚰫mr1=5?i3N\"Q	7s᎗ Z9uCP3to?SVKV %o|ZS^[0!+d٭c`HX߲XeZ=v5nv=P
+*"POؼ!v~q=3WKf>~pn?'zޘ֕bN-/vى[!K=~mɿBaePCR3↾9'ln:ʆ[]Ϣqd1F)V{oPm
+F˃J'S鼱tҶQPCeGҮ責BJ,>PT$FDiSfkcvzǁFhR8['рzC[$zlg"a=|
+܊F)BjN *Sw`A^'>~6/Ɔl<8=~#ڢ) C޿9huS	SC*eV`6:Uňzw	%֧R_=	m1D#!j~k%NNwYR_ֳĨRa{ڝ5L|DYzG2s^={fr):?{"Nj^v[yi)lofE͇MxGE1nRh|9;au~N::6z2e9]+Ƹ{h%,%}jQviZ|ɁQW=gm$Obe,KK]SoͰT^1GN0t=AhHy5JŔ	łk^l*F2VɤYSi#1Pgz}\{"_z,S"d8J$Za/e*!c4 ,8+c`їIr-<w!
+6`"<&_ñcS?<C{-g= {ƻ ;\ŖNxݨC%As]{g
+\Q=Jl9@ 2tI.5}.=ߧ!YU4j>f>-y;kϪPsu񀸀PM6J0ywu+Ogjڻ|σ92`VK:Ee6\GEK[Bú)`ٹei$l2`׎g W@vOwyKjbzY,ʳ4ڢ[ArsAEg}I9 ,pI0,4[/,U>(#B[;ϰCaQ|ZoCEՎ>1;Kl8mN$v]ut<7{bQ]MNV=qLq'@s!r".y
+#=0/)TFmܑvKfܒnnjUԓYV3b$\8w_Bq܉.";.oq*׀(L-{$Zo6"pu1YZҮ:]=Crڵmtq؉NZ⧦&
+}>X<Ms
+?n℁lF"kZlSojxfAH^}#i JEt߼2n/W|;xg~kz8R^{n
+Wbïӑ
+]l3iNUD0/ 8maah*:3CA3iDOL3b'RF(Q	¢FKEˊFow]nX~X]7JKqoP.$;[PFaC9<S8svn]K;Tgt=)K6Mq!!OV:K_rᴣ>czpoUYS
+VY(0.nT~8w}U۷WI|+4wVĐɂ4Fi+rڽkwoĢ8OSKZz@IZ|,+SQ=6޷irQhPs&`BumN!B2xI}&X4^De%Q6	}#;qZi-O)66΋<Nr3_)OϢcBEM}P:yl1#4V.2T85kaUtBW
+E\4m )iX%yA}8}&lKVRZnD}Rq/mXt#<JAJq/K⪈i2lWwA iP__WN퐳Yy.2 KXF*FkGd	y4ʇRk]HC,UiWXM34O8B|׉-r SKJ_z(cu"ٚ%sĞ\*fxOHG	ׄh	K7*hM76Vxq[tWSxvg?Ffs!;ddıVcIrWʭv*?ތN[jE7A>?3Kr¦\2 S6x!Z+1m:qutz>x0}6qZ)s$-w_\ZDc5ARkKE=f.GcZsQb'rZ$kQy}qQ3Ŭfq6 P820V&@7fjAg0.w<\lb*ٖ½Y5wk<~
+x^b0#w"Rgpm 3_%F9߸0Qe0va9ZcYTV9%tMEIZW2"v;ӧ.Dcno{\|!C2ZU=xƎ|6܃i5_No+HԂnw?^`3Dmux;!܇+J
+ݗFo1QH']opNx䛃pc-W2ySC9GOk{1~p(3B?Ag쭱\Y7$YC}?8aN-{ZE@CrD2Èȇ'j.78vc=QƳu;}#`: m!}nFvl,B=x};_.- I=~'gyzyƱF]![l.*OCUe{pP2n1ԛ;FE&;b2c2<O|:܅ZWRojo:'9 6ŮFvnC]~J-Ԙ;iԜ[T"[	)a2A9֮8t1WZM?bp{'`{VL%rDlg=۾R񲏳lzOcԃ ',H@$`.G_Bkٛ•u3ZC;yo[fBSJŴ2	㥢t/=cKlb4Y2=VEjx,/>EcVO}(?>$5rnmVVfy0@8KN9~tϱdW8YucaUiO>U8cq}Dk>SyCVcwǅ6<O!3%\L"|}!01,5UY*"=Zee'JtvÍ03q,[/9B ')Tx7p)1s2ŕfTH+yZzd>YRzU6/KBX0 _1NExNp(ySv _x[H^,J1~VxF;S|~]_pH G,E,b|-z(B,K(RՒ(26uֻ/"g+1~SI吸,D3H{/XLT`RN_ &=cܫ˿)8݂ SkUqW}bI1rҚIPkw?qAcھCmW\ZICg/%Q@&vc<}dM־KXOD;,uX<ܱՆ6U^ܙb&X1~*|&2;ž{Wos,1Cu=F_?Ɠr-_m:	o1$NG$ude~:g[3Ea=ʡJ|ImBIH)oJK%]-XdBsh-ը"fJAT;{tSf>B8^q"1jTpF{Mse*nȒΐ	UX%?-Q.&fٌD^QW減ERsRǤɟAWkTE5k
+0eݺQ/wTj:pZ|%S;(w7db-n<S[>bIж-9d'Q斎SOB"ғeEaV3*t +	QfvmkW/_2wo6FXgN4 ipd8#.W? f	.n`} ?Gfj(pb;/S$iۓ6m*bsWt2["%0@\yhFvxVI[\HRM"5Եa1e0鮘@SLJ\sn'`[-ix|]ax3*Ҟkݜ=2+MDFJ-?sV9Ǔ'=#DrЎPOHܠϐlC,s>$Os1+UE"0m̎0I'ϳ=8P1C[mzsT@scWGQwǷyBZv[o݋iڵ<$Y%Oo}E٫"K
+>OUY}s%?b54o;.*^㱍z>.rk]rgC:Q=wM}=?5=.-H|~uiiFiSmf^꧉=!⹲JY	U1K~yA8)^~z
+x%FMQ%BWXc95#}O"N)T
+|ykg
+wKЗ86uFƲH豤)i{R_k\eR!mGꋡ1]3tszGmL(HP^A.[欕uV]w [}[R#
+:MC6Ar5#:ʂŀ\iCBSeI}mLTA~ - '*8{E`4}X࿑]i8e>+ 3aMyе>_e,MY2f?:]9t}#ݿNi'6'N0CgPGva ԩMwQQ
+b'jN3]e;w'RET,aU&.W7X \WN֊paZc`Cӣ9݀w	s־kJڡǭ{vsc1V^QhP<Ád^/"/@Ahiӈ\'v4TOn
+9<Y.{k^[crIC~J)[rɾ Tə1I&I˸Q*PdY
+ܗcf<5%i_ض}
+qTqWY7kz9̧o* .T%s)1mޚf:}ardSKo	h}~PD[QŸvmWyssYHEED?@:x]qG&{PаۉdId5
+!@M1uJnZ9N.P`2Y',6:@u?gΠÎCb
+RP99I֭wPWdl$z	W9ɫ{wjWo|Pȶ1FǜceSJQǖ4F6YZ?ߙpeB0,mFAI.Q<eͬ:9aRFBA/xG7<̻AAsHT<}9d %.r<|56a~/zb"Y^oT% ߜetgR?ZIHWT8y1K{]J;3*ECtV'y?<(ͳdGЦ
+ؗ5NL0=Jr%&$M4
+@AfpУ[cBVLt>DUڦ!dA7y\݇UBn1w\z#iPjqawȽ7!.7wuQk\AB#YVte*0VV
+.UW(||Da4VKsrK<H>Ԭ/msPJgFs笅'agh_*ȦWZ'UaPS6N'"(yxN)0ϯP&")25PaiUuVX4qB#(D;)m;E^!1$e8
+[f)c|\_ҹ~fIdOՂ<b:S !x[d
+7uƃ+'GQ-/A b6P[[7OgU6Ƿ5 0RԨDn$t2:"tkp_K4d}oNcjjt2E7S1(IzsQ8Em$3f("1XH4*<}zشXY8,oJ9bc=.CtW*^b/b&sb/nH,зP_"@P_9'vݿ0ԯUuh\S+EQZ`?5RD~/C`7]%k/<JO<EYVퟕj-@[9)R_ &(Sg"\z9ލ87=7D޳yicؠ\PԑpGGlʽ%Qak4ImmCGxۻZ*IZVqK_+C! #\GO0c99}R2Dݦ!rߜ^a8}&dzwBe^;ƛ&rJl4"VgBв|%M<τ8.V/ /K"9frй'>Ct1ԴWf]:mx.<grFs7|B f8'b*PDATh};%ϸJJo 3{+B ?FNFuD0T=pMk%:mC[<٧l;qXWV&zERH̧l[CXQ^'<ޟҦ4Qn֐bQPhi(jw(<oډ(!QkumZfC!}BH~:K(R*uNx|V/;\~VhHvKWsnR;o5LP,,1j~+<U:bc[t=hA w!.2ua~<k__Ѩz.2mvI m
+0w"QXݮ2e IeZ{V1jW~4dvSYCG^35C$uÀr)OP'G-DR$תw,-czܞ<hyνΟ. VeCshė|_}+NM^1S<2f*6`D*F|I
+N	qd.?U
+Z=V==yL&˚1ACOv˖FQbM!C}?~ۿݗ/_>?~|~g?ֿ˗o?m۟O#/?o񗊸_t})۟|/I"k
\ No newline at end of file
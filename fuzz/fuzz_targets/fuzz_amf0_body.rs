@@ -1,8 +1,8 @@
 #![no_main]
 use libfuzzer_sys::fuzz_target;
 
-use flash_lso::amf0::read;
+use flash_lso::amf0;
 
 fuzz_target!(|data: &[u8]| {
-    decoder::parse_body(data);
+    let _ = amf0::read::AMF0Decoder::default().parse_body(data);
 });
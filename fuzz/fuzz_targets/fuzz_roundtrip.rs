@@ -0,0 +1,17 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+use flash_lso::read::Reader;
+use flash_lso::write::write_to_bytes;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok((_, lso)) = Reader::default().parse(data) {
+        let bytes = write_to_bytes(&lso);
+        let (_, reparsed) = Reader::default()
+            .parse(&bytes)
+            .expect("Written output must parse");
+
+        // Compare the debug output, as NaN != NaN
+        assert_eq!(format!("{:?}", lso), format!("{:?}", reparsed));
+    }
+});
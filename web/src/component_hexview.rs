@@ -92,11 +92,11 @@ const CHUNK_SIZE: usize = 8;
 impl HexView {
     fn remove_button(&self) -> Html {
         if self.selected.is_some() {
-            return html! {
+            html! {
                 <span onclick=self.link.callback(move |_| Msg::Remove) class="ml-2 btn btn-danger">{"Remove"}</span>
-            };
+            }
         } else {
-            return html! {};
+            html! {}
         }
     }
 
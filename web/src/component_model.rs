@@ -20,6 +20,7 @@ use crate::url_bindgen::URL;
 use crate::web_expect::WebSafeExpect;
 use crate::EditableValue;
 use crate::TreeNodePath;
+use crate::VALUE_TYPES;
 use flash_lso::write::write_to_bytes;
 use std::ops::Deref;
 
@@ -54,6 +55,7 @@ pub enum Msg {
     Loaded(usize, FileData),
     Selection(EditableValue),
     Edited(Value),
+    ChangeType(Value),
     TabSelected(usize),
     CloseTab(usize),
     CloseModal(usize),
@@ -128,7 +130,7 @@ impl Component for Model {
                     self.current_selection = Some(val);
                 }
             }
-            Msg::Edited(val) => {
+            Msg::Edited(val) | Msg::ChangeType(val) => {
                 self.current_selection
                     .as_ref()
                     .web_expect("Unable to get current selection")
@@ -215,6 +217,24 @@ impl Model {
         }
     }
 
+    /// Allow the selected value to be replaced by a value of another type, converted from it where possible
+    fn type_dropdown(&self, val: EditableValue) -> Html {
+        html! {
+            <select class="custom-select custom-select-sm" style="width: auto;" onchange=self.link.callback(move |cd| {
+                if let ChangeData::Select(select) = cd {
+                    Msg::ChangeType(val.converted_to(&select.value()))
+                } else {
+                    Msg::ChangeType(val.value.clone())
+                }
+            })>
+                <option selected=true disabled=true>{"Change type"}</option>
+                { for VALUE_TYPES.iter().map(|t| html! {
+                    <option value={*t}>{ t }</option>
+                })}
+            </select>
+        }
+    }
+
     fn value_details(&self, val: EditableValue) -> Html {
         match val.value {
             Value::Object(children, Some(def)) => {
@@ -249,7 +269,7 @@ impl Model {
                     }
                 };
 
-                return html! {
+                html! {
                     <>
                       <div class="input-group mb-2">
                         <div class="input-group-prepend">
@@ -272,11 +292,11 @@ impl Model {
                       </ul>
                         { static_props_details }
                     </>
-                };
+                }
             }
             Value::VectorObject(elements, name, fixed_length) => {
                 let elements_clone_2 = elements.clone();
-                return html! {
+                html! {
                     <>
                     <StringInput onchange=self.link.callback(move |new_name| Msg::Edited(Value::VectorObject(elements.clone(), new_name, fixed_length))) value={&name}/>
                     <div class="custom-control custom-switch">
@@ -286,7 +306,7 @@ impl Model {
                       <label class={"custom-control-label"} for={"customSwitch1"}>{"Fixed Length"}</label>
                     </div>
                     </>
-                };
+                }
             }
             Value::Number(n) => html! {
                 <NumberInput<f64> onchange=self.link.callback(move |data| Msg::Edited(Value::Number(data))) value={n}/>
@@ -296,7 +316,7 @@ impl Model {
             },
             Value::ByteArray(n) => {
                 let n_clone = n.clone();
-                return html! {
+                html! {
                 <>
                     <HexView
                         bytes={n.clone()}
@@ -312,7 +332,7 @@ impl Model {
                             Msg::Edited(Value::ByteArray(e))
                         })/>
                   </>
-                };
+                }
             }
             Value::String(s) => html! {
                 <StringInput onchange=self.link.callback(move |s| Msg::Edited(Value::String(s))) value={s.clone()}/>
@@ -370,7 +390,7 @@ impl Model {
             Value::VectorInt(elements, fixed_length) => {
                 let elements_clone = elements.clone();
                 let elements_clone3 = elements.clone();
-                return html! {
+                html! {
                     <>
                         <div class="custom-control custom-switch mb-2">
                           <input type={"checkbox"} class={"custom-control-input"} id={"vectorIntFixed"} checked={fixed_length} onclick={self.link.callback(move |_| {
@@ -431,12 +451,12 @@ impl Model {
                             Msg::Edited(Value::VectorInt(e, fixed_length))
                           })} class="btn btn-primary">{"Add"}</span>
                     </>
-                };
+                }
             }
             Value::VectorUInt(elements, fixed_length) => {
                 let elements_clone = elements.clone();
                 let elements_clone3 = elements.clone();
-                return html! {
+                html! {
                     <>
                         <div class="custom-control custom-switch mb-2">
                           <input type={"checkbox"} class={"custom-control-input"} id={"vectorIntFixed"} checked={fixed_length} onclick={self.link.callback(move |_| {
@@ -497,12 +517,12 @@ impl Model {
                             Msg::Edited(Value::VectorUInt(e, fixed_length))
                           })} class="btn btn-primary">{"Add"}</span>
                     </>
-                };
+                }
             }
             Value::VectorDouble(elements, fixed_length) => {
                 let elements_clone = elements.clone();
                 let elements_clone3 = elements.clone();
-                return html! {
+                html! {
                     <>
                         <div class="custom-control custom-switch mb-2">
                           <input type={"checkbox"} class={"custom-control-input"} id={"vectorIntFixed"} checked={fixed_length} onclick={self.link.callback(move |_| {
@@ -563,7 +583,7 @@ impl Model {
                             Msg::Edited(Value::VectorDouble(e, fixed_length))
                           })} class="btn btn-primary">{"Add"}</span>
                     </>
-                };
+                }
             }
             // Value::AMF3(e) => self.value_details(e.clone()),
             _ => html! {},
@@ -614,11 +634,11 @@ impl Model {
             let blob = Blob::new(arr2, options.into());
             let url = URL::createObjectURL(&blob);
 
-            return html! {
+            html! {
                 <a href={url} download={"save.sol"} class="btn btn-primary" style="height: 38px">{"Save"}</a>
-            };
+            }
         } else {
-            return html! {};
+            html! {}
         }
     }
 
@@ -640,7 +660,7 @@ impl Model {
                                 onclick=self.link.callback(move |_| Msg::RootSelected)>{ "/" }</span>
                             <ul>
                                 { for data.body.iter().map(|e| html! {
-                                    <TreeNode element_callback=self.link.callback(|e| Msg::ElementChange(e)) filter=self.search.clone() selection=self.current_selection.clone() parent_path={TreeNodePath::root()} name={e.name.clone()} value={e.value.deref().clone()} parent_callback=self.link.callback(|val| Msg::Selection(val))></TreeNode>
+                                    <TreeNode element_callback=self.link.callback(|e| Msg::ElementChange(e)) filter=self.search.clone() selection=self.current_selection.clone() parent_path={TreeNodePath::root()} name={e.name.to_string()} value={e.value.deref().clone()} parent_callback=self.link.callback(|val| Msg::Selection(val))></TreeNode>
                                 })}
                             </ul>
                         </div>
@@ -649,6 +669,7 @@ impl Model {
                         {
                             if let Some(selection) = &self.current_selection {
                                 let details_content = self.value_details(selection.clone());
+                                let type_dropdown = self.type_dropdown(selection.clone());
                                 let value_type = match &selection.value {
                                     Value::Number(_) => "Number".to_string(),
                                     Value::Bool(_) => "Boolean".to_string(),
@@ -684,6 +705,7 @@ impl Model {
                                     <ul class="list-group list-group-horizontal mt-2 mb-2">
                                       <li class="list-group-item">{value_type}</li>
                                       <li class="list-group-item">{self.current_selection.clone().map(|cs| cs.path.string()).unwrap_or("/".to_string())}</li>
+                                      <li class="list-group-item">{type_dropdown}</li>
                                     </ul>
                                     {{details_content}}
                                     </>
@@ -1,4 +1,4 @@
-use crate::{EditableValue, TreeNodePath};
+use crate::{convert_value, EditableValue, TreeNodePath, VALUE_TYPES};
 use flash_lso::types::{Element, Value};
 use std::ops::Deref;
 use std::rc::Rc;
@@ -14,6 +14,9 @@ pub enum Msg {
     ElementChange(Element),
     CustomElementChange(Element),
     CustomElementChangeStandard(Element),
+    AddElement(Element),
+    RemoveElement(usize),
+    RemoveCustomElement(usize),
 }
 
 pub struct TreeNode {
@@ -69,7 +72,7 @@ impl Component for TreeNode {
             }
             Msg::ElementChange(el) => {
                 match &mut self.value {
-                    Value::Object(old_el, _) => {
+                    Value::Object(old_el, _) | Value::ECMAArray(_, old_el, _) => {
                         let index = old_el.iter().position(|e| e.name == el.name);
                         if let Some(index) = index {
                             old_el[index] = el;
@@ -80,6 +83,7 @@ impl Component for TreeNode {
                     }
                 }
 
+                self.emit_change();
                 true
             }
             Msg::CustomElementChange(el) => {
@@ -95,6 +99,7 @@ impl Component for TreeNode {
                     }
                 }
 
+                self.emit_change();
                 true
             }
             Msg::CustomElementChangeStandard(el) => {
@@ -110,13 +115,77 @@ impl Component for TreeNode {
                     }
                 }
 
+                self.emit_change();
+                true
+            }
+            Msg::AddElement(el) => {
+                match &mut self.value {
+                    Value::Object(elements, _) | Value::Custom(_, elements, _) => {
+                        elements.push(el);
+                    }
+                    Value::ECMAArray(_, assoc, size) => {
+                        assoc.push(el);
+                        *size += 1;
+                    }
+                    _ => {
+                        log::warn!("Unable to add element to non-object");
+                        return false;
+                    }
+                }
+
+                self.emit_change();
+                true
+            }
+            // Elements are removed by index, as names can be repeated
+            Msg::RemoveElement(index) => {
+                match &mut self.value {
+                    Value::Object(elements, _) | Value::Custom(_, elements, _) => {
+                        if index < elements.len() {
+                            elements.remove(index);
+                        }
+                    }
+                    Value::ECMAArray(_, assoc, size) => {
+                        if index < assoc.len() {
+                            assoc.remove(index);
+                            *size = size.saturating_sub(1);
+                        }
+                    }
+                    _ => {
+                        log::warn!("Unable to remove element from non-object");
+                        return false;
+                    }
+                }
+
+                self.emit_change();
+                true
+            }
+            Msg::RemoveCustomElement(index) => {
+                match &mut self.value {
+                    Value::Custom(custom, _, _) => {
+                        if index < custom.len() {
+                            custom.remove(index);
+                        }
+                    }
+                    _ => {
+                        log::warn!("Unable to remove element from non-object");
+                        return false;
+                    }
+                }
+
+                self.emit_change();
                 true
             }
         }
     }
 
     fn change(&mut self, props: Self::Properties) -> bool {
-        self.props.neq_assign(props)
+        // Nodes are reused by position, so after a sibling is removed this node may be showing a different value
+        if self.props.neq_assign(props) {
+            self.value = self.props.value.clone();
+            true
+        } else {
+            false
+        }
     }
 
     fn view(&self) -> Html {
@@ -129,6 +198,8 @@ impl Component for TreeNode {
             } else {
                 "icon/folder-plus.svg"
             }
+        } else if TreeNode::is_placeholder(&value) {
+            "icon/file.svg"
         } else {
             "icon/file-text.svg"
         };
@@ -159,6 +230,8 @@ impl Component for TreeNode {
                         callback: callback.clone(),
                         path: path.clone(),
                     }))>{ name }</span>
+                { self.class_badge() }
+                { self.placeholder_editor() }
                 { if self.expanded {
                     self.view_sol_value(Rc::new(self.value.clone()))
                 } else {
@@ -170,6 +243,103 @@ impl Component for TreeNode {
 }
 
 impl TreeNode {
+    /// Pass the current value up to the parent, so that changes to children propagate to the root
+    fn emit_change(&self) {
+        if let Some(x) = &self.props.element_callback {
+            x.emit(Element::new(self.props.name.clone(), self.value.clone()));
+        }
+    }
+
+    /// Pick a name for a new element that doesn't clash with the existing ones
+    fn new_element_name(&self) -> String {
+        let taken = |name: &str| {
+            self.value
+                .sorted_elements()
+                .iter()
+                .any(|e| e.name() == name)
+        };
+
+        let mut name = "newElement".to_string();
+        let mut index = 1;
+        while taken(&name) {
+            name = format!("newElement{}", index);
+            index += 1;
+        }
+        name
+    }
+
+    fn add_button(&self) -> Html {
+        let element = Element::new(self.new_element_name(), Value::Null);
+        html! {
+            <li>
+                <span onclick=self.link.callback(move |_| Msg::AddElement(element.clone())) class="btn btn-primary btn-sm">{"Add"}</span>
+            </li>
+        }
+    }
+
+    /// Values with no contents to show or edit, which would otherwise only be shown by name
+    fn is_placeholder(data: &Value) -> bool {
+        matches!(data, Value::Null | Value::Undefined | Value::Unsupported)
+    }
+
+    /// Show the class of a typed object or the type of a vector, so that they can be told apart from plain objects
+    /// and arrays
+    fn class_badge(&self) -> Html {
+        let mut value = &self.value;
+        while let Value::AMF3(inner) = value {
+            value = inner;
+        }
+
+        let label = match value {
+            Value::Object(_, Some(class_def)) | Value::Custom(_, _, Some(class_def))
+                if !class_def.name.is_empty() =>
+            {
+                class_def.name.clone()
+            }
+            Value::VectorObject(_, type_name, _) => format!("Vector.<{}>", type_name),
+            _ => return html! {},
+        };
+
+        html! {
+            <span class="badge badge-secondary ml-2">{ label }</span>
+        }
+    }
+
+    /// Label a placeholder value and allow it to be replaced by a value of another type,
+    /// so that empty slots can be filled in with objects or arrays
+    fn placeholder_editor(&self) -> Html {
+        let label = match self.value {
+            Value::Null => "null",
+            Value::Undefined => "undefined",
+            Value::Unsupported => "unsupported",
+            _ => return html! {},
+        };
+
+        html! {
+            <>
+                <span class="text-muted ml-2">{ label }</span>
+                <select class="custom-select custom-select-sm ml-2" style="width: auto;" onchange=self.link.callback(|cd| {
+                    if let ChangeData::Select(select) = cd {
+                        Msg::Edited(convert_value(&Value::Null, &select.value()))
+                    } else {
+                        Msg::Edited(Value::Null)
+                    }
+                })>
+                    <option selected=true disabled=true>{"Change type"}</option>
+                    { for VALUE_TYPES.iter().map(|t| html! {
+                        <option value={*t}>{ t }</option>
+                    })}
+                </select>
+            </>
+        }
+    }
+
+    fn remove_button(&self, remove: impl Fn(usize) -> Msg + 'static, index: usize) -> Html {
+        html! {
+            <span onclick=self.link.callback(move |_| remove(index))><img src={"icon/x.svg"} style={"width: 16px; height: 16px;"} class={"ml-2"}/></span>
+        }
+    }
+
     pub fn is_visible(&self) -> bool {
         // Visible if no filter or if we are included in filter, also we must be visible if we have visible children
         let has_visible_children = match &self.props.value {
@@ -207,7 +377,7 @@ impl TreeNode {
 
     pub fn selected(&self) -> bool {
         let selected_path = self.props.selection.clone().map(|s| s.path);
-        selected_path.map_or(false, |tnp| tnp.contains(self.path()))
+        selected_path.is_some_and(|tnp| tnp.contains(self.path()))
     }
 
     pub fn has_children(data: &Value) -> bool {
@@ -236,9 +406,13 @@ impl TreeNode {
             Value::AMF3(e) => self.view_sol_value(e.clone()),
             Value::Object(elements, _class_def) => html! {
                 <ul>
-                    { for elements.iter().map(|e| html! {
-                        <TreeNode element_callback=self.link.callback(|el| Msg::ElementChange(el)) filter=self.props.filter.clone() selection=self.props.selection.clone() parent_path=self.path() name={e.name.clone()} value={e.value.deref().clone()} parent_callback={self.link.callback(|val| Msg::Selection(val))}></TreeNode>
+                    { for elements.iter().enumerate().map(|(i, e)| html! {
+                        <>
+                        <TreeNode element_callback=self.link.callback(|el| Msg::ElementChange(el)) filter=self.props.filter.clone() selection=self.props.selection.clone() parent_path=self.path() name={e.name.to_string()} value={e.value.deref().clone()} parent_callback={self.link.callback(|val| Msg::Selection(val))}></TreeNode>
+                        { self.remove_button(Msg::RemoveElement, i) }
+                        </>
                     })}
+                    { self.add_button() }
                 </ul>
             },
             Value::StrictArray(x) => html! {
@@ -249,9 +423,13 @@ impl TreeNode {
             Value::ECMAArray(dense, assoc, _size) => html! {
                     <ul>
                        { for dense.iter().enumerate().map(|(i, v)| self.view_array_element(i, v))}
-                        { for assoc.iter().map(|e| html! {
-                            <TreeNode filter=self.props.filter.clone() selection=self.props.selection.clone() parent_path=self.path() name={e.name.clone()} value={e.value.deref().clone()} parent_callback={self.link.callback(|val| Msg::Selection(val))}></TreeNode>
+                        { for assoc.iter().enumerate().map(|(i, e)| html! {
+                            <>
+                            <TreeNode element_callback=self.link.callback(|el| Msg::ElementChange(el)) filter=self.props.filter.clone() selection=self.props.selection.clone() parent_path=self.path() name={e.name.to_string()} value={e.value.deref().clone()} parent_callback={self.link.callback(|val| Msg::Selection(val))}></TreeNode>
+                            { self.remove_button(Msg::RemoveElement, i) }
+                            </>
                         })}
+                        { self.add_button() }
                     </ul>
             },
             Value::VectorObject(children, _name, _fixed_len) => html! {
@@ -278,17 +456,24 @@ impl TreeNode {
                     <li>
                         {"Custom elements"}
                         <ul>
-                            { for el.iter().map(|e| html! {
-                                <TreeNode element_callback=self.link.callback(|el| Msg::CustomElementChange(el)) filter=self.props.filter.clone() selection=self.props.selection.clone() parent_path=self.path() name={e.name.clone()} value={e.value.deref().clone()} parent_callback={self.link.callback(|val| Msg::Selection(val))}></TreeNode>
+                            { for el.iter().enumerate().map(|(i, e)| html! {
+                                <>
+                                <TreeNode element_callback=self.link.callback(|el| Msg::CustomElementChange(el)) filter=self.props.filter.clone() selection=self.props.selection.clone() parent_path=self.path() name={e.name.to_string()} value={e.value.deref().clone()} parent_callback={self.link.callback(|val| Msg::Selection(val))}></TreeNode>
+                                { self.remove_button(Msg::RemoveCustomElement, i) }
+                                </>
                             })}
                         </ul>
                     </li>
                     <li>
                         {"Standard elements"}
                         <ul>
-                           { for el2.iter().map(|e| html! {
-                                <TreeNode element_callback=self.link.callback(|el| Msg::CustomElementChangeStandard(el)) filter=self.props.filter.clone() selection=self.props.selection.clone() parent_path=self.path() name={e.name.clone()} value={e.value.deref().clone()} parent_callback={self.link.callback(|val| Msg::Selection(val))}></TreeNode>
+                           { for el2.iter().enumerate().map(|(i, e)| html! {
+                                <>
+                                <TreeNode element_callback=self.link.callback(|el| Msg::CustomElementChangeStandard(el)) filter=self.props.filter.clone() selection=self.props.selection.clone() parent_path=self.path() name={e.name.to_string()} value={e.value.deref().clone()} parent_callback={self.link.callback(|val| Msg::Selection(val))}></TreeNode>
+                                { self.remove_button(Msg::RemoveElement, i) }
+                                </>
                             })}
+                            { self.add_button() }
                         </ul>
                     </li>
                 </ul>
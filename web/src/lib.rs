@@ -4,6 +4,7 @@ use wasm_bindgen::prelude::*;
 use yew::prelude::*;
 
 use flash_lso::types::Value;
+use std::rc::Rc;
 
 pub(crate) mod blob_bindgen;
 pub(crate) mod component_hexview;
@@ -28,19 +29,64 @@ pub struct EditableValue {
     pub path: TreeNodePath,
 }
 
+impl EditableValue {
+    /// Convert the value to the type with the given name from `VALUE_TYPES`, see `convert_value`
+    pub fn converted_to(&self, type_name: &str) -> Value {
+        convert_value(&self.value, type_name)
+    }
+}
+
+/// The types a value can be changed to, by the names shown in the type dropdowns
+pub const VALUE_TYPES: [&str; 7] = [
+    "Null",
+    "Undefined",
+    "Boolean",
+    "Number",
+    "String",
+    "Object",
+    "Array",
+];
+
+/// Convert a value to the type with the given name from `VALUE_TYPES`, keeping what can be kept:
+/// - a value that already has the type is unchanged
+/// - numbers and booleans become their text as a `String`, and strings holding a number become that `Number`
+/// - anything other than a placeholder (null, undefined or unsupported) becomes the only item of an `Array`
+///
+/// Otherwise the value is replaced by the default of the type: false, 0, an empty string, object or array
+pub fn convert_value(value: &Value, type_name: &str) -> Value {
+    match (type_name, value) {
+        ("Undefined", _) => Value::Undefined,
+        ("Boolean", Value::Bool(b)) => Value::Bool(*b),
+        ("Boolean", _) => Value::Bool(false),
+        ("Number", Value::Number(n)) => Value::Number(*n),
+        ("Number", Value::Integer(i)) => Value::Number(f64::from(*i)),
+        ("Number", Value::String(s)) => Value::Number(s.trim().parse().unwrap_or(0.0)),
+        ("Number", _) => Value::Number(0.0),
+        ("String", Value::String(s)) => Value::String(s.clone()),
+        ("String", Value::Number(n)) => Value::String(n.to_string()),
+        ("String", Value::Integer(i)) => Value::String(i.to_string()),
+        ("String", Value::Bool(b)) => Value::String(b.to_string()),
+        ("String", _) => Value::String(String::new()),
+        ("Object", Value::Object(..)) => value.clone(),
+        ("Object", _) => Value::Object(Vec::new(), None),
+        ("Array", Value::ECMAArray(..) | Value::StrictArray(_)) => value.clone(),
+        ("Array", Value::Null | Value::Undefined | Value::Unsupported) => {
+            Value::ECMAArray(Vec::new(), Vec::new(), 0)
+        }
+        ("Array", _) => Value::StrictArray(vec![Rc::new(value.clone())]),
+        _ => Value::Null,
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct TreeNodePath(Vec<String>);
 impl TreeNodePath {
     pub fn root() -> Self {
-        Self {
-            0: vec!["/".to_string()],
-        }
+        Self(vec!["/".to_string()])
     }
 
     pub fn join(&self, child: String) -> Self {
-        Self {
-            0: self.0.iter().chain(&[child]).cloned().collect(),
-        }
+        Self(self.0.iter().chain(&[child]).cloned().collect())
     }
 
     pub fn contains(&self, other: Self) -> bool {